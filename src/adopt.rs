@@ -0,0 +1,135 @@
+//! Turning hand-written scripts in the bins directory into aliases, as done by
+//! `cmdlink adopt`.
+
+use crate::{
+	alias::{AliasBuilder, AliasSpec},
+	Result,
+};
+
+/// Extracts an alias definition from a shell script or batch file, or returns
+/// `None` if the script does more than export variables and run one command.
+///
+/// Comments, shebangs, `@echo off` and `echo.` lines are skipped, a leading
+/// `exec` and trailing `"$@"` or `%*` argument forwarding are stripped, and
+/// `export KEY=value` or `set "KEY=value"` lines become environment variables,
+/// so links written by cmdlink itself are understood too. Variables are
+/// escaped so that they're still expanded by the shell at run time, see
+/// [template](crate::template).
+pub fn parse(name: &str, script: &str) -> Option<Result<AliasSpec>> {
+	let mut env = Vec::new();
+	let mut cmd = None;
+	for line in script.lines().map(str::trim) {
+		let lower = line.to_ascii_lowercase();
+		let skipped = line.is_empty()
+			|| line.starts_with('#')
+			|| line.starts_with("::")
+			|| lower.starts_with("rem ")
+			|| lower == "@echo off"
+			|| lower == "echo.";
+		if skipped {
+			continue;
+		}
+		if lower.starts_with("export ") || lower.starts_with("set ") {
+			env.push(env_assignment(line)?);
+			continue;
+		}
+		// Only scripts running a single command can be turned into an alias.
+		if cmd.replace(command(line)?).is_some() {
+			return None;
+		}
+	}
+
+	let builder = env
+		.into_iter()
+		.fold(AliasBuilder::new(name, cmd?), |builder, (key, value)| builder.env(key, value));
+	Some(builder.build())
+}
+
+/// Strips the `exec` prefix and argument forwarding from a command line,
+/// returning `None` if nothing is left.
+fn command(line: &str) -> Option<String> {
+	let line = line.strip_prefix("exec ").unwrap_or(line).trim_start();
+	let line = ["\"$@\"", "$@", "\"$*\"", "$*", "%*"]
+		.iter()
+		.find_map(|forward| line.strip_suffix(forward))
+		.unwrap_or(line)
+		.trim_end();
+	(!line.is_empty()).then(|| escape(line))
+}
+
+/// Parses an `export KEY=value` or `set "KEY=value"` line, unquoting the
+/// value. Values the shell would expand, such as `"$PATH:~/bin"`, can't be
+/// kept as is and yield `None`.
+fn env_assignment(line: &str) -> Option<(String, String)> {
+	let assignment = match line.strip_prefix("export ") {
+		Some(rest) => rest.trim(),
+		None => {
+			let rest = line.get(..4).filter(|set| set.eq_ignore_ascii_case("set "))?;
+			line[rest.len()..].trim().strip_prefix('"')?.strip_suffix('"')?
+		},
+	};
+	let (key, value) = assignment.split_once('=')?;
+	let value = value
+		.strip_prefix('\'')
+		.and_then(|value| value.strip_suffix('\''))
+		.map(|value| value.replace(r"'\''", "'"))
+		.or_else(|| {
+			let value = value
+				.strip_prefix('"')
+				.and_then(|value| value.strip_suffix('"'))
+				.unwrap_or(value);
+			(!value.contains(['$', '`', '%'])).then(|| value.to_string())
+		})?;
+	Some((key.to_string(), escape(&value)))
+}
+
+/// Escapes `${VAR}` references so that cmdlink leaves them to the shell.
+fn escape(text: &str) -> String { text.replace("${", "$${") }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// The command and environment of the alias parsed from the script.
+	fn parsed(script: &str) -> Option<(String, Vec<(String, String)>)> {
+		let spec = parse("tool", script)?.unwrap();
+		Some((spec.cmd, spec.env.into_iter().collect()))
+	}
+
+	/// Owned key-value pairs.
+	fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+		pairs
+			.iter()
+			.map(|(key, value)| (key.to_string(), value.to_string()))
+			.collect()
+	}
+
+	#[test]
+	fn shell_scripts_are_adopted() {
+		let script =
+			"#!/bin/sh\n# Runs the tool.\nexport MODE=fast\nexport NAME='it'\\''s'\n\nexec tool --verbose \"$@\"\n";
+		assert_eq!(
+			parsed(script),
+			Some(("tool --verbose".to_string(), env(&[("MODE", "fast"), ("NAME", "it's")])))
+		);
+		assert_eq!(parsed("echo ${HOME} $@"), Some(("echo $${HOME}".to_string(), Vec::new())));
+	}
+
+	#[test]
+	fn batch_files_are_adopted() {
+		let script = "@echo off\r\nrem Runs the tool.\r\n:: quietly\r\nset \"MODE=fast\"\r\ntool.exe --verbose %*\r\n";
+		assert_eq!(
+			parsed(script),
+			Some(("tool.exe --verbose".to_string(), env(&[("MODE", "fast")])))
+		);
+	}
+
+	#[test]
+	fn scripts_doing_more_are_left_alone() {
+		assert_eq!(parsed("#!/bin/sh\ncd /tmp\ntool\n"), None);
+		assert_eq!(parsed("#!/bin/sh\nexport PATH=\"$PATH:/opt/bin\"\ntool\n"), None);
+		assert_eq!(parsed("set MODE=fast\ntool\n"), None);
+		assert_eq!(parsed("#!/bin/sh\n# nothing to run\n"), None);
+		assert_eq!(parsed("exec \"$@\"\n"), None);
+	}
+}
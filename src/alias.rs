@@ -0,0 +1,599 @@
+use std::collections::BTreeMap;
+
+use crate::{
+	config::{AliasValues, CaptureMode, MinVersion, PlatformCommands},
+	error::Error,
+	release::Release,
+	secret::SecretRef,
+	settings::WindowsExtension,
+	version::Requirement,
+	Result,
+};
+
+/// A fully specified alias definition, ready to be inserted into a
+/// [Config](crate::Config). Created through an [AliasBuilder].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AliasSpec {
+	/// The name of the alias.
+	pub name: String,
+	/// The command to run in place of the alias, empty if it opens a `url`,
+	/// jumps to a `dir` or runs a `release`.
+	pub cmd: String,
+	/// A web page opened in the browser in place of running a command.
+	pub url: Option<String>,
+	/// A directory the shell changes to in place of running a command.
+	pub dir: Option<String>,
+	/// A command run in the shell after changing to `dir`.
+	pub activate: Option<String>,
+	/// A GitHub release, as `owner/repo@tag`, whose binary runs in place of a
+	/// command.
+	pub release: Option<String>,
+	/// The name of the release asset to download, with `*` wildcards.
+	pub asset: Option<String>,
+	/// The name of the binary in the release asset.
+	pub bin: Option<String>,
+	/// The SHA-256 checksum the release asset is verified against.
+	pub sha256: Option<String>,
+	/// An optional description for the alias.
+	pub description: Option<String>,
+	/// A longer explanation of the alias.
+	pub long_description: Option<String>,
+	/// Example invocations of the alias.
+	pub examples: Vec<String>,
+	/// The words offered by tab completion for each argument position.
+	pub completions: BTreeMap<String, Vec<String>>,
+	/// Tags used to group and filter aliases.
+	pub tags: Vec<String>,
+	/// The group the alias inherits defaults from.
+	pub group: Option<String>,
+	/// Environment variables exported before the command is run.
+	pub env: BTreeMap<String, String>,
+	/// Environment variables retrieved from the OS keychain when the alias is
+	/// invoked.
+	pub secrets: BTreeMap<String, String>,
+	/// A file of `KEY=VALUE` lines loaded when the alias is invoked.
+	pub env_file: Option<String>,
+	/// The directory the command runs in.
+	pub cwd: Option<String>,
+	/// The shell the command runs through on Unix.
+	pub shell: Option<String>,
+	/// The version of the tool the command needs, checked before it runs.
+	pub min_version: Option<MinVersion>,
+	/// Whether or not the link passes its arguments on to the command, `true`
+	/// when unset.
+	pub forward_args: Option<bool>,
+	/// A file the output of the command is copied to.
+	pub capture: Option<String>,
+	/// How the `capture` file is written.
+	pub capture_mode: Option<CaptureMode>,
+	/// An optional `nice` value the command is run with.
+	pub nice: Option<i8>,
+	/// Runs the command at low CPU and IO priority.
+	pub low_priority: bool,
+	/// Launches the command without waiting for it on Windows.
+	pub gui: bool,
+	/// Registers the link under `App Paths` on Windows.
+	pub app_path: bool,
+	/// Makes the link a symlink to the program the command runs on Unix, when
+	/// nothing needs wrapping.
+	pub symlink: bool,
+	/// Platform specific commands used in place of `cmd`.
+	pub platform: PlatformCommands,
+	/// The extension of the link file on Windows, overriding the
+	/// `windows_extension` setting.
+	pub windows_extension: Option<WindowsExtension>,
+}
+
+impl AliasSpec {
+	/// Creates a spec from the values stored in a config file.
+	pub(crate) fn from_values(name: String, values: &AliasValues) -> Self {
+		AliasSpec {
+			name,
+			cmd: values.cmd.clone(),
+			url: values.url.clone(),
+			dir: values.dir.clone(),
+			activate: values.activate.clone(),
+			release: values.release.clone(),
+			asset: values.asset.clone(),
+			bin: values.bin.clone(),
+			sha256: values.sha256.clone(),
+			description: values.description.clone(),
+			tags: values.tags.clone(),
+			group: values.group.clone(),
+			long_description: values.long_description.clone(),
+			examples: values.examples.clone(),
+			completions: values.completions.clone(),
+			env: values.env.clone(),
+			secrets: values.secrets.clone(),
+			env_file: values.env_file.clone(),
+			cwd: values.cwd.clone(),
+			shell: values.shell.clone(),
+			min_version: values.min_version.clone(),
+			forward_args: values.forward_args,
+			capture: values.capture.clone(),
+			capture_mode: values.capture_mode,
+			nice: values.nice,
+			low_priority: values.low_priority,
+			gui: values.gui,
+			app_path: values.app_path,
+			symlink: values.symlink,
+			platform: values.platform.clone(),
+			windows_extension: values.windows_extension,
+		}
+	}
+
+	/// Validates the alias name, command, url, dir or release, environment
+	/// variable names, completion words, working directory, shell, version
+	/// requirement and capture file.
+	pub fn validate(&self) -> Result<()> {
+		validate_name(&self.name)?;
+		let invalid = |reason| Err(Error::InvalidAlias(self.name.clone(), reason));
+		match [&self.url, &self.dir, &self.release]
+			.iter()
+			.filter(|kind| kind.is_some())
+			.count()
+		{
+			0 if self.cmd.trim().is_empty() => return invalid("command cannot be empty"),
+			1 if !self.cmd.is_empty() => {
+				return invalid(
+					"an alias opening a url, jumping to a dir or running a release can't also run a command",
+				);
+			},
+			2.. => return invalid("an alias can only open a url, jump to a dir or run a release"),
+			_ => {},
+		}
+		if self.url.as_ref().is_some_and(|url| !url.contains("://")) {
+			return invalid("url must start with a scheme, such as https://");
+		}
+		if self.dir.as_ref().is_some_and(|dir| dir.trim().is_empty()) {
+			return invalid("dir cannot be empty");
+		}
+		if self.activate.is_some() && self.dir.is_none() {
+			return invalid("activate requires a dir to jump to");
+		}
+		if self
+			.release
+			.as_ref()
+			.is_some_and(|release| Release::parse(release).is_none())
+		{
+			return invalid("release must be written as owner/repo@tag");
+		}
+		if (self.asset.is_some() || self.bin.is_some() || self.sha256.is_some()) && self.release.is_none() {
+			return invalid("asset, bin and sha256 require a release");
+		}
+		if self
+			.sha256
+			.as_ref()
+			.is_some_and(|sha256| sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()))
+		{
+			return invalid("sha256 must be 64 hexadecimal digits");
+		}
+		if let Some(key) = self
+			.env
+			.keys()
+			.chain(self.secrets.keys())
+			.find(|k| !is_valid_env_key(k))
+		{
+			return Err(Error::InvalidEnvKey(self.name.clone(), key.clone()));
+		}
+		for reference in self.secrets.values() {
+			SecretRef::parse(reference)?;
+		}
+		if self
+			.completions
+			.keys()
+			.any(|position| !position.parse::<usize>().is_ok_and(|position| position > 0))
+		{
+			return Err(Error::InvalidAlias(
+				self.name.clone(),
+				"completion positions must be numbers starting at 1",
+			));
+		}
+		if self
+			.completions
+			.values()
+			.flatten()
+			.any(|word| word.is_empty() || word.contains(char::is_whitespace))
+		{
+			return Err(Error::InvalidAlias(
+				self.name.clone(),
+				"completion words cannot be empty or contain whitespace",
+			));
+		}
+		if self.cwd.as_ref().is_some_and(|cwd| cwd.trim().is_empty()) {
+			return Err(Error::InvalidAlias(self.name.clone(), "working directory cannot be empty"));
+		}
+		if self.shell.as_ref().is_some_and(|shell| shell.trim().is_empty()) {
+			return Err(Error::InvalidAlias(self.name.clone(), "shell cannot be empty"));
+		}
+		if let Some(min_version) = &self.min_version {
+			if self.url.is_some() || self.dir.is_some() {
+				return invalid("min_version only applies to aliases running a command or release");
+			}
+			if min_version.cmd.trim().is_empty() {
+				return invalid("min_version cmd cannot be empty");
+			}
+			if Requirement::parse(&min_version.require).is_none() {
+				return invalid("min_version require must be comparisons such as >=20 or >=1.2, <2");
+			}
+		}
+		match &self.capture {
+			Some(path) if path.trim().is_empty() => {
+				return Err(Error::InvalidAlias(self.name.clone(), "capture file cannot be empty"));
+			},
+			None if self.capture_mode.is_some() => {
+				return Err(Error::InvalidAlias(self.name.clone(), "capture_mode requires a capture file"));
+			},
+			_ => {},
+		}
+		Ok(())
+	}
+
+	/// The definition as it would be written to the `[aliases]` table of the
+	/// config file, e.g. to compare two definitions of an alias.
+	pub fn to_toml(&self) -> Result<String> { Ok(toml::to_string(&self.clone().into_values().1)?) }
+
+	/// Converts the spec into the values stored in the config file, without a
+	/// link.
+	pub(crate) fn into_values(self) -> (String, AliasValues) {
+		let values = AliasValues {
+			link: Default::default(),
+			description: self.description,
+			cmd: self.cmd,
+			url: self.url,
+			dir: self.dir,
+			activate: self.activate,
+			release: self.release,
+			asset: self.asset,
+			bin: self.bin,
+			sha256: self.sha256,
+			tags: self.tags,
+			group: self.group,
+			long_description: self.long_description,
+			examples: self.examples,
+			completions: self.completions,
+			env: self.env,
+			secrets: self.secrets,
+			env_file: self.env_file,
+			cwd: self.cwd,
+			shell: self.shell,
+			min_version: self.min_version,
+			forward_args: self.forward_args,
+			capture: self.capture,
+			capture_mode: self.capture_mode,
+			nice: self.nice,
+			low_priority: self.low_priority,
+			gui: self.gui,
+			app_path: self.app_path,
+			symlink: self.symlink,
+			platform: self.platform,
+			windows_extension: self.windows_extension,
+			disabled: false,
+			created: None,
+			updated: None,
+		};
+		(self.name, values)
+	}
+}
+
+/// Builder for [AliasSpec]s.
+///
+/// ```
+/// use cmdlink::alias::AliasBuilder;
+///
+/// let spec = AliasBuilder::new("gs", "git status")
+///     .description("Short git status")
+///     .tag("git")
+///     .env("GIT_PAGER", "cat")
+///     .build()
+///     .unwrap();
+/// assert_eq!(spec.tags, ["git"]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct AliasBuilder {
+	spec: AliasSpec,
+}
+
+impl AliasBuilder {
+	/// Starts building an alias with the given name and command.
+	pub fn new(name: impl Into<String>, cmd: impl Into<String>) -> Self {
+		AliasBuilder {
+			spec: AliasSpec {
+				name: name.into(),
+				cmd: cmd.into(),
+				url: None,
+				dir: None,
+				activate: None,
+				release: None,
+				asset: None,
+				bin: None,
+				sha256: None,
+				description: None,
+				tags: Vec::new(),
+				group: None,
+				long_description: None,
+				examples: Vec::new(),
+				completions: BTreeMap::new(),
+				env: BTreeMap::new(),
+				secrets: BTreeMap::new(),
+				env_file: None,
+				cwd: None,
+				shell: None,
+				min_version: None,
+				forward_args: None,
+				capture: None,
+				capture_mode: None,
+				nice: None,
+				low_priority: false,
+				gui: false,
+				app_path: false,
+				symlink: false,
+				platform: PlatformCommands::default(),
+				windows_extension: None,
+			},
+		}
+	}
+
+	/// Starts building an alias with the given name that opens a web page in
+	/// the browser instead of running a command.
+	pub fn url(name: impl Into<String>, url: impl Into<String>) -> Self {
+		let mut builder = AliasBuilder::new(name, "");
+		builder.spec.url = Some(url.into());
+		builder
+	}
+
+	/// Starts building an alias with the given name that changes the shell to
+	/// a directory instead of running a command, see
+	/// [AliasValues::dir](crate::config::AliasValues::dir).
+	pub fn dir(name: impl Into<String>, dir: impl Into<String>) -> Self {
+		let mut builder = AliasBuilder::new(name, "");
+		builder.spec.dir = Some(dir.into());
+		builder
+	}
+
+	/// Starts building an alias with the given name that runs the binary of a
+	/// GitHub release, written as `owner/repo@tag`, instead of a command. See
+	/// [crate::release].
+	pub fn release(name: impl Into<String>, release: impl Into<String>) -> Self {
+		let mut builder = AliasBuilder::new(name, "");
+		builder.spec.release = Some(release.into());
+		builder
+	}
+
+	/// Sets the name of the release asset to download, with `*` wildcards,
+	/// when it can't be recognized by the current platform.
+	pub fn asset(mut self, asset: impl Into<String>) -> Self {
+		self.spec.asset = Some(asset.into());
+		self
+	}
+
+	/// Sets the name of the binary in the release asset, the repository name
+	/// by default.
+	pub fn bin(mut self, bin: impl Into<String>) -> Self {
+		self.spec.bin = Some(bin.into());
+		self
+	}
+
+	/// Sets the SHA-256 checksum the release asset is verified against.
+	pub fn sha256(mut self, sha256: impl Into<String>) -> Self {
+		self.spec.sha256 = Some(sha256.into());
+		self
+	}
+
+	/// Sets the command run in the shell after changing to the alias' `dir`,
+	/// such as `source .venv/bin/activate`.
+	pub fn activate(mut self, activate: impl Into<String>) -> Self {
+		self.spec.activate = Some(activate.into());
+		self
+	}
+
+	/// Sets the description of the alias.
+	pub fn description(mut self, description: impl Into<String>) -> Self {
+		self.spec.description = Some(description.into());
+		self
+	}
+
+	/// Sets the description of the alias if one is given.
+	pub fn maybe_description(mut self, description: Option<String>) -> Self {
+		self.spec.description = description;
+		self
+	}
+
+	/// Sets a longer explanation of the alias.
+	pub fn long_description(mut self, long_description: impl Into<String>) -> Self {
+		self.spec.long_description = Some(long_description.into());
+		self
+	}
+
+	/// Adds an example invocation of the alias.
+	pub fn example(mut self, example: impl Into<String>) -> Self {
+		self.spec.examples.push(example.into());
+		self
+	}
+
+	/// Sets the words offered by tab completion for an argument position,
+	/// starting at `1`.
+	pub fn completions<I, T>(mut self, position: usize, words: I) -> Self
+	where
+		I: IntoIterator<Item = T>,
+		T: Into<String>,
+	{
+		self.spec
+			.completions
+			.insert(position.to_string(), words.into_iter().map(Into::into).collect());
+		self
+	}
+
+	/// Adds a tag to the alias.
+	pub fn tag(mut self, tag: impl Into<String>) -> Self {
+		let tag = tag.into();
+		if !self.spec.tags.contains(&tag) {
+			self.spec.tags.push(tag);
+		}
+		self
+	}
+
+	/// Adds multiple tags to the alias.
+	pub fn tags<I, T>(self, tags: I) -> Self
+	where
+		I: IntoIterator<Item = T>,
+		T: Into<String>,
+	{
+		tags.into_iter().fold(self, |builder, tag| builder.tag(tag))
+	}
+
+	/// Sets an environment variable to be exported before the command runs.
+	pub fn env(mut self, key: impl Into<String>, value: impl Into<String>) -> Self {
+		self.spec.env.insert(key.into(), value.into());
+		self
+	}
+
+	/// Sets an environment variable to be retrieved from the OS keychain when
+	/// the alias is invoked, using a `keyring:<service>/<account>` reference.
+	pub fn secret(mut self, key: impl Into<String>, reference: impl Into<String>) -> Self {
+		self.spec.secrets.insert(key.into(), reference.into());
+		self
+	}
+
+	/// Sets a file of `KEY=VALUE` lines to be loaded when the alias is invoked.
+	pub fn env_file(mut self, path: impl Into<String>) -> Self {
+		self.spec.env_file = Some(path.into());
+		self
+	}
+
+	/// Makes the alias inherit the defaults of a group, see
+	/// [Group](crate::config::Group).
+	pub fn group(mut self, group: impl Into<String>) -> Self {
+		self.spec.group = Some(group.into());
+		self
+	}
+
+	/// Sets the directory the command runs in.
+	pub fn cwd(mut self, path: impl Into<String>) -> Self {
+		self.spec.cwd = Some(path.into());
+		self
+	}
+
+	/// Requires a version of the tool the command runs, printed by `cmd` and
+	/// matching `require`, such as `>=20`, which the link checks before running
+	/// the command.
+	pub fn min_version(mut self, cmd: impl Into<String>, require: impl Into<String>) -> Self {
+		self.spec.min_version = Some(MinVersion {
+			cmd: cmd.into(),
+			require: require.into(),
+		});
+		self
+	}
+
+	/// Sets the shell the command runs through on Unix, such as `bash`.
+	pub fn shell(mut self, shell: impl Into<String>) -> Self {
+		self.spec.shell = Some(shell.into());
+		self
+	}
+
+	/// Sets whether or not the link passes its arguments on to the command.
+	pub fn forward_args(mut self, forward_args: bool) -> Self {
+		self.spec.forward_args = Some(forward_args);
+		self
+	}
+
+	/// Sets a file the output of the command is copied to.
+	pub fn capture(mut self, path: impl Into<String>) -> Self {
+		self.spec.capture = Some(path.into());
+		self
+	}
+
+	/// Sets how the capture file is written, see [CaptureMode].
+	pub fn capture_mode(mut self, mode: CaptureMode) -> Self {
+		self.spec.capture_mode = Some(mode);
+		self
+	}
+
+	/// Sets the `nice` value the command is run with.
+	pub fn nice(mut self, nice: i8) -> Self {
+		self.spec.nice = Some(nice);
+		self
+	}
+
+	/// Marks the command to be run at low priority.
+	pub fn low_priority(mut self, low_priority: bool) -> Self {
+		self.spec.low_priority = low_priority;
+		self
+	}
+
+	/// Marks the command as a GUI app, launched without waiting for it on
+	/// Windows.
+	pub fn gui(mut self, gui: bool) -> Self {
+		self.spec.gui = gui;
+		self
+	}
+
+	/// Registers the link under `App Paths` on Windows, so that the alias can
+	/// be launched from the Run dialog.
+	pub fn app_path(mut self, app_path: bool) -> Self {
+		self.spec.app_path = app_path;
+		self
+	}
+
+	/// Makes the link a symlink to the program the command runs on Unix, if
+	/// the alias needs nothing a script would wrap the command with.
+	pub fn symlink(mut self, symlink: bool) -> Self {
+		self.spec.symlink = symlink;
+		self
+	}
+
+	/// Sets the platform specific command variants.
+	pub fn platform(mut self, platform: PlatformCommands) -> Self {
+		self.spec.platform = platform;
+		self
+	}
+
+	/// Sets the extension of the link file on Windows.
+	pub fn windows_extension(mut self, extension: WindowsExtension) -> Self {
+		self.spec.windows_extension = Some(extension);
+		self
+	}
+
+	/// Validates and returns the finished [AliasSpec].
+	pub fn build(self) -> Result<AliasSpec> {
+		self.spec.validate()?;
+		Ok(self.spec)
+	}
+}
+
+/// The result of an [upsert](crate::Config::upsert) operation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UpsertOutcome {
+	/// The alias did not exist and was created.
+	Created,
+	/// The alias existed with a different definition and was updated.
+	Updated,
+	/// The alias already existed with an identical definition.
+	Unchanged,
+}
+
+/// Validates that an alias name can be used as a link file name.
+pub(crate) fn validate_name(name: &str) -> Result<()> {
+	let reason = if name.is_empty() {
+		"name cannot be empty"
+	} else if name
+		.chars()
+		.any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+	{
+		"name contains characters that are not allowed in file names"
+	} else if name.chars().any(char::is_whitespace) {
+		"name cannot contain whitespace"
+	} else if name.starts_with('.') || name.starts_with('-') {
+		"name cannot start with '.' or '-'"
+	} else {
+		return Ok(());
+	};
+	Err(Error::InvalidAlias(name.to_string(), reason))
+}
+
+/// Whether or not the key is a portable environment variable name.
+pub(crate) fn is_valid_env_key(key: &str) -> bool {
+	let mut chars = key.chars();
+	matches!(chars.next(), Some(c) if c.is_ascii_alphabetic() || c == '_')
+		&& chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
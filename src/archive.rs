@@ -0,0 +1,191 @@
+//! Self-contained bundles of the config and links, as written by
+//! `cmdlink export --archive`, for setting up aliases on machines without
+//! network access.
+//!
+//! The bundle is a zip archive holding `cmdlink/config.toml`, the generated
+//! links under `cmdlink/bins/` and an install script for the current platform,
+//! `install.sh` or `install.cmd`. Entries are stored uncompressed, as configs
+//! and links are small.
+
+use std::path::{Path, PathBuf};
+
+use crate::{error::Error, platform_binary::Link, Config, Result};
+
+/// The script installing the bundle into `~/.cmdlink`. Links are regenerated
+/// if cmdlink is installed, as the bundled ones contain paths of the machine
+/// they were exported on.
+#[cfg(not(target_os = "windows"))]
+const INSTALL_SCRIPT: (&str, &str) = (
+	"install.sh",
+	r#"#!/bin/sh
+# Installs the bundled cmdlink aliases into ~/.cmdlink.
+set -e
+here="$(cd "$(dirname "$0")" && pwd)"
+home="$HOME/.cmdlink"
+if [ -e "$home/config.toml" ]; then
+	echo "$home already has a config, merge the bundled one with: cmdlink merge '$here/cmdlink/config.toml'" >&2
+	exit 1
+fi
+mkdir -p "$home/bins"
+cp "$here/cmdlink/config.toml" "$home/config.toml"
+for link in "$here/cmdlink/bins/"*; do
+	[ -e "$link" ] || continue
+	cp "$link" "$home/bins/"
+	chmod +x "$home/bins/$(basename "$link")"
+done
+if command -v cmdlink >/dev/null 2>&1; then
+	exec cmdlink refresh --force
+fi
+echo "Installed the aliases into $home, add $home/bins to your PATH to use them."
+"#,
+);
+
+/// The script installing the bundle into `%USERPROFILE%\.cmdlink`. Links are
+/// regenerated if cmdlink is installed, as the bundled ones contain paths of
+/// the machine they were exported on.
+#[cfg(target_os = "windows")]
+const INSTALL_SCRIPT: (&str, &str) = (
+	"install.cmd",
+	r#"@echo off
+rem Installs the bundled cmdlink aliases into %USERPROFILE%\.cmdlink.
+setlocal
+set "home=%USERPROFILE%\.cmdlink"
+if exist "%home%\config.toml" (
+	echo %home% already has a config, merge the bundled one with: cmdlink merge "%~dp0cmdlink\config.toml" 1>&2
+	exit /b 1
+)
+mkdir "%home%\bins" 2>nul
+copy /y "%~dp0cmdlink\config.toml" "%home%\config.toml" >nul || exit /b 1
+if exist "%~dp0cmdlink\bins\*" copy /y "%~dp0cmdlink\bins\*" "%home%\bins\" >nul || exit /b 1
+where cmdlink >nul 2>nul && (cmdlink refresh --force & exit /b)
+echo Installed the aliases into %home%, add %home%\bins to your PATH to use them.
+"#,
+);
+
+/// Writes the config, the links of all aliases and an install script into a
+/// zip archive at `path`, returning the number of bundled links.
+pub fn export(cfg: &Config, path: &Path) -> Result<usize> {
+	let fail = |e: std::io::Error| Error::Export(path.to_path_buf(), e.to_string());
+
+	let mut zip = ZipWriter::default();
+	zip.add("cmdlink/config.toml", cfg.to_canonical()?.as_bytes(), false);
+	let mut links: Vec<(PathBuf, Vec<u8>)> = Vec::new();
+	for (alias, values) in cfg.aliases() {
+		let link = values.link(alias, cfg.links());
+		let path = link.file_path().to_path_buf();
+		if !path.is_file() {
+			continue;
+		}
+		// Symlinks point to programs of this machine, so the script they stand
+		// in for is bundled instead.
+		let contents = match path.is_symlink() {
+			true => link.contents()?.into_bytes(),
+			false => std::fs::read(&path).map_err(fail)?,
+		};
+		links.push((path, contents));
+	}
+	links.sort();
+	for (link, contents) in &links {
+		let name = link.file_name().unwrap_or_default().to_string_lossy();
+		zip.add(&format!("cmdlink/bins/{name}"), contents, true);
+	}
+	let (script_name, script) = INSTALL_SCRIPT;
+	zip.add(script_name, script.as_bytes(), true);
+
+	if let Some(parent) = path.parent().filter(|parent| !parent.as_os_str().is_empty()) {
+		std::fs::create_dir_all(parent).map_err(fail)?;
+	}
+	std::fs::write(path, zip.finish()).map_err(fail)?;
+	Ok(links.len())
+}
+
+/// The DOS date of the entries, 1980-01-01, the earliest a zip archive can
+/// record. Entries aren't dated, so that exporting the same aliases twice
+/// gives the same archive.
+const DOS_DATE: u16 = (1 << 5) | 1;
+
+/// Writes a zip archive with uncompressed entries in memory.
+#[derive(Default)]
+struct ZipWriter {
+	/// The local headers and contents of the entries written so far.
+	entries: Vec<u8>,
+	/// The central directory records of the entries written so far.
+	directory: Vec<u8>,
+	/// The number of entries written so far.
+	count: u16,
+}
+
+impl ZipWriter {
+	/// Adds a file, marked executable for the unzip tools that restore Unix
+	/// permissions.
+	fn add(&mut self, name: &str, contents: &[u8], executable: bool) {
+		let offset = self.entries.len() as u32;
+		let crc = crc32(contents);
+		let size = contents.len() as u32;
+		let mode: u32 = if executable { 0o100755 } else { 0o100644 };
+
+		// Version 2.0, with UTF-8 names, stored without compression.
+		let common = |out: &mut Vec<u8>| {
+			out.extend(20u16.to_le_bytes());
+			out.extend(0x0800u16.to_le_bytes());
+			out.extend(0u16.to_le_bytes());
+			out.extend(0u16.to_le_bytes());
+			out.extend(DOS_DATE.to_le_bytes());
+			out.extend(crc.to_le_bytes());
+			out.extend(size.to_le_bytes());
+			out.extend(size.to_le_bytes());
+			out.extend((name.len() as u16).to_le_bytes());
+			out.extend(0u16.to_le_bytes());
+		};
+
+		self.entries.extend(0x04034b50u32.to_le_bytes());
+		common(&mut self.entries);
+		self.entries.extend(name.as_bytes());
+		self.entries.extend(contents);
+
+		self.directory.extend(0x02014b50u32.to_le_bytes());
+		// Made by Unix, so that the permissions in the external attributes
+		// apply.
+		self.directory.extend((3u16 << 8 | 20).to_le_bytes());
+		common(&mut self.directory);
+		self.directory.extend(0u16.to_le_bytes());
+		self.directory.extend(0u16.to_le_bytes());
+		self.directory.extend(0u16.to_le_bytes());
+		self.directory.extend((mode << 16).to_le_bytes());
+		self.directory.extend(offset.to_le_bytes());
+		self.directory.extend(name.as_bytes());
+		self.count += 1;
+	}
+
+	/// Appends the central directory, returning the archive.
+	fn finish(mut self) -> Vec<u8> {
+		let directory_offset = self.entries.len() as u32;
+		let directory_size = self.directory.len() as u32;
+		self.entries.append(&mut self.directory);
+		self.entries.extend(0x06054b50u32.to_le_bytes());
+		self.entries.extend(0u16.to_le_bytes());
+		self.entries.extend(0u16.to_le_bytes());
+		self.entries.extend(self.count.to_le_bytes());
+		self.entries.extend(self.count.to_le_bytes());
+		self.entries.extend(directory_size.to_le_bytes());
+		self.entries.extend(directory_offset.to_le_bytes());
+		self.entries.extend(0u16.to_le_bytes());
+		self.entries
+	}
+}
+
+/// The CRC-32 checksum zip archives use to verify entries.
+fn crc32(data: &[u8]) -> u32 {
+	let mut crc = !0u32;
+	for &byte in data {
+		crc ^= u32::from(byte);
+		for _ in 0..8 {
+			crc = if crc & 1 == 1 {
+				(crc >> 1) ^ 0xEDB8_8320
+			} else {
+				crc >> 1
+			};
+		}
+	}
+	!crc
+}
@@ -0,0 +1,165 @@
+//! Exporting the aliases as doskey macros for cmd.exe, loaded into every
+//! interactive cmd session through its AutoRun registry value.
+
+use std::{fmt::Write, path::PathBuf};
+
+use crate::{config::AliasValues, error::Error, paths::CmdlinkPaths, platform_binary::Link, Config, Result};
+
+/// The file name of the generated script in the project directory.
+pub const SCRIPT_NAME: &str = "autorun.cmd";
+
+/// The path of the generated script in the given project directory.
+pub fn script_path(paths: &CmdlinkPaths) -> PathBuf { paths.root().join(SCRIPT_NAME) }
+
+/// Renders a batch script defining a doskey macro for every enabled alias.
+///
+/// Macros run the alias command directly, so that aliases behave like shell
+/// aliases. Aliases that need more than a plain command, such as environment
+/// variables, a working directory, a version check, a priority, output capture,
+/// exec hooks, run recording, references to other aliases, a URL to open, a
+/// directory to jump to or a release to run, run their link instead, as do
+/// commands using `|`, `<`, `>` or `&`, which doskey can't store verbatim.
+pub fn script(cfg: &Config) -> Result<String> {
+	let mut aliases: Vec<(&str, &AliasValues)> = cfg.aliases().filter(|(_, values)| !values.disabled).collect();
+	aliases.sort_by_key(|(alias, _)| *alias);
+
+	let mut script = String::from("@echo off\nrem Generated by cmdlink, changes are overwritten.\n");
+	for (alias, values) in aliases {
+		let link = values.link(alias, cfg.links());
+		let cmd = link.expand(link.cmd())?;
+		let needs_link = values.url.is_some()
+			|| values.dir.is_some()
+			|| values.release.is_some()
+			|| !values.env.is_empty()
+			|| !values.secrets.is_empty()
+			|| link.inherited_env().is_some_and(|env| !env.is_empty())
+			|| values.env_file.is_some()
+			|| link.cwd().is_some()
+			|| values.min_version.is_some()
+			|| values.capture.is_some()
+			|| values.niceness().is_some()
+			|| values.gui
+			|| link.pre_exec().is_some()
+			|| link.post_exec().is_some()
+			|| link.records_runs()
+			|| !link.chained().is_empty()
+			|| cmd.contains(['|', '<', '>', '&']);
+		let target = if needs_link {
+			format!("\"{}\"", link.file_path().display())
+		} else {
+			// `$` starts doskey's own placeholders, and `%` would be expanded
+			// when the script runs rather than when the macro does.
+			cmd.replace('$', "$$").replace('%', "%%")
+		};
+		let _ = writeln!(script, "doskey {alias}={target} $*");
+	}
+	Ok(script)
+}
+
+/// Writes the script for the config to [script_path].
+pub fn write(cfg: &Config) -> Result<PathBuf> {
+	let path = script_path(cfg.paths());
+	std::fs::write(&path, script(cfg)?).map_err(|e| Error::AutoRunScript(path.clone(), e))?;
+	Ok(path)
+}
+
+/// Rewrites the script if it was written before, so that the macros stay in
+/// line with the config.
+pub fn refresh(cfg: &Config) -> Result<()> {
+	if script_path(cfg.paths()).exists() {
+		write(cfg)?;
+	}
+	Ok(())
+}
+
+/// Registers the script of the given project directory under the `AutoRun`
+/// value of `HKCU\Software\Microsoft\Command Processor`, keeping any commands
+/// already there. Returns whether or not it had to be added.
+pub fn register(paths: &CmdlinkPaths) -> Result<bool> {
+	#[cfg(target_os = "windows")]
+	{
+		let command = autorun_command(paths);
+		update_value(|current| append_command(current, &command))
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		let _ = paths;
+		Err(Error::AutoRun("cmd.exe AutoRun is only available on Windows".to_string()))
+	}
+}
+
+/// Removes the script from the `AutoRun` value and deletes it, leaving any
+/// other commands in place. The value is deleted if nothing else is left.
+/// Returns whether or not the script was registered.
+pub fn unregister(paths: &CmdlinkPaths) -> Result<bool> {
+	#[cfg(target_os = "windows")]
+	let registered = {
+		let command = autorun_command(paths);
+		update_value(|current| remove_command(current, &command))?
+	};
+	#[cfg(not(target_os = "windows"))]
+	let registered = false;
+
+	let path = script_path(paths);
+	match std::fs::remove_file(&path) {
+		Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::AutoRunScript(path, e)),
+		_ => Ok(registered),
+	}
+}
+
+#[cfg(target_os = "windows")]
+/// The command added to the `AutoRun` value, which skips the script if it was
+/// deleted by hand.
+fn autorun_command(paths: &CmdlinkPaths) -> String {
+	format!("if exist \"{0}\" call \"{0}\"", script_path(paths).display())
+}
+
+#[cfg(target_os = "windows")]
+/// Updates the `AutoRun` value with the given function, which returns `None`
+/// if nothing has to change. An empty value is deleted.
+fn update_value(update: impl FnOnce(&str) -> Option<String>) -> Result<bool> {
+	use windows_registry::CURRENT_USER;
+
+	let to_error = |e: windows_result::Error| Error::AutoRun(e.message());
+	let processor = CURRENT_USER
+		.create(r"Software\Microsoft\Command Processor")
+		.map_err(to_error)?;
+	let current = processor.get_string("AutoRun").unwrap_or_default();
+	let Some(updated) = update(&current) else {
+		return Ok(false);
+	};
+
+	if updated.is_empty() {
+		processor.remove_value("AutoRun").map_err(to_error)?;
+	} else {
+		processor.set_string("AutoRun", &updated).map_err(to_error)?;
+	}
+	Ok(true)
+}
+
+#[cfg(target_os = "windows")]
+/// Appends the command to an `AutoRun` command line, returning `None` if it's
+/// already there.
+fn append_command(current: &str, command: &str) -> Option<String> {
+	if current.contains(command) {
+		None
+	} else if current.trim().is_empty() {
+		Some(command.to_string())
+	} else {
+		Some(format!("{} & {command}", current.trim()))
+	}
+}
+
+#[cfg(target_os = "windows")]
+/// Removes the command from an `AutoRun` command line, along with the `&`
+/// joining it to the others, returning `None` if it isn't there.
+fn remove_command(current: &str, command: &str) -> Option<String> {
+	if !current.contains(command) {
+		return None;
+	}
+	let updated = current
+		.replace(&format!(" & {command}"), "")
+		.replace(&format!("{command} & "), "")
+		.replace(command, "");
+	Some(updated.trim().to_string())
+}
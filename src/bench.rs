@@ -0,0 +1,178 @@
+//! Timing how much each way of running an alias adds to its command, as done
+//! by `cmdlink bench`, to choose one for aliases run in hot loops.
+//!
+//! Every backend is invoked the given number of times from a loop in a single
+//! shell, the way a script calling the alias would, with the output discarded.
+//! The time per call of running the command directly is the baseline the
+//! overhead of the others is measured against.
+
+use std::{
+	process::{Command, Stdio},
+	time::{Duration, Instant},
+};
+
+use crate::{config::AliasValues, error::Error, platform_binary::Link, Result};
+
+/// The command of the alias timed when no alias is given, which does nothing.
+pub const TRIVIAL_CMD: &str = if cfg!(target_os = "windows") { "cd ." } else { "true" };
+
+/// The name of the alias timed when no alias is given.
+pub const TRIVIAL_ALIAS: &str = "cmdlink-bench";
+
+/// The values of the alias timed when no alias is given, named
+/// [TRIVIAL_ALIAS].
+pub fn trivial() -> AliasValues {
+	AliasValues {
+		cmd: TRIVIAL_CMD.to_string(),
+		..Default::default()
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// A way of running an alias.
+pub enum Backend {
+	/// The command itself, without any shim.
+	Direct,
+	/// A shell function running the command, as defined by the completion
+	/// loader for aliases jumping to a `dir`. Unix only.
+	Function,
+	/// The link file in the bins directory.
+	Script,
+	/// `cmdlink run`, which loads the config and runs the link from there.
+	Dispatcher,
+}
+
+impl Backend {
+	/// The name of the backend, as shown in the results.
+	pub fn name(self) -> &'static str {
+		match self {
+			Backend::Direct => "direct",
+			Backend::Function => "function",
+			Backend::Script => "script",
+			Backend::Dispatcher => "dispatcher",
+		}
+	}
+}
+
+#[derive(Debug, Clone, Copy)]
+/// The time per call of a backend.
+pub struct Timing {
+	/// The backend that was timed.
+	pub backend: Backend,
+	/// The average time of a call.
+	pub per_call: Duration,
+	/// The time per call on top of running the command directly.
+	pub overhead: Duration,
+}
+
+/// Times `runs` invocations of the link through each backend available on this
+/// platform. The dispatcher is only timed for aliases in the config, passed as
+/// `configured`.
+pub fn run(link: &impl Link, configured: bool, runs: u32) -> Result<Vec<Timing>> {
+	let error = |e| Error::AliasRun(link.alias().to_string(), e);
+	if link.url().is_some() || link.dir().is_some() || link.release_binary().is_some() {
+		return Err(Error::InvalidAlias(
+			link.alias().to_string(),
+			"only aliases running a command can be benchmarked",
+		));
+	}
+	let cmd = link.expand(link.cmd())?;
+	let script = std::env::temp_dir().join(format!(
+		"cmdlink-bench-{}-{}{}",
+		link.alias(),
+		std::process::id(),
+		link.extension()
+	));
+	std::fs::write(&script, link.contents()?).map_err(error)?;
+	#[cfg(target_family = "unix")]
+	{
+		use std::os::unix::fs::PermissionsExt;
+
+		std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).map_err(error)?;
+	}
+	let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+
+	let mut backends = vec![Backend::Direct];
+	if cfg!(target_family = "unix") {
+		backends.push(Backend::Function);
+	}
+	backends.push(Backend::Script);
+	if configured {
+		backends.push(Backend::Dispatcher);
+	}
+
+	let mut timings: Vec<Timing> = Vec::new();
+	for backend in backends {
+		let call = match backend {
+			Backend::Direct | Backend::Function => cmd.clone(),
+			Backend::Script => quote(&script.display().to_string()),
+			Backend::Dispatcher => format!("{} run {}", quote(&exe.display().to_string()), quote(link.alias())),
+		};
+		let elapsed = time_loop(&call, backend == Backend::Function, runs);
+		let per_call = match elapsed {
+			Ok(elapsed) => elapsed / runs.max(1),
+			Err(e) => {
+				let _ = std::fs::remove_file(&script);
+				return Err(error(e));
+			},
+		};
+		let direct = timings.first().map_or(per_call, |direct| direct.per_call);
+		debug!("Timed the {} backend at {:?} per call", backend.name(), per_call);
+		timings.push(Timing {
+			backend,
+			per_call,
+			overhead: per_call.saturating_sub(direct),
+		});
+	}
+
+	let _ = std::fs::remove_file(&script);
+	Ok(timings)
+}
+
+/// Runs the call `runs` times from a loop in a shell, through a function
+/// wrapping it if `in_function`, returning how long the loop took.
+fn time_loop(call: &str, in_function: bool, runs: u32) -> std::io::Result<Duration> {
+	// Functions only exist in the shell they're defined in, which cmd.exe
+	// has none of.
+	#[cfg(target_os = "windows")]
+	let (mut command, path) = {
+		let _ = in_function;
+		let path = std::env::temp_dir().join(format!("cmdlink-bench-loop-{}.bat", std::process::id()));
+		std::fs::write(&path, format!("@echo off\nfor /l %%i in (1,1,{runs}) do call {call}\n"))?;
+		let mut command = Command::new("cmd");
+		command.arg("/d").arg("/c").arg(&path);
+		(command, path)
+	};
+	#[cfg(not(target_os = "windows"))]
+	let mut command = {
+		let (prelude, call) = match in_function {
+			true => (format!("cmdlink_bench() {{\n\t{call} \"$@\"\n}}\n"), "cmdlink_bench"),
+			false => (String::new(), call),
+		};
+		let mut command = Command::new("sh");
+		command.arg("-c").arg(format!(
+			"{prelude}i=0\nwhile [ $i -lt {runs} ]; do\n\t{call}\n\ti=$((i + 1))\ndone"
+		));
+		command
+	};
+
+	let start = Instant::now();
+	let status = command
+		.stdin(Stdio::null())
+		.stdout(Stdio::null())
+		.stderr(Stdio::null())
+		.status();
+	let elapsed = start.elapsed();
+	#[cfg(target_os = "windows")]
+	let _ = std::fs::remove_file(path);
+	status.map(|_| elapsed)
+}
+
+/// Quotes a path or word for the shell the loop runs in.
+fn quote(word: &str) -> String {
+	if cfg!(target_os = "windows") {
+		format!("\"{word}\"")
+	} else {
+		format!("'{}'", word.replace('\'', "'\\''"))
+	}
+}
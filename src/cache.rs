@@ -0,0 +1,91 @@
+//! A small on-disk index of which aliases are missing their link file, used to
+//! avoid re-checking every link on startup when nothing has changed.
+//!
+//! The cache is keyed by a [Stamp] of the config file (modification time and
+//! content hash) and of the `bins` directory (modification time, which changes
+//! whenever a file is added, removed or renamed in it). When either differs the
+//! cache is considered stale and the links are scanned again.
+
+use std::{
+	collections::BTreeSet,
+	hash::{DefaultHasher, Hash, Hasher},
+	path::{Path, PathBuf},
+	time::UNIX_EPOCH,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::paths::CmdlinkPaths;
+
+/// The file the link cache is stored in.
+fn cache_path(paths: &CmdlinkPaths) -> PathBuf { paths.root().join("cache.toml") }
+
+/// The modification time of the given path in nanoseconds since the epoch, or
+/// `None` if it can't be determined.
+fn modified(path: &Path) -> Option<u128> {
+	let modified = std::fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+	modified.duration_since(UNIX_EPOCH).ok().map(|d| d.as_nanos())
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A fingerprint of the config file and the `bins` directory.
+pub(crate) struct Stamp {
+	/// The modification time and content hash of the config file.
+	config: String,
+	/// The modification time of the `bins` directory.
+	bins: String,
+}
+
+impl Stamp {
+	/// Computes the stamp for the current state of the config file of the
+	/// given project directory, given its contents, and of the `bins`
+	/// directory.
+	pub(crate) fn current(config_contents: &str, paths: &CmdlinkPaths, bins_dir: &Path) -> Self {
+		let mut hasher = DefaultHasher::new();
+		config_contents.hash(&mut hasher);
+		let config_modified = modified(&paths.config_file()).unwrap_or_default();
+		let bins_modified = modified(bins_dir).unwrap_or_default();
+
+		Stamp {
+			config: format!("{config_modified:x}-{:x}", hasher.finish()),
+			bins: format!("{bins_modified:x}"),
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// The cached link state, valid as long as its [Stamp] matches.
+pub(crate) struct LinkCache {
+	#[serde(flatten)]
+	/// The stamp the cache was computed for.
+	stamp: Stamp,
+	/// The aliases whose link file was missing.
+	missing: BTreeSet<String>,
+}
+
+impl LinkCache {
+	/// Creates a new cache from the given stamp and missing aliases.
+	pub(crate) fn new(stamp: Stamp, missing: BTreeSet<String>) -> Self { LinkCache { stamp, missing } }
+
+	/// Loads the cache of the given project directory, returning `None` if it
+	/// doesn't exist, can't be parsed, or was computed for a different stamp.
+	pub(crate) fn load(paths: &CmdlinkPaths, stamp: &Stamp) -> Option<Self> {
+		let contents = std::fs::read_to_string(cache_path(paths)).ok()?;
+		let cache: LinkCache = toml::from_str(&contents).ok()?;
+		(cache.stamp == *stamp).then_some(cache)
+	}
+
+	/// Saves the cache in the given project directory. Failures are only
+	/// logged, as the cache is an optimization.
+	pub(crate) fn save(&self, paths: &CmdlinkPaths) {
+		let result = toml::to_string(self)
+			.map_err(|e| e.to_string())
+			.and_then(|contents| std::fs::write(cache_path(paths), contents).map_err(|e| e.to_string()));
+		if let Err(why) = result {
+			debug!("Failed to save link cache: {why}");
+		}
+	}
+
+	/// Whether or not the link file for the given alias was missing.
+	pub(crate) fn is_missing(&self, alias: &str) -> bool { self.missing.contains(alias) }
+}
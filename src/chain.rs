@@ -0,0 +1,190 @@
+//! Alias chaining, where a command invokes other aliases by writing `@name`,
+//! e.g. `cmd = "@gs && @gl"`.
+//!
+//! References are resolved when link files are generated, by running the link
+//! of the referenced alias, so that its environment variables, hooks and
+//! platform variant apply as well. Only words starting with `@` that name an
+//! alias are references, so arguments such as curl's `@data.json` are left
+//! untouched, and `@@` at the start of a word stands for a literal `@`.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	path::{Path, PathBuf},
+};
+
+/// The names referenced by the `@name` words of the command, in order of
+/// appearance. Whether or not they name aliases isn't checked.
+pub fn references(cmd: &str) -> Vec<String> {
+	let mut names = Vec::new();
+	rewrite(cmd, |name| {
+		names.push(name.to_string());
+		None
+	});
+	names
+}
+
+/// Replaces the `@name` words of the command naming one of the given links
+/// with an invocation of the link file, and unescapes `@@`.
+pub fn resolve(cmd: &str, links: &BTreeMap<String, PathBuf>) -> String {
+	rewrite(cmd, |name| links.get(name).map(|path| invocation(path)))
+}
+
+/// Finds a cycle in the references between aliases, returned as the aliases
+/// along it, starting and ending with the same alias.
+pub fn find_cycle(references: &BTreeMap<&str, BTreeSet<&str>>) -> Option<Vec<String>> {
+	/// Visits the aliases referenced by `alias` depth first, keeping the
+	/// aliases leading to it in `path`.
+	fn visit<'a>(
+		references: &BTreeMap<&'a str, BTreeSet<&'a str>>, alias: &'a str, path: &mut Vec<&'a str>,
+		done: &mut BTreeSet<&'a str>,
+	) -> Option<Vec<String>> {
+		if let Some(start) = path.iter().position(|visited| *visited == alias) {
+			return Some(path[start..].iter().chain([&alias]).map(|a| a.to_string()).collect());
+		}
+		if done.contains(alias) {
+			return None;
+		}
+		path.push(alias);
+		for other in references.get(alias).into_iter().flatten() {
+			if let Some(cycle) = visit(references, other, path, done) {
+				return Some(cycle);
+			}
+		}
+		path.pop();
+		done.insert(alias);
+		None
+	}
+
+	let mut done = BTreeSet::new();
+	references
+		.keys()
+		.find_map(|alias| visit(references, alias, &mut Vec::new(), &mut done))
+}
+
+/// Whether or not the character ends a word of a command.
+fn ends_word(c: char) -> bool {
+	c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(' | ')' | '<' | '>' | '`' | '"' | '\'')
+}
+
+/// Whether or not a word of a command starts after the character.
+fn starts_word(c: char) -> bool { c.is_whitespace() || matches!(c, ';' | '&' | '|' | '(') }
+
+/// Rewrites the `@name` words of the command with the result of `replace` for
+/// the name, leaving the word as is when it returns `None`. `@@` at the start
+/// of a word is replaced with a literal `@`.
+fn rewrite(cmd: &str, mut replace: impl FnMut(&str) -> Option<String>) -> String {
+	let mut output = String::with_capacity(cmd.len());
+	let mut rest = cmd;
+	let mut word_start = true;
+	while let Some(c) = rest.chars().next() {
+		if word_start {
+			if let Some(after) = rest.strip_prefix("@@") {
+				output.push('@');
+				rest = after;
+				word_start = false;
+				continue;
+			}
+			if let Some(after) = rest.strip_prefix('@') {
+				let end = after.find(ends_word).unwrap_or(after.len());
+				if let Some(replacement) = (end > 0).then(|| replace(&after[..end])).flatten() {
+					output.push_str(&replacement);
+					rest = &after[end..];
+					word_start = false;
+					continue;
+				}
+			}
+		}
+		output.push(c);
+		rest = &rest[c.len_utf8()..];
+		word_start = starts_word(c);
+	}
+	output
+}
+
+/// The command running a link file from another link. Batch files have to be
+/// `call`ed, or the calling link would never continue after them.
+fn invocation(path: &Path) -> String {
+	#[cfg(target_os = "windows")]
+	{
+		format!("call \"{}\"", path.display())
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		crate::platform_binary::sh_quote(&path.display().to_string())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn links() -> BTreeMap<String, PathBuf> {
+		BTreeMap::from([
+			("gs".to_string(), PathBuf::from("/bins/gs")),
+			("gl".to_string(), PathBuf::from("/bins/gl")),
+		])
+	}
+
+	#[test]
+	fn references_are_words_starting_with_at() {
+		assert_eq!(references("@gs && (@gl -n 3)|@up"), ["gs", "gl", "up"]);
+		assert!(references("curl -d@data.json me@host @ @@gs").is_empty());
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	#[test]
+	fn resolve_runs_the_referenced_links() {
+		assert_eq!(resolve("@gs && @gl -n 3", &links()), "'/bins/gs' && '/bins/gl' -n 3");
+		assert_eq!(resolve("@other;@gs", &links()), "@other;'/bins/gs'");
+		assert_eq!(resolve("echo @@gs @gsx", &links()), "echo @gs @gsx");
+	}
+
+	#[cfg(target_os = "windows")]
+	#[test]
+	fn resolve_runs_the_referenced_links() {
+		assert_eq!(
+			resolve("@gs && @gl -n 3", &links()),
+			"call \"/bins/gs\" && call \"/bins/gl\" -n 3"
+		);
+		assert_eq!(resolve("@other;@gs", &links()), "@other;call \"/bins/gs\"");
+		assert_eq!(resolve("echo @@gs @gsx", &links()), "echo @gs @gsx");
+	}
+
+	#[cfg(not(target_os = "windows"))]
+	#[test]
+	fn invocation_quotes_the_link() {
+		assert_eq!(invocation(Path::new("/home/o'brien/bins/gs")), r"'/home/o'\''brien/bins/gs'");
+	}
+
+	#[cfg(target_os = "windows")]
+	#[test]
+	fn invocation_calls_the_link() {
+		assert_eq!(
+			invocation(Path::new(r"C:\cmdlink\bins\gs.bat")),
+			r#"call "C:\cmdlink\bins\gs.bat""#
+		);
+	}
+
+	#[test]
+	fn rewrite_passes_the_names_in_order() {
+		let mut seen = Vec::new();
+		let output = rewrite("@a `@b` \"@c\" x@d", |name| {
+			seen.push(name.to_string());
+			Some(name.to_uppercase())
+		});
+		assert_eq!(seen, ["a"]);
+		assert_eq!(output, "A `@b` \"@c\" x@d");
+	}
+
+	#[test]
+	fn find_cycle_reports_the_aliases_along_it() {
+		let chain = BTreeMap::from([("a", BTreeSet::from(["b"])), ("b", BTreeSet::from(["c"]))]);
+		assert_eq!(find_cycle(&chain), None);
+
+		let cycle = BTreeMap::from([("a", BTreeSet::from(["b"])), ("b", BTreeSet::from(["a"]))]);
+		assert_eq!(find_cycle(&cycle).unwrap(), ["a", "b", "a"]);
+
+		let own = BTreeMap::from([("a", BTreeSet::from(["b"])), ("b", BTreeSet::from(["b"]))]);
+		assert_eq!(find_cycle(&own).unwrap(), ["b", "b"]);
+	}
+}
@@ -1,31 +1,175 @@
-use clap::{Args, Parser, Subcommand};
-use tracing::level_filters::LevelFilter;
+use std::{
+	collections::HashSet,
+	ffi::OsString,
+	fs::OpenOptions,
+	io::{IsTerminal, Write},
+	path::{Path, PathBuf},
+	process::Command,
+	sync::Mutex,
+	time::{Duration, SystemTime},
+};
 
-use crate::{config::Config, Result};
+use clap::{Args, CommandFactory, Parser, Subcommand, ValueEnum};
+use cmdlink::{
+	adopt, archive, autorun, bench, cli_completion, clipboard, completion, config, describe, diff,
+	dotfiles::{self, Layout},
+	gc::{self, Retention},
+	graph::Graph,
+	history::{self, Frequent},
+	import::Resolution,
+	omz, open,
+	pack::{self, Compatibility, Pack, PackManifest},
+	paths, platform_binary, report,
+	runs::{self, Run},
+	sandbox::Sandbox,
+	secret::SecretRef,
+	service,
+	settings::{self, ConfirmMode, PathScope, Settings},
+	suggest, tr, trace, user_path, version, watch, which, AliasBuilder, AliasSpec, Config, Error, Link, PlatformBinary,
+	Result,
+};
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::{fmt, layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 #[derive(Args, Debug)]
 pub struct Verbosity {
 	#[arg(short, long, action = clap::ArgAction::Count, global = true)]
-	/// Increases the verbosity level. -v for INFO (default), -vv for DEBUG,
-	/// -vvv for TRACE
+	/// Increases the verbosity level. -v for INFO, -vv for DEBUG, -vvv for
+	/// TRACE. Only warnings and errors are shown by default.
 	verbose: u8,
 	#[arg(short, long, action = clap::ArgAction::Count, global = true, conflicts_with = "verbose")]
-	/// Silences all logging
+	/// Silences all logging except errors
 	quiet: u8,
 }
 
 impl Verbosity {
 	/// Converts the verbosity settings into a tracing level filter.
-	pub fn as_level_filter(&self) -> Option<LevelFilter> {
+	pub fn as_level_filter(&self) -> LevelFilter {
 		if self.quiet > 0 {
-			return None;
+			return LevelFilter::ERROR;
 		}
 
-		Some(match self.verbose.min(3) {
+		match self.verbose {
+			0 => LevelFilter::WARN,
+			1 => LevelFilter::INFO,
 			2 => LevelFilter::DEBUG,
-			3 => LevelFilter::TRACE,
-			_ => LevelFilter::INFO, // Default to ERROR
-		})
+			_ => LevelFilter::TRACE,
+		}
+	}
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+/// The format fatal errors are reported in.
+pub enum OutputFormat {
+	/// Human readable diagnostics.
+	Text,
+	/// JSON objects with a stable error code, for wrapper scripts.
+	Json,
+}
+
+impl OutputFormat {
+	/// Reports a fatal error on stderr.
+	pub fn report(self, error: &Error) {
+		match self {
+			OutputFormat::Text => eprintln!("{}", error.diagnostic()),
+			OutputFormat::Json => eprintln!("{}", error.to_json()),
+		}
+	}
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+/// The dotfiles manager to export the config for.
+pub enum DotfilesLayout {
+	/// A GNU stow package.
+	Stow,
+	/// A chezmoi source directory.
+	Chezmoi,
+}
+
+impl From<DotfilesLayout> for Layout {
+	fn from(layout: DotfilesLayout) -> Self {
+		match layout {
+			DotfilesLayout::Stow => Layout::Stow,
+			DotfilesLayout::Chezmoi => Layout::Chezmoi,
+		}
+	}
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+/// The shell to complete cmdlink's subcommands in.
+pub enum CompletionShell {
+	/// bash, with the bash-completion package.
+	Bash,
+	/// zsh, with `compinit`.
+	Zsh,
+	/// fish.
+	Fish,
+	#[value(name = "powershell")]
+	/// PowerShell.
+	PowerShell,
+}
+
+impl From<CompletionShell> for cli_completion::Shell {
+	fn from(shell: CompletionShell) -> Self {
+		match shell {
+			CompletionShell::Bash => cli_completion::Shell::Bash,
+			CompletionShell::Zsh => cli_completion::Shell::Zsh,
+			CompletionShell::Fish => cli_completion::Shell::Fish,
+			CompletionShell::PowerShell => cli_completion::Shell::PowerShell,
+		}
+	}
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+/// The format of an alias report.
+pub enum ReportFormat {
+	/// A standalone HTML page.
+	Html,
+	/// A Markdown document.
+	#[value(name = "md", alias = "markdown")]
+	Markdown,
+}
+
+impl From<ReportFormat> for report::Format {
+	fn from(format: ReportFormat) -> Self {
+		match format {
+			ReportFormat::Html => report::Format::Html,
+			ReportFormat::Markdown => report::Format::Markdown,
+		}
+	}
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+/// How imported aliases that collide with existing aliases are handled.
+pub enum OnConflict {
+	/// Keeps the existing alias.
+	Skip,
+	/// Replaces the existing alias.
+	Overwrite,
+	/// Imports the alias under a new name with a numeric suffix.
+	Rename,
+	/// Asks which of the above to do for each conflict.
+	Prompt,
+}
+
+impl OnConflict {
+	/// Decides how to handle an imported alias that already exists, asking the
+	/// user if the strategy is `prompt`.
+	fn resolve(self, existing: &AliasSpec, spec: &AliasSpec, interactive: bool) -> Result<Resolution> {
+		match self {
+			OnConflict::Skip => Ok(Resolution::Skip),
+			OnConflict::Overwrite => Ok(Resolution::Overwrite),
+			OnConflict::Rename => Ok(Resolution::Rename),
+			OnConflict::Prompt => {
+				if !interactive || !std::io::stdin().is_terminal() {
+					return Err(Error::NonInteractive(
+						"Resolving alias conflicts",
+						"pass --on-conflict skip, overwrite or rename",
+					));
+				}
+				choose_resolution(existing, spec)
+			},
+		}
 	}
 }
 
@@ -34,17 +178,165 @@ impl Verbosity {
 pub struct Cli {
 	#[command(flatten)]
 	verbose: Verbosity,
+	#[arg(long, global = true)]
+	/// Appends log output to the given file in addition to the terminal,
+	/// overriding the `log_file` config setting.
+	log_file: Option<PathBuf>,
+	#[arg(long, global = true, default_value = "false")]
+	/// Never prompts for input, failing instead when a decision is needed. Also
+	/// enabled by setting `CMDLINK_NONINTERACTIVE=1`.
+	non_interactive: bool,
+	#[arg(long, global = true, value_enum, default_value_t = OutputFormat::Text)]
+	/// The format fatal errors are reported in.
+	output: OutputFormat,
 	#[command(subcommand)]
 	pub subcommand: Commands,
 }
 
 #[derive(Debug, Subcommand)]
+// The command is parsed once per run, so the size of `Add` doesn't matter.
+#[allow(clippy::large_enum_variant)]
 pub enum Commands {
+	/// Creates the project and bins directories and config file, and adds the
+	/// bins directory to your PATH.
+	Init {
+		#[arg(long, default_value = "false", conflicts_with = "no_path")]
+		/// Adds the bins directory to your PATH without asking.
+		path: bool,
+		#[arg(long, default_value = "false")]
+		/// Leaves your PATH untouched.
+		no_path: bool,
+		#[arg(long, default_value = "false", conflicts_with = "no_path")]
+		/// Adds the bins directory to the PATH of every user of the machine
+		/// instead of yours, overriding the `path_scope` setting. Requires an
+		/// elevated prompt on Windows, or root elsewhere.
+		machine: bool,
+		#[arg(long, default_value = "false")]
+		/// Keeps the config and bins directory next to the cmdlink executable
+		/// instead of in your home directory, e.g. on a USB stick.
+		portable: bool,
+	},
 	/// Refreshes links by retrieving the latest config file and updating the
 	/// associated binaries in the `bins` directory.
-	Refresh,
+	Refresh {
+		#[arg(short, long, default_value = "false")]
+		/// Rewrites every binary from the current settings, not just the
+		/// missing and outdated ones.
+		force: bool,
+		#[arg(short, long, default_value = "false")]
+		/// Deletes files in the bins directory that don't belong to any alias.
+		prune: bool,
+	},
 	/// Displays all current aliases and their associated descriptions.
-	Display,
+	Display {
+		#[arg(short, long, default_value = "false")]
+		/// Shows the full command, tags, link status and timestamps of each
+		/// alias.
+		long: bool,
+		#[arg(short, long, default_value = "false", conflicts_with = "long")]
+		/// Groups the aliases by pack and tag in a tree instead of a table.
+		tree: bool,
+		#[arg(long, default_value = "false", conflicts_with = "tree")]
+		/// Wraps long commands and descriptions across lines instead of
+		/// truncating them to fit the terminal.
+		full: bool,
+		#[arg(short, long, default_value = "false")]
+		/// Only lists aliases whose link is missing or whose program can't be
+		/// found, i.e. what `refresh` or a cleanup should address.
+		broken: bool,
+	},
+	/// Shows the description, long description, command and examples of an
+	/// alias.
+	Show {
+		/// The alias to show.
+		alias: String,
+	},
+	/// Copies the command of an alias to the clipboard, e.g. to paste it into a
+	/// script or ticket.
+	CopyCmd {
+		/// The alias to copy the command of.
+		alias: String,
+	},
+	/// Prints the link of an alias, then runs it with tracing turned on to show
+	/// exactly which command and arguments its target receives.
+	Test {
+		/// The alias to test.
+		alias: String,
+		#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+		/// Sample arguments to pass to the alias.
+		args: Vec<String>,
+	},
+	/// Runs an alias, optionally in a sandbox with a scrubbed environment to
+	/// safely try out aliases from packs or remote configs.
+	Run {
+		#[arg(long, default_value = "false")]
+		/// Runs the alias with a minimal PATH, keeping only an allowlist of the
+		/// environment variables.
+		sandbox: bool,
+		#[arg(long, value_name = "VAR", requires = "sandbox")]
+		/// Keeps an environment variable in the sandbox. Can be repeated.
+		allow_env: Vec<String>,
+		#[arg(long, default_value = "false", requires = "sandbox")]
+		/// Runs the alias in a new temporary directory, removed afterwards.
+		temp_dir: bool,
+		/// The alias to run.
+		alias: String,
+		#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+		/// The arguments to pass to the alias.
+		args: Vec<String>,
+	},
+	/// Times invocations of an alias through each way of running it, to show
+	/// how much a link or `cmdlink run` adds to running the command directly.
+	Bench {
+		/// The alias to time, a trivial one that does nothing by default.
+		alias: Option<String>,
+		#[arg(short = 'n', long, default_value_t = 100, value_parser = clap::value_parser!(u32).range(1..))]
+		/// The number of invocations to time through each backend.
+		runs: u32,
+	},
+	/// Shows the most recent runs of your aliases, with their exit codes and
+	/// durations, as recorded by the links with the `run_history` setting.
+	History {
+		/// Only shows the runs of this alias.
+		alias: Option<String>,
+		#[arg(long, default_value = "false")]
+		/// Only shows runs that exited with a non-zero code.
+		failed: bool,
+		#[arg(short = 'n', long, default_value = "20")]
+		/// The number of runs to show, newest last.
+		limit: usize,
+		#[arg(long, default_value = "false")]
+		/// Prints the runs as JSON instead of a table.
+		json: bool,
+	},
+	/// Runs the most recently recorded alias invocation again, with the same
+	/// arguments.
+	Last {
+		#[arg(long, default_value = "false")]
+		/// Chooses the invocation to run from the most recent ones instead.
+		pick: bool,
+		#[arg(short = 'n', long, default_value = "10", requires = "pick")]
+		/// The number of invocations to choose from.
+		limit: usize,
+	},
+	/// Lists the aliases that weren't used recently, judged by the run history
+	/// or else when they were last added or updated.
+	Stale {
+		#[arg(long, default_value = "90")]
+		/// The number of days an alias has to go unused to be listed.
+		days: u64,
+		#[arg(long, default_value = "false")]
+		/// Asks whether to remove each of the listed aliases.
+		remove: bool,
+	},
+	/// Removes old runs from the run history, cached releases no alias uses
+	/// and leftover link backups, keeping as much as the `gc_keep` and
+	/// `gc_max_age` settings say.
+	Gc {
+		#[arg(long, default_value = "false")]
+		/// Only reports what would be removed.
+		dry_run: bool,
+	},
 	/// Adds a new command link to the config file, adding the appropriate bin
 	/// to the `bins` directory.
 	Add {
@@ -53,45 +345,1577 @@ pub enum Commands {
 		#[arg(short, long = "desc")]
 		/// An optional description for the alias.
 		description: Option<String>,
-		#[arg(short, long)]
+		#[arg(
+			short,
+			long,
+			required_unless_present_any = ["url", "dir", "release", "from_clipboard", "from_history"]
+		)]
 		/// The command to run in place of the alias.
-		cmd: String,
+		cmd: Option<String>,
+		#[arg(long, default_value = "false", conflicts_with = "cmd")]
+		/// Takes the command from the clipboard instead of --cmd, e.g. after
+		/// trying it out in another terminal.
+		from_clipboard: bool,
+		#[arg(
+			long,
+			value_name = "N",
+			num_args = 0..=1,
+			default_missing_value = "20",
+			conflicts_with_all = ["cmd", "from_clipboard"]
+		)]
+		/// Picks the command from the last N distinct commands of your shell
+		/// history instead of --cmd, 20 unless given.
+		from_history: Option<usize>,
+		#[arg(long, conflicts_with_all = ["cmd", "from_clipboard", "from_history", "auto_desc", "shell"])]
+		/// Opens a web page in the browser instead of running a command,
+		/// appending the arguments the alias is invoked with as query
+		/// parameters.
+		url: Option<String>,
+		#[arg(
+			long,
+			value_name = "PATH",
+			conflicts_with_all = ["cmd", "url", "from_clipboard", "from_history", "auto_desc", "shell"]
+		)]
+		/// Changes the shell to a directory instead of running a command,
+		/// through the function defined by the sourced completion loader on
+		/// Unix.
+		dir: Option<String>,
+		#[arg(
+			long,
+			value_name = "CMD",
+			requires = "dir",
+			conflicts_with_all = ["cmd", "url", "release", "from_clipboard", "from_history"]
+		)]
+		/// A command run in the shell after changing to --dir, such as
+		/// `source .venv/bin/activate`.
+		activate: Option<String>,
+		#[arg(
+			long,
+			value_name = "OWNER/REPO@TAG",
+			conflicts_with_all = ["cmd", "url", "dir", "from_clipboard", "from_history", "auto_desc", "shell"]
+		)]
+		/// Runs the binary of a GitHub release, downloaded into a cache and
+		/// verified against its checksum, instead of a command.
+		release: Option<String>,
+		#[arg(
+			long,
+			value_name = "PATTERN",
+			requires = "release",
+			conflicts_with_all = ["cmd", "url", "dir", "from_clipboard", "from_history", "auto_desc", "shell"]
+		)]
+		/// The name of the release asset to download, with `*` wildcards, if it
+		/// can't be recognized by the current platform.
+		asset: Option<String>,
+		#[arg(
+			long,
+			value_name = "NAME",
+			requires = "release",
+			conflicts_with_all = ["cmd", "url", "dir", "from_clipboard", "from_history", "auto_desc", "shell"]
+		)]
+		/// The name of the binary in the release asset, the repository name by
+		/// default.
+		bin: Option<String>,
+		#[arg(
+			long,
+			value_name = "HEX",
+			requires = "release",
+			conflicts_with_all = ["cmd", "url", "dir", "from_clipboard", "from_history", "auto_desc", "shell"]
+		)]
+		/// The SHA-256 checksum to verify the release asset against, instead of
+		/// the one published with the release.
+		sha256: Option<String>,
+		#[arg(short, long = "tag")]
+		/// Tags used to group and filter the alias. Can be repeated.
+		tags: Vec<String>,
+		#[arg(short, long, value_parser = parse_env_pair)]
+		/// An environment variable to export before running the command, in
+		/// KEY=VALUE form. Can be repeated.
+		env: Vec<(String, String)>,
+		#[arg(short, long, value_parser = parse_env_pair)]
+		/// An environment variable retrieved from the OS keychain when the
+		/// alias is invoked, in KEY=keyring:<service>/<account> form. Can be
+		/// repeated.
+		secret: Vec<(String, String)>,
+		#[arg(long, value_name = "PATH")]
+		/// A file of KEY=VALUE lines to load each time the alias is invoked,
+		/// such as `~/.config/myapp/.env`.
+		env_file: Option<String>,
+		#[arg(long, value_name = "PATH")]
+		/// A file the output of the alias is appended to each time it runs,
+		/// such as `~/.cmdlink/logs/backup.log`.
+		capture: Option<String>,
+		#[arg(short, long)]
+		/// The group to inherit environment variables, working directory, shell
+		/// and tags from, defined in a `[groups.<name>]` table of the config.
+		group: Option<String>,
+		#[arg(long, value_name = "PATH")]
+		/// The directory the command runs in.
+		cwd: Option<String>,
+		#[arg(long)]
+		/// The shell the command runs through on Unix, such as `bash`.
+		shell: Option<String>,
+		#[arg(long, value_name = "REQUIREMENT", conflicts_with_all = ["url", "dir"])]
+		/// The versions of the tool the command works with, such as `>=20`,
+		/// checked before the command runs.
+		min_version: Option<String>,
+		#[arg(long, value_name = "CMD", requires = "min_version", conflicts_with_all = ["url", "dir"])]
+		/// The command printing the version of the tool for --min-version,
+		/// `<program> --version` by default.
+		version_cmd: Option<String>,
+		#[arg(long, default_value = "false")]
+		/// Runs the command without the arguments the alias is invoked with.
+		no_forward_args: bool,
+		#[arg(long, default_value = "false", conflicts_with = "description")]
+		/// Uses the first summary line of the command's `--help` output as the
+		/// description.
+		auto_desc: bool,
 		#[arg(short, long, default_value = "false")]
 		/// Forces the creation of the alias even if it already exists.
 		force: bool,
 	},
 	/// Removes a command link from the config file and bins.
-	Remove { alias: String },
+	Remove {
+		alias: String,
+		#[arg(long, default_value = "false")]
+		/// Succeeds without doing anything if the alias doesn't exist, instead
+		/// of failing.
+		ignore_missing: bool,
+	},
+	/// Watches the config file and bins directory, regenerating missing or
+	/// outdated links whenever they change.
+	Watch {
+		#[arg(long, default_value = "500")]
+		/// How long to wait for changes to settle before refreshing, in
+		/// milliseconds.
+		debounce_ms: u64,
+	},
+	/// Checks whether the bins directory exists and is on your PATH, to find
+	/// out why aliases aren't found.
+	Env,
+	/// Manages the background service that runs `watch` on login.
+	Service {
+		#[command(subcommand)]
+		command: ServiceCommand,
+	},
+	/// Prints or installs tab completion for cmdlink's own subcommands and
+	/// flags.
+	Completions {
+		#[command(subcommand)]
+		command: CompletionsCommand,
+	},
+	/// Manages the doskey macro script that defines the aliases in every
+	/// cmd.exe session.
+	Autorun {
+		#[command(subcommand)]
+		command: AutorunCommand,
+	},
+	/// Opens the project directory in the file manager or editor.
+	Open {
+		#[arg(short, long, default_value = "false")]
+		/// Opens the bins directory instead of the project directory.
+		bins: bool,
+		#[arg(short, long, default_value = "false")]
+		/// Opens the directory in `$VISUAL` or `$EDITOR` instead of the file
+		/// manager.
+		editor: bool,
+	},
+	/// Prints the bins directory, or the path of an alias' binary.
+	Path {
+		/// The alias to print the binary path of.
+		alias: Option<String>,
+	},
+	/// Moves all links to a new bins directory and updates PATH to match.
+	Relocate {
+		/// The new bins directory, e.g. `~/.local/bin`.
+		dir: PathBuf,
+		#[arg(long, default_value = "false")]
+		/// Leaves PATH untouched.
+		no_path: bool,
+	},
+	/// Moves the whole project directory, e.g. onto another drive or into a
+	/// synced folder, and updates the links and PATH to match.
+	MigrateHome {
+		/// The new project directory, which must not exist yet or be empty.
+		dir: PathBuf,
+		#[arg(long, default_value = "false")]
+		/// Leaves PATH untouched.
+		no_path: bool,
+	},
+	/// Merges the aliases of another cmdlink config file into this one.
+	Merge {
+		/// The config file to merge aliases from.
+		path: PathBuf,
+		#[arg(long, value_enum, default_value = "skip")]
+		/// How aliases defined differently in both configs are handled. `skip`
+		/// keeps yours, `overwrite` takes theirs.
+		on_conflict: OnConflict,
+	},
+	/// Lists the aliases whose command invokes a program or contains some text.
+	Why {
+		/// The program, such as `kubectl`, or text to look for.
+		command: String,
+	},
+	/// Shows which aliases invoke other aliases or share a program.
+	Graph {
+		#[arg(long, default_value = "false")]
+		/// Prints the graph in the Graphviz DOT language instead of as a tree.
+		dot: bool,
+	},
+	/// Suggests aliases for long commands typed often in your shell history.
+	Suggest {
+		#[arg(long = "history")]
+		/// Reads the given history file instead of the detected bash, zsh, fish
+		/// and PowerShell history. Can be repeated.
+		files: Vec<PathBuf>,
+		#[arg(long, default_value = "3")]
+		/// Only suggests commands typed at least this many times.
+		min_count: usize,
+		#[arg(long, default_value = "15")]
+		/// Only suggests commands at least this many characters long.
+		min_length: usize,
+		#[arg(short = 'n', long, default_value = "10")]
+		/// The maximum number of suggestions.
+		limit: usize,
+	},
+	/// Exports the config for a dotfiles manager, along with a script that
+	/// regenerates the links on other machines, as a bundle for offline
+	/// machines, or as an oh-my-zsh plugin.
+	Export {
+		#[arg(long, required_unless_present_any = ["archive", "omz_plugin"], conflicts_with_all = ["archive", "omz_plugin"])]
+		/// The dotfiles directory to write to, e.g. your stow directory or
+		/// chezmoi source directory.
+		dotfiles: Option<PathBuf>,
+		#[arg(long, value_name = "PATH")]
+		/// Writes a zip archive with the config, the links and an install
+		/// script instead, e.g. `cmdlink-bundle.zip`.
+		archive: Option<PathBuf>,
+		#[arg(long, value_name = "DIR", conflicts_with = "archive")]
+		/// Writes an oh-my-zsh plugin defining the aliases as zsh aliases and
+		/// functions instead, for machines without cmdlink, e.g.
+		/// `$ZSH_CUSTOM/plugins/team-aliases`.
+		omz_plugin: Option<PathBuf>,
+		#[arg(long, value_enum, default_value = "stow")]
+		/// The dotfiles manager the layout is made for.
+		layout: DotfilesLayout,
+	},
+	/// Renders all aliases, grouped by tag with their descriptions and platform
+	/// variants, as a document for a team wiki.
+	Report {
+		#[arg(long, value_enum, default_value = "md")]
+		/// The format of the document.
+		format: ReportFormat,
+		#[arg(short, long, value_name = "PATH")]
+		/// Writes the document to the given file instead of printing it.
+		out: Option<PathBuf>,
+	},
+	/// Turns hand-written scripts in the bins directory into aliases, asking
+	/// for each script whose command can be extracted.
+	Adopt,
+	/// Proposes short, unclaimed alias names for a command.
+	SuggestName {
+		/// The command to suggest names for, e.g. "kubectl get pods".
+		cmd: String,
+		#[arg(short = 'n', long, default_value = "5")]
+		/// The maximum number of names to propose.
+		count: usize,
+	},
+	/// Inspects and edits the config file.
+	Config {
+		#[command(subcommand)]
+		command: ConfigCommand,
+	},
+	/// Installs, updates and removes alias packs.
+	Pack {
+		#[command(subcommand)]
+		command: PackCommand,
+	},
+	/// Prints a secret from the OS keychain. Used by generated shims.
+	#[command(hide = true)]
+	Secret {
+		/// The secret reference, in keyring:<service>/<account> form.
+		reference: String,
+	},
+	/// Opens a URL in the default browser, with the arguments appended as
+	/// query parameters. Used by generated shims.
+	#[command(hide = true)]
+	OpenUrl {
+		/// The URL to open.
+		url: String,
+		#[arg(trailing_var_arg = true, allow_hyphen_values = true)]
+		/// The arguments the alias was invoked with.
+		args: Vec<String>,
+	},
+	/// Checks that the version of an alias' tool meets its requirement. Used by
+	/// generated shims.
+	#[command(hide = true)]
+	CheckVersion {
+		/// The alias the tool is run by.
+		alias: String,
+		/// The versions the alias works with, such as `>=20`.
+		require: String,
+		/// The command printing the version of the tool.
+		cmd: String,
+	},
+	/// Appends a run of an alias to the run history. Used by generated shims.
+	#[command(hide = true)]
+	Record {
+		/// The alias that was run.
+		alias: String,
+		#[arg(allow_negative_numbers = true)]
+		/// The exit code of the command.
+		status: i32,
+		#[arg(value_parser = humantime::parse_duration)]
+		/// How long the command ran, e.g. `3s` or `1250ms`.
+		elapsed: Duration,
+		#[arg(last = true)]
+		/// The arguments the alias was run with.
+		args: Vec<String>,
+	},
+	/// Runs an external `cmdlink-<name>` plugin found on PATH.
+	#[command(external_subcommand)]
+	External(Vec<OsString>),
+}
+
+impl Commands {
+	/// Whether or not the command only reads the config.
+	fn is_read_only(&self) -> bool {
+		matches!(
+			self,
+			Commands::Display { .. }
+				| Commands::Show { .. }
+				| Commands::CopyCmd { .. }
+				| Commands::Test { .. }
+				| Commands::Run { .. }
+				| Commands::Bench { .. }
+				| Commands::History { .. }
+				| Commands::Last { .. }
+				| Commands::Stale { remove: false, .. }
+				| Commands::Gc { .. }
+				| Commands::Path { .. }
+				| Commands::SuggestName { .. }
+				| Commands::Why { .. }
+				| Commands::Graph { .. }
+				| Commands::Export { .. }
+				| Commands::Report { .. }
+				| Commands::Autorun {
+					command: AutorunCommand::Install { .. }
+				} | Commands::Config {
+				command: ConfigCommand::Cat { .. } | ConfigCommand::Get { .. }
+			} | Commands::Pack {
+				command: PackCommand::List | PackCommand::Create { .. }
+			}
+		)
+	}
+}
+
+#[derive(Debug, Subcommand)]
+pub enum CompletionsCommand {
+	/// Prints the completion script for a shell.
+	Print {
+		#[arg(long, value_enum)]
+		/// The shell to print the script for, detected from `$SHELL` if
+		/// omitted.
+		shell: Option<CompletionShell>,
+	},
+	/// Writes the completion script to where the shell picks it up: the
+	/// bash-completion user directory, `~/.zsh/completions`, the fish
+	/// completions directory, or next to the PowerShell profile.
+	Install {
+		#[arg(long, value_enum)]
+		/// The shell to install the script for, detected from `$SHELL` if
+		/// omitted.
+		shell: Option<CompletionShell>,
+		#[arg(long, default_value = "false")]
+		/// Removes the installed script instead.
+		uninstall: bool,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ServiceCommand {
+	/// Registers `cmdlink watch` as a systemd user unit, launchd agent, or
+	/// Windows Task Scheduler job and starts it.
+	Install,
+	/// Stops and removes the background service.
+	Uninstall,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum AutorunCommand {
+	/// Writes the script and registers it under the cmd.exe AutoRun registry
+	/// value. The script is kept up to date as aliases change.
+	Install {
+		#[arg(long, default_value = "false")]
+		/// Only writes the script, without registering it.
+		no_register: bool,
+	},
+	/// Unregisters and deletes the script, keeping any other AutoRun
+	/// commands.
+	Uninstall,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum ConfigCommand {
+	/// Prints the config as cmdlink loaded it.
+	Cat {
+		#[arg(long, default_value = "false")]
+		/// Prints the config as JSON instead of TOML.
+		json: bool,
+	},
+	/// Opens the config in `$VISUAL` or `$EDITOR`, validates it once the editor
+	/// exits, and regenerates the links to match it.
+	Edit,
+	/// Prints the value of a global setting, or of all settings if no key is
+	/// given.
+	Get {
+		/// The setting to print, e.g. `log_file`.
+		key: Option<String>,
+	},
+	/// Changes a global setting.
+	Set {
+		/// The setting to change, e.g. `log_file`.
+		key: String,
+		/// The new value of the setting.
+		value: String,
+	},
+	/// Resets a global setting to its default.
+	Unset {
+		/// The setting to reset.
+		key: String,
+	},
+	/// Rewrites the config in the canonical style cmdlink saves it in.
+	Fmt {
+		#[arg(long, default_value = "false")]
+		/// Fails instead of rewriting the config if it isn't formatted.
+		check: bool,
+	},
+}
+
+#[derive(Debug, Subcommand)]
+pub enum PackCommand {
+	/// Installs a pack from a file path, URL, or the name of a pack in the
+	/// `packs` directory.
+	Install {
+		/// The pack file path, URL or name.
+		source: String,
+		#[arg(short, long)]
+		/// The prefix to install the aliases under, defaulting to the pack's
+		/// own prefix or name. Pass an empty string to install without a
+		/// prefix.
+		prefix: Option<String>,
+		#[arg(short, long, default_value = "false", conflicts_with = "on_conflict")]
+		/// Overwrites existing aliases with the same name. Same as
+		/// `--on-conflict overwrite`.
+		force: bool,
+		#[arg(long, value_enum)]
+		/// How aliases that already exist are handled. Without it, the install
+		/// fails if any alias already exists.
+		on_conflict: Option<OnConflict>,
+	},
+	/// Updates an installed pack from its original source.
+	Update { name: String },
+	/// Removes an installed pack and all of its aliases.
+	Remove { name: String },
+	/// Lists the installed packs.
+	List,
+	/// Creates a shareable pack file from existing aliases.
+	Create {
+		/// The name of the pack.
+		name: String,
+		#[arg(short = 't', long = "from-tag")]
+		/// Includes all aliases with the given tag. Can be repeated.
+		tags: Vec<String>,
+		#[arg(short, long = "alias")]
+		/// Includes the given alias. Can be repeated.
+		aliases: Vec<String>,
+		#[arg(long, default_value = "0.1.0")]
+		/// The version of the pack.
+		version: String,
+		#[arg(short, long = "desc")]
+		/// An optional description for the pack.
+		description: Option<String>,
+		#[arg(short, long)]
+		/// The prefix the aliases are installed under, defaulting to the pack
+		/// name.
+		prefix: Option<String>,
+		#[arg(long = "platform")]
+		/// Restricts the pack to the given platforms (windows, macos, linux).
+		/// Can be repeated.
+		platforms: Vec<String>,
+		#[arg(short, long)]
+		/// Where to write the pack file, defaulting to the `packs` directory.
+		output: Option<PathBuf>,
+	},
 }
 
 impl Cli {
 	/// Sets up the logging configuration based on the verbosity settings.
-	fn setup_logging(&self) {
-		if let Some(filter) = self.verbose.as_level_filter() {
-			tracing_subscriber::fmt().with_max_level(filter).init();
+	/// Directives in `RUST_LOG` take precedence, e.g.
+	/// `RUST_LOG=cmdlink::platform_binary=trace` traces link generation while
+	/// the rest of cmdlink logs at the level chosen by the flags.
+	///
+	/// Logs are written to stderr, and appended to the log file if one is
+	/// configured.
+	fn setup_logging(&self, settings: &Settings) {
+		let filter = EnvFilter::builder()
+			.with_default_directive(self.verbose.as_level_filter().into())
+			.from_env_lossy();
+		let log_file = self.log_file.as_ref().or(settings.log_file.as_ref());
+		let file_layer = log_file.and_then(|path| match OpenOptions::new().create(true).append(true).open(path) {
+			Ok(file) => Some(fmt::layer().with_ansi(false).with_writer(Mutex::new(file))),
+			Err(why) => {
+				eprintln!("{}", tr!("log.file_failed", path = path.display(), error = why));
+				None
+			},
+		});
+		let color = settings.color.unwrap_or_default().enabled();
+
+		tracing_subscriber::registry()
+			.with(filter)
+			.with(fmt::layer().with_ansi(color).with_writer(std::io::stderr))
+			.with(file_layer)
+			.init();
+	}
+
+	/// The `[settings]` of the config, needed before the command runs to set up
+	/// logging and prompts.
+	fn settings(&self) -> Settings {
+		// Shims look up secrets and record runs on every invocation, which must
+		// not touch the config.
+		if matches!(
+			self.subcommand,
+			Commands::Secret { .. }
+				| Commands::Record { .. }
+				| Commands::OpenUrl { .. }
+				| Commands::CheckVersion { .. }
+		) {
+			return Settings::default();
 		}
+		// Only the table itself is read, as the whole config is loaded again for
+		// the command, which reports any errors in it.
+		config::file_settings(paths::get())
 	}
 
-	/// Runs the CLI application by processing the provided command-line
-	/// arguments.
-	pub fn run() -> Result<()> {
-		let cli = Cli::parse();
-		cli.setup_logging();
+	/// The format fatal errors are reported in.
+	pub fn output(&self) -> OutputFormat { self.output }
+
+	/// Whether or not cmdlink may interact with the user, which is disabled by
+	/// `--non-interactive` or a `CMDLINK_NONINTERACTIVE` value other than
+	/// empty, `0` or `false`.
+	fn interactive(&self) -> bool {
+		let from_env = std::env::var("CMDLINK_NONINTERACTIVE")
+			.is_ok_and(|value| !matches!(value.trim().to_ascii_lowercase().as_str(), "" | "0" | "false"));
+		!(self.non_interactive || from_env)
+	}
+
+	/// Runs the CLI application with the parsed command-line arguments.
+	pub fn run(self) -> Result<()> {
+		// The portable marker has to exist before the project directory is
+		// first resolved, which already happens while looking up the
+		// log file.
+		if let Commands::Init { portable: true, .. } = self.subcommand {
+			cmdlink::enable_portable()?;
+		}
+		// The same goes for moving the project directory, so that the config is
+		// loaded from its new location.
+		let migrated_from = match &self.subcommand {
+			Commands::MigrateHome { dir, .. } => Some(paths::migrate(dir)?),
+			_ => None,
+		};
+		let settings = self.settings();
+		self.setup_logging(&settings);
+		let interactive = self.interactive();
+		let confirm_mode = settings.confirm.unwrap_or_default();
 
-		// Cfg must be after logging setup to ensure logging is initialized
-		let mut cfg = Config::new()?;
+		// Commands that don't operate on the config are dispatched before
+		// loading it.
+		let command = match self.subcommand {
+			// Plugins manage their own state.
+			Commands::External(args) => return run_plugin(args),
+			// The watcher reloads the config on every change, so it doesn't hold one.
+			Commands::Watch { debounce_ms } => return watch::watch(Duration::from_millis(debounce_ms)),
+			// Secrets are looked up by shims on every invocation, which must not touch
+			// the config.
+			Commands::Secret { reference } => {
+				print!("{}", SecretRef::parse(&reference)?.resolve()?);
+				return Ok(());
+			},
+			Commands::Record {
+				alias,
+				status,
+				elapsed,
+				args,
+			} => return runs::record(paths::get(), &alias, status, elapsed, args),
+			Commands::OpenUrl { url, args } => return open::in_browser(&open::url_with_args(&url, &args)),
+			Commands::CheckVersion { alias, require, cmd } => return version::check(&alias, &cmd, &require),
+			Commands::Service { command } => {
+				match command {
+					ServiceCommand::Install => info!("{}", tr!("service.installed", name = service::install()?)),
+					ServiceCommand::Uninstall => {
+						service::uninstall()?;
+						info!("{}", tr!("service.removed"));
+					},
+				}
+				return Ok(());
+			},
+			Commands::Env => {
+				print!("{}", user_path::diagnose(&platform_binary::bins_dir()));
+				return Ok(());
+			},
+			Commands::Completions { command } => {
+				let shell = |shell: Option<CompletionShell>| {
+					shell
+						.map(cli_completion::Shell::from)
+						.or_else(cli_completion::Shell::detect)
+						.ok_or(Error::ShellUnknown)
+				};
+				match command {
+					CompletionsCommand::Print { shell: name } => {
+						print!("{}", cli_completion::script(Cli::command(), shell(name)?));
+					},
+					CompletionsCommand::Install {
+						shell: name,
+						uninstall: false,
+					} => {
+						let shell = shell(name)?;
+						let path = cli_completion::install(Cli::command(), shell)?;
+						info!("{}", tr!("completions.installed", shell = shell, path = path.display()));
+						if shell == cli_completion::Shell::Zsh {
+							info!(
+								"{}",
+								tr!("completions.zsh_fpath", dir = path.parent().unwrap_or(&path).display())
+							);
+						}
+					},
+					CompletionsCommand::Install {
+						shell: name,
+						uninstall: true,
+					} => {
+						let shell = shell(name)?;
+						match cli_completion::uninstall(shell)? {
+							Some(path) => info!("{}", tr!("completions.removed", path = path.display())),
+							None => info!("{}", tr!("completions.not_installed", shell = shell)),
+						}
+					},
+				}
+				return Ok(());
+			},
+			Commands::Autorun {
+				command: AutorunCommand::Uninstall,
+			} => {
+				match autorun::unregister(paths::get())? {
+					true => info!("{}", tr!("autorun.removed")),
+					false => info!("{}", tr!("autorun.not_registered")),
+				}
+				return Ok(());
+			},
+			Commands::Init {
+				path, no_path, machine, ..
+			} => {
+				let scope = if machine {
+					PathScope::Machine
+				} else {
+					settings.path_scope.unwrap_or_default()
+				};
+				let add_path = match (path, no_path) {
+					(true, _) => true,
+					(_, true) => false,
+					_ => ask(
+						interactive,
+						confirm_mode,
+						&tr!("init.path_prompt", dir = platform_binary::bins_dir().display(), scope = scope),
+						"Adding the bins directory to PATH",
+						"pass --path or --no-path",
+					)?,
+				};
+				return init(add_path.then_some(scope));
+			},
+			Commands::Open { bins, editor } => {
+				let path = if bins {
+					platform_binary::bins_dir()
+				} else {
+					paths::get().root().to_path_buf()
+				};
+				return if editor {
+					open::in_editor(&path)
+				} else {
+					open::in_file_manager(&path)
+				};
+			},
+			Commands::Config {
+				command: ConfigCommand::Edit,
+			} => {
+				if !interactive {
+					return Err(Error::NonInteractive(
+						"Editing the config",
+						"edit config.toml directly and run `cmdlink refresh`",
+					));
+				}
+				return edit_config();
+			},
+			Commands::Config {
+				command: ConfigCommand::Fmt { check },
+			} => {
+				if Config::format_file(paths::get(), check)? {
+					eprintln!("{}", tr!("config.formatted", path = paths::get().config_file().display()));
+				}
+				return Ok(());
+			},
+			command => command,
+		};
+
+		// Cfg must be after logging setup to ensure logging is initialized.
+		// Commands that only read the config skip link checks and never
+		// save.
+		let mut cfg = if command.is_read_only() {
+			Config::read_only()?
+		} else {
+			Config::new()?
+		};
 
-		match cli.subcommand {
-			Commands::Refresh => cfg.refresh_links()?,
+		match command {
+			Commands::Refresh { force, prune } => cfg.refresh_links(force, prune, |link, expected| {
+				keep_link(link, expected, interactive, confirm_mode)
+			})?,
 			Commands::Add {
 				alias,
 				description,
 				cmd,
+				from_clipboard,
+				from_history,
+				url,
+				dir,
+				activate,
+				release,
+				asset,
+				bin,
+				sha256,
+				min_version,
+				version_cmd,
+				tags,
+				env,
+				secret,
+				env_file,
+				capture,
+				group,
+				cwd,
+				shell,
+				no_forward_args,
+				auto_desc,
 				force,
-			} => cfg.create_alias(alias, cmd, description, force)?,
-			Commands::Remove { alias } => cfg.remove_alias(&alias)?,
-			Commands::Display => cfg.display_aliases(),
+			} => {
+				let cmd = match cmd {
+					Some(cmd) => cmd,
+					None if url.is_some() || dir.is_some() || release.is_some() => String::new(),
+					None if from_clipboard => {
+						// Copied commands often end with the newline of the line
+						// they were copied from.
+						let cmd = clipboard::paste()?.trim().to_string();
+						info!("{}", tr!("add.from_clipboard", cmd = cmd));
+						cmd
+					},
+					None => {
+						let Some(limit) = from_history else {
+							unreachable!(
+								"clap requires --cmd, --url, --dir, --release, --from-clipboard or --from-history"
+							);
+						};
+						if !interactive || !std::io::stdin().is_terminal() {
+							return Err(Error::NonInteractive("Picking a command from history", "pass --cmd"));
+						}
+						let commands = history::recent(history::read_all(&history::files()), limit);
+						if commands.is_empty() {
+							return Err(Error::EmptyHistory);
+						}
+						let Some(cmd) = choose_command(commands) else {
+							return Ok(());
+						};
+						cmd
+					},
+				};
+				let description = if auto_desc {
+					let description = describe::from_help(&cmd);
+					if description.is_none() {
+						warn!("{}", tr!("add.no_description", cmd = cmd));
+					}
+					description
+				} else {
+					description
+				};
+				// Most tools print their version with `--version`.
+				let min_version = min_version.map(|require| {
+					let version_cmd = version_cmd
+						.or_else(|| Some(format!("{} --version", cmd.split_whitespace().next()?)))
+						.unwrap_or_default();
+					(version_cmd, require)
+				});
+				let mut builder = match (url, dir, release) {
+					(Some(url), ..) => AliasBuilder::url(alias, url),
+					(_, Some(dir), _) => match activate {
+						Some(activate) => AliasBuilder::dir(alias, dir).activate(activate),
+						None => AliasBuilder::dir(alias, dir),
+					},
+					(.., Some(release)) => AliasBuilder::release(alias, release),
+					(None, None, None) => AliasBuilder::new(alias, cmd),
+				};
+				if let Some(asset) = asset {
+					builder = builder.asset(asset);
+				}
+				if let Some(bin) = bin {
+					builder = builder.bin(bin);
+				}
+				if let Some(sha256) = sha256 {
+					builder = builder.sha256(sha256);
+				}
+				let builder = env
+					.into_iter()
+					.fold(builder, |builder, (key, value)| builder.env(key, value));
+				let mut builder = secret
+					.into_iter()
+					.fold(builder, |builder, (key, reference)| builder.secret(key, reference));
+				if let Some(env_file) = env_file {
+					builder = builder.env_file(env_file);
+				}
+				if let Some(capture) = capture {
+					builder = builder.capture(capture);
+				}
+				if let Some(group) = group {
+					builder = builder.group(group);
+				}
+				if let Some(cwd) = cwd {
+					builder = builder.cwd(cwd);
+				}
+				if let Some(shell) = shell {
+					builder = builder.shell(shell);
+				}
+				if let Some((version_cmd, require)) = min_version {
+					builder = builder.min_version(version_cmd, require);
+				}
+				if no_forward_args {
+					builder = builder.forward_args(false);
+				}
+				let spec = builder.maybe_description(description).tags(tags).build()?;
+				cfg.create_alias(spec, force)?
+			},
+			Commands::Remove { alias, ignore_missing } => match cfg.remove_alias(&alias) {
+				Err(Error::UnknownAlias(_)) if ignore_missing => {
+					info!("{}", tr!("remove.missing", alias = alias))
+				},
+				result => result?,
+			},
+			Commands::Display { tree: true, broken, .. } => cfg.display_tree(broken),
+			Commands::Display { long, full, broken, .. } => cfg.display_aliases(long, full, broken),
+			Commands::Show { alias } => cfg.show_alias(&alias)?,
+			Commands::CopyCmd { alias } => {
+				let values = cfg.alias(&alias).ok_or_else(|| Error::UnknownAlias(alias.clone()))?;
+				clipboard::copy(values.target())?;
+				eprintln!("{}", tr!("copy.copied", alias = alias));
+			},
+			Commands::Test { alias, args } => {
+				let values = cfg.alias(&alias).ok_or_else(|| Error::UnknownAlias(alias.clone()))?;
+				let link = values.link(&alias, cfg.links());
+				eprintln!("{}", tr!("test.script", path = link.file_path().display()));
+				println!("{}\n", link.contents()?);
+				eprintln!("{}", tr!("test.running", alias = alias));
+				let status = trace::run(&link, &args)?;
+				if !status.success() {
+					std::process::exit(status.code().unwrap_or(1));
+				}
+			},
+			Commands::Run {
+				sandbox,
+				allow_env,
+				temp_dir,
+				alias,
+				args,
+			} => {
+				let values = cfg.alias(&alias).ok_or_else(|| Error::UnknownAlias(alias.clone()))?;
+				let link = values.link(&alias, cfg.links());
+				let status = if sandbox {
+					Sandbox { allow_env, temp_dir }.run(&link, &args)?
+				} else {
+					trace::run_script(&link, &link.contents()?, &args, |_| {})?
+				};
+				if !status.success() {
+					std::process::exit(status.code().unwrap_or(1));
+				}
+			},
+			Commands::Bench { alias, runs } => {
+				let timings = match &alias {
+					Some(alias) => {
+						let values = cfg.alias(alias).ok_or_else(|| Error::UnknownAlias(alias.clone()))?;
+						bench::run(&values.link(alias, cfg.links()), true, runs)?
+					},
+					None => {
+						eprintln!("{}", tr!("bench.no_dispatcher"));
+						bench::run(&bench::trivial().link(bench::TRIVIAL_ALIAS, cfg.links()), false, runs)?
+					},
+				};
+				eprintln!(
+					"{}",
+					tr!(
+						"bench.timed",
+						runs = runs,
+						alias = alias.as_deref().unwrap_or(bench::TRIVIAL_ALIAS)
+					)
+				);
+				cfg.display_bench(&timings);
+			},
+			Commands::History {
+				alias,
+				failed,
+				limit,
+				json,
+			} => {
+				let mut runs: Vec<Run> = runs::read(cfg.paths())?
+					.into_iter()
+					.filter(|run| alias.as_ref().is_none_or(|alias| run.alias == *alias) && (!failed || run.failed()))
+					.collect();
+				runs.drain(..runs.len().saturating_sub(limit));
+				if json {
+					println!("{}", serde_json::to_string_pretty(&runs)?);
+				} else {
+					cfg.display_runs(&runs);
+				}
+			},
+			Commands::Last { pick, limit } => {
+				let runs = runs::latest(cfg.paths(), if pick { limit } else { 1 })?;
+				let run = if pick {
+					if !interactive || !std::io::stdin().is_terminal() {
+						return Err(Error::NonInteractive("Picking a run", "run `cmdlink last` without --pick"));
+					}
+					choose_run(runs)
+				} else {
+					runs.into_iter().next()
+				};
+				let Some(run) = run else {
+					return Ok(());
+				};
+
+				let values = cfg
+					.alias(&run.alias)
+					.ok_or_else(|| Error::UnknownAlias(run.alias.clone()))?;
+				let link = values.link(&run.alias, cfg.links());
+				eprintln!("{}", run.command_line());
+				let status = trace::run_script(&link, &link.contents()?, &run.args, |_| {})?;
+				if !status.success() {
+					std::process::exit(status.code().unwrap_or(1));
+				}
+			},
+			Commands::Stale { days, remove } => {
+				if !cfg.settings().run_history.unwrap_or_default() {
+					warn!("{}", tr!("stale.no_history"));
+				}
+				let since = SystemTime::now()
+					.checked_sub(Duration::from_secs(days.saturating_mul(24 * 60 * 60)))
+					.unwrap_or(SystemTime::UNIX_EPOCH);
+				let stale = cfg.stale_aliases(&runs::read(cfg.paths())?, since);
+				if stale.is_empty() {
+					eprintln!("{}", tr!("stale.none", days = days));
+					return Ok(());
+				}
+				cfg.display_stale(&stale);
+				if remove {
+					for (alias, _) in stale {
+						if ask(
+							interactive,
+							confirm_mode,
+							&tr!("stale.remove_prompt", alias = alias),
+							"Removing stale aliases",
+							"remove them with `cmdlink remove` instead",
+						)? {
+							cfg.remove_alias(&alias)?;
+						}
+					}
+				}
+			},
+			Commands::Gc { dry_run } => {
+				let garbage = gc::collect(&cfg, Retention::from_settings(cfg.settings())?, dry_run)?;
+				if garbage.is_empty() {
+					eprintln!("{}", tr!("gc.nothing"));
+				}
+				let report = |count: usize, removed: String, would_remove: String| {
+					if count > 0 {
+						eprintln!("{}", if dry_run { would_remove } else { removed });
+					}
+				};
+				let (runs, releases, copies) = (garbage.runs, garbage.releases.len(), garbage.link_copies.len());
+				report(runs, tr!("gc.runs", count = runs), tr!("gc.would_runs", count = runs));
+				report(
+					releases,
+					tr!("gc.releases", count = releases),
+					tr!("gc.would_releases", count = releases),
+				);
+				report(
+					copies,
+					tr!("gc.link_copies", count = copies),
+					tr!("gc.would_link_copies", count = copies),
+				);
+			},
+			Commands::Path { alias } => {
+				let path = match alias {
+					Some(alias) => cfg
+						.alias(&alias)
+						.map(|values| values.link(&alias, cfg.links()).file_path().to_path_buf())
+						.ok_or(Error::UnknownAlias(alias))?,
+					None => cfg.links().bins_dir().to_path_buf(),
+				};
+				println!("{}", path.display());
+			},
+			Commands::Relocate { dir, no_path } => {
+				let old_dir = cfg.links().bins_dir().to_path_buf();
+				let new_dir = cfg.relocate_bins(&dir)?;
+				eprintln!("{}", tr!("refresh.relocated", dir = new_dir.display()));
+				if !no_path {
+					relocate_path(&old_dir, &new_dir, cfg.settings().path_scope.unwrap_or_default())?;
+				}
+			},
+			Commands::MigrateHome { no_path, .. } => {
+				let Some(old) = migrated_from else {
+					unreachable!("the project directory is moved before the config is loaded");
+				};
+				let new = cfg.paths().clone();
+				eprintln!(
+					"{}",
+					tr!("migrate.moved", from = old.root().display(), to = new.root().display())
+				);
+				// Links may contain paths into the project directory, such as those
+				// of chained aliases and `{cmdlink_dir}` placeholders.
+				cfg.refresh_links(true, false, |_, _| Ok(false))?;
+				if !no_path && cfg.settings().bins_dir.is_none() {
+					let scope = cfg.settings().path_scope.unwrap_or_default();
+					relocate_path(&old.default_bins_dir(), &new.default_bins_dir(), scope)?;
+				}
+				// The AutoRun value keeps calling the old script, which it skips
+				// now that it's gone.
+				if cfg!(target_os = "windows") && autorun::script_path(&new).exists() {
+					autorun::register(&new)?;
+				}
+				if completion::loader_path(&new).exists() {
+					warn!(
+						"{}",
+						tr!(
+							"migrate.source",
+							old = old.root().join(completion::LOADER_NAME).display(),
+							new = completion::loader_path(&new).display()
+						)
+					);
+				}
+			},
+			Commands::Merge { path, on_conflict } => {
+				let report = cfg.merge(&path, |existing, spec| on_conflict.resolve(existing, spec, interactive))?;
+				print!("{report}");
+			},
+			Commands::Why { command } => cfg.display_dependents(&command),
+			Commands::Autorun {
+				command: AutorunCommand::Install { no_register },
+			} => {
+				let path = autorun::write(&cfg)?;
+				info!("{}", tr!("autorun.written", path = path.display()));
+				if !no_register {
+					match autorun::register(cfg.paths())? {
+						true => info!("{}", tr!("autorun.registered")),
+						false => info!("{}", tr!("autorun.already_registered")),
+					}
+				}
+			},
+			Commands::Graph { dot } => {
+				let graph = Graph::new(&cfg);
+				if dot {
+					println!("{}", graph.to_dot());
+				} else if graph.is_empty() {
+					eprintln!("{}", tr!("graph.empty"));
+				} else {
+					print!("{graph}");
+				}
+			},
+			Commands::Suggest {
+				files,
+				min_count,
+				min_length,
+				limit,
+			} => {
+				let files = if files.is_empty() { history::files() } else { files };
+				let commands = files.iter().flat_map(|file| history::read(file));
+				let frequent = history::frequent(commands, min_length, min_count);
+				suggest_aliases(&mut cfg, frequent, limit, interactive)?;
+			},
+			Commands::Adopt => adopt_scripts(&mut cfg, interactive)?,
+			Commands::Export {
+				dotfiles,
+				archive,
+				omz_plugin,
+				layout,
+			} => match (dotfiles, archive, omz_plugin) {
+				(Some(dotfiles), ..) => {
+					for path in dotfiles::export(&cfg, &dotfiles, layout.into())? {
+						eprintln!("{}", tr!("export.wrote", path = path.display()));
+					}
+				},
+				(None, Some(archive), _) => {
+					let links = archive::export(&cfg, &archive)?;
+					eprintln!("{}", tr!("export.bundled", path = archive.display(), count = links));
+				},
+				(None, None, Some(dir)) => {
+					let plugin = omz::export(&cfg, &dir)?;
+					for alias in &plugin.skipped {
+						warn!("{}", tr!("export.omz_skipped", alias = alias));
+					}
+					eprintln!(
+						"{}",
+						tr!(
+							"export.omz_plugin",
+							path = plugin.path.display(),
+							count = plugin.aliases,
+							name = plugin.name
+						)
+					);
+				},
+				(None, None, None) => unreachable!("clap requires --dotfiles, --archive or --omz-plugin"),
+			},
+			Commands::Report { format, out } => {
+				let report = report::render(&cfg, format.into());
+				match out {
+					Some(path) => {
+						std::fs::write(&path, report).map_err(|e| Error::Export(path.clone(), e.to_string()))?;
+						eprintln!("{}", tr!("export.wrote", path = path.display()));
+					},
+					None => print!("{report}"),
+				}
+			},
+			Commands::SuggestName { cmd, count } => {
+				let names = suggest::names(&cmd, count, |name| cfg.alias(name).is_some());
+				if names.is_empty() {
+					eprintln!("{}", tr!("suggest.no_names", cmd = cmd));
+				}
+				for name in names {
+					println!("{name}");
+				}
+			},
+			Commands::Config { command } => match command {
+				ConfigCommand::Cat { json } => println!("{}", cfg.serialize(json)?),
+				ConfigCommand::Get { key: Some(key) } => {
+					if let Some(value) = cfg.settings().get(&key)? {
+						println!("{value}");
+					}
+				},
+				ConfigCommand::Get { key: None } => {
+					for key in settings::SETTINGS {
+						println!("{key} = {}", cfg.settings().get(key)?.unwrap_or_default());
+					}
+				},
+				ConfigCommand::Set { key, value } => cfg.set_setting(&key, Some(&value))?,
+				ConfigCommand::Unset { key } => cfg.set_setting(&key, None)?,
+				ConfigCommand::Edit | ConfigCommand::Fmt { .. } => unreachable!("handled before the config is loaded"),
+			},
+			Commands::Pack { command } => match command {
+				PackCommand::Install {
+					source,
+					prefix,
+					force,
+					on_conflict,
+				} => {
+					let source = pack::normalize_source(&source);
+					let pack = Pack::load(&source)?;
+					let strategy = on_conflict.or(force.then_some(OnConflict::Overwrite));
+					let links = cfg.links().clone();
+					let report =
+						cfg.install_pack(&pack, &source, prefix.as_deref(), |existing, spec| match strategy {
+							Some(strategy) => strategy.resolve(existing, spec, interactive),
+							None => Err(Error::LinkAlreadyExists(spec.name.clone(), links.link_path(&spec.name))),
+						})?;
+					print!("{report}");
+				},
+				PackCommand::Update { name } => cfg.update_pack(&name)?,
+				PackCommand::Remove { name } => cfg.remove_pack(&name)?,
+				PackCommand::List => cfg.display_packs(),
+				PackCommand::Create {
+					name,
+					tags,
+					aliases,
+					version,
+					description,
+					prefix,
+					platforms,
+					output,
+				} => {
+					let output = output.unwrap_or_else(|| pack::packs_dir().join(format!("{name}.toml")));
+					let manifest = PackManifest {
+						name,
+						version,
+						description,
+						prefix,
+						compatibility: Compatibility {
+							min_version: None,
+							platforms,
+						},
+					};
+					cfg.create_pack(manifest, &tags, &aliases)?.save(&output)?;
+					info!("{}", tr!("pack.written", path = output.display()));
+				},
+			},
+			Commands::External(_)
+			| Commands::Secret { .. }
+			| Commands::Record { .. }
+			| Commands::OpenUrl { .. }
+			| Commands::CheckVersion { .. }
+			| Commands::Watch { .. }
+			| Commands::Service { .. }
+			| Commands::Env
+			| Commands::Completions { .. }
+			| Commands::Autorun {
+				command: AutorunCommand::Uninstall,
+			}
+			| Commands::Open { .. }
+			| Commands::Init { .. } => {
+				unreachable!("handled before the config is loaded")
+			},
+		}
+		cfg.save()
+	}
+}
+
+/// Runs an external `cmdlink-<name>` plugin, passing the remaining arguments
+/// through and exiting with the plugin's exit code.
+///
+/// The plugin receives the location of the cmdlink directories through the
+/// `CMDLINK_HOME`, `CMDLINK_CONFIG` and `CMDLINK_BINS` environment variables.
+fn run_plugin(args: Vec<OsString>) -> Result<()> {
+	let mut args = args.into_iter();
+	let name = args.next().unwrap_or_default().to_string_lossy().into_owned();
+	let plugin =
+		which::find_executable(&format!("cmdlink-{name}")).ok_or_else(|| Error::PluginNotFound(name.clone()))?;
+	debug!("Running plugin {}", plugin.display());
+
+	let status = Command::new(&plugin)
+		.args(args)
+		.env("CMDLINK_HOME", paths::get().root())
+		.env("CMDLINK_CONFIG", paths::get().config_file())
+		.env("CMDLINK_BINS", platform_binary::bins_dir())
+		.status()
+		.map_err(|e| Error::PluginSpawn(name, e))?;
+	std::process::exit(status.code().unwrap_or(1));
+}
+
+/// Sets up the project directory and config, and adds the bins directory to
+/// the PATH of the given scope, if any.
+fn init(path_scope: Option<PathScope>) -> Result<()> {
+	let bins_dir = platform_binary::bins_dir();
+	std::fs::create_dir_all(&bins_dir).map_err(|e| Error::ProjectDirCreation(bins_dir.clone(), e))?;
+	drop(Config::new()?);
+	eprintln!("{}", tr!("init.initialized", path = paths::get().root().display()));
+
+	let Some(scope) = path_scope else {
+		return Ok(());
+	};
+	if user_path::add(&bins_dir, scope)? {
+		eprintln!("{}", tr!("path.added", dir = bins_dir.display(), scope = scope));
+	} else {
+		eprintln!("{}", tr!("path.present", dir = bins_dir.display(), scope = scope));
+	}
+	Ok(())
+}
+
+/// Replaces the old bins directory with the new one on the PATH of the scope.
+fn relocate_path(old_dir: &Path, new_dir: &Path, scope: PathScope) -> Result<()> {
+	if user_path::remove(old_dir, scope)? {
+		eprintln!("{}", tr!("path.removed", dir = old_dir.display(), scope = scope));
+	}
+	if user_path::add(new_dir, scope)? {
+		eprintln!("{}", tr!("path.added", dir = new_dir.display(), scope = scope));
+	} else {
+		eprintln!("{}", tr!("path.present", dir = new_dir.display(), scope = scope));
+	}
+	Ok(())
+}
+
+/// Proposes aliases for the most frequent commands that aren't aliased yet.
+/// On a terminal each one is offered for adding, otherwise they are printed as
+/// `[aliases]` entries to paste into the config. With the `confirm` setting
+/// set to `yes` all of them are added, and with `no` they are only printed.
+fn suggest_aliases(cfg: &mut Config, frequent: Vec<Frequent>, limit: usize, interactive: bool) -> Result<()> {
+	let aliased: HashSet<String> = cfg
+		.aliases()
+		.map(|(_, values)| values.effective_cmd().to_string())
+		.collect();
+	let mode = cfg.settings().confirm.unwrap_or_default();
+	let prompt = interactive && std::io::stdin().is_terminal();
+	let mut suggested = 0;
+
+	for Frequent { cmd, count } in frequent.into_iter().filter(|f| !aliased.contains(&f.cmd)) {
+		if suggested == limit {
+			break;
+		}
+		let Some(name) = suggest::names(&cmd, 1, |name| cfg.alias(name).is_some()).pop() else {
+			continue;
+		};
+		suggested += 1;
+
+		if mode == ConfirmMode::No || (mode == ConfirmMode::Ask && !prompt) {
+			println!("{name} = {{ cmd = {} }} # typed {count} times", toml::Value::String(cmd));
+		} else if mode == ConfirmMode::Yes || confirm(&tr!("suggest.prompt", alias = name, cmd = cmd, count = count)) {
+			cfg.create_alias(AliasBuilder::new(name.as_str(), cmd).build()?, false)?;
+			eprintln!("{}", tr!("suggest.added", alias = name));
+		}
+	}
+	if suggested == 0 {
+		eprintln!("{}", tr!("suggest.no_commands"));
+	}
+	Ok(())
+}
+
+/// Offers to turn the unmanaged scripts in the bins directory into aliases.
+/// Their files are replaced with regular links once adopted.
+fn adopt_scripts(cfg: &mut Config, interactive: bool) -> Result<()> {
+	let mode = cfg.settings().confirm.unwrap_or_default();
+	let prompt = interactive && std::io::stdin().is_terminal();
+	let files = cfg.unmanaged_files()?;
+	if files.is_empty() {
+		eprintln!("{}", tr!("adopt.none", dir = cfg.links().bins_dir().display()));
+	}
+
+	for path in files {
+		let name = match platform_binary::alias_of(&path) {
+			Some(name) => name.to_string(),
+			None => path.file_stem().unwrap_or_default().to_string_lossy().into_owned(),
+		};
+		let spec = match std::fs::read(&path).map(|script| adopt::parse(&name, &String::from_utf8_lossy(&script))) {
+			Ok(Some(Ok(spec))) => spec,
+			Ok(Some(Err(why))) => {
+				warn!("{}", tr!("adopt.skipped", path = path.display(), reason = why));
+				continue;
+			},
+			Ok(None) => {
+				eprintln!("{}", tr!("adopt.unparsed", path = path.display()));
+				continue;
+			},
+			Err(e) => {
+				warn!("{}", tr!("adopt.skipped", path = path.display(), reason = e));
+				continue;
+			},
+		};
+
+		if mode == ConfirmMode::No || (mode == ConfirmMode::Ask && !prompt) {
+			println!("{} = {{ cmd = {} }}", spec.name, toml::Value::String(spec.cmd));
+		} else if mode == ConfirmMode::Yes || confirm(&tr!("adopt.prompt", path = path.display(), cmd = spec.cmd)) {
+			let name = spec.name.clone();
+			// The link overwrites the script, unless it's named differently,
+			// e.g. a `.cmd` script while links are `.bat` files.
+			if path != cfg.links().link_path(&name) {
+				std::fs::remove_file(&path).map_err(|e| Error::LinkRemoval(name.clone(), path.clone(), e))?;
+			}
+			cfg.create_alias(spec, true)?;
+			eprintln!("{}", tr!("adopt.adopted", alias = name));
+		}
+	}
+	Ok(())
+}
+
+/// Opens the config file in the user's editor until it's valid, then brings
+/// the links in line with it.
+fn edit_config() -> Result<()> {
+	let config_file_path = paths::get().config_file();
+	let mut cfg = loop {
+		open::in_editor(&config_file_path)?;
+		match Config::unchecked().and_then(|cfg| cfg.validate().map(|_| cfg)) {
+			Ok(cfg) => break cfg,
+			// Only offer to fix the config when someone is there to answer.
+			Err(why) if std::io::stdin().is_terminal() => {
+				error!("{why}");
+				if !confirm(&tr!("edit.again")) {
+					return Err(why);
+				}
+			},
+			Err(why) => return Err(why),
+		}
+	};
+
+	for orphan in cfg.orphaned_files()? {
+		warn!("{}", tr!("refresh.orphaned", path = orphan.display()));
+	}
+	if cfg.sync_links()? == 0 {
+		info!("{}", tr!("refresh.up_to_date"));
+	}
+	Ok(())
+}
+
+/// Asks the user a yes/no question, failing with an error naming the action and
+/// its non-interactive alternative if no one can answer it. The `confirm`
+/// setting can answer the question up front instead.
+fn ask(
+	interactive: bool, mode: ConfirmMode, prompt: &str, action: &'static str, alternative: &'static str,
+) -> Result<bool> {
+	match mode {
+		ConfirmMode::Yes => return Ok(true),
+		ConfirmMode::No => return Ok(false),
+		ConfirmMode::Ask => {},
+	}
+	if !interactive || !std::io::stdin().is_terminal() {
+		return Err(Error::NonInteractive(action, alternative));
+	}
+	Ok(confirm(prompt))
+}
+
+/// Asks the user a yes/no question on the terminal, defaulting to yes. Returns
+/// `false` if the answer can't be read.
+fn confirm(prompt: &str) -> bool {
+	eprint!("{}", tr!("prompt.confirm", prompt = prompt));
+	let _ = std::io::stderr().flush();
+	let mut answer = String::new();
+	match std::io::stdin().read_line(&mut answer) {
+		Ok(0) | Err(_) => false,
+		Ok(_) => !matches!(answer.trim().to_ascii_lowercase().as_str(), "n" | "no"),
+	}
+}
+
+/// Asks the user how to handle an imported alias that already exists,
+/// defaulting to skipping it.
+fn choose_resolution(existing: &AliasSpec, incoming: &AliasSpec) -> Result<Resolution> {
+	let diff = format!(
+		"--- {}\n+++ {}\n{}",
+		tr!("prompt.diff_current", alias = existing.name),
+		tr!("prompt.diff_incoming", alias = incoming.name),
+		diff::lines(&existing.to_toml()?, &incoming.to_toml()?)
+	);
+	let answers = [
+		(["s", "skip"], Resolution::Skip),
+		(["o", "overwrite"], Resolution::Overwrite),
+		(["r", "rename"], Resolution::Rename),
+	];
+	let prompt = tr!("prompt.resolution", alias = incoming.name);
+	Ok(choose_with_diff(&prompt, &answers, &tr!("prompt.resolution_invalid"), &diff).unwrap_or(Resolution::Skip))
+}
+
+/// Decides whether to keep a link whose file differs from the contents it
+/// would be generated with, as it may have been edited by hand. On a terminal
+/// the user is asked, defaulting to keeping it. Otherwise the link is
+/// regenerated, unless the `confirm` setting is `no`.
+fn keep_link(link: &PlatformBinary, expected: &str, interactive: bool, mode: ConfirmMode) -> Result<bool> {
+	// Symlinks aren't edited by hand, and links turn into or from one when
+	// the alias' `symlink` changes.
+	if link.file_path().is_symlink() || link.symlink_target().is_some() {
+		return Ok(false);
+	}
+	if mode != ConfirmMode::Ask || !interactive || !std::io::stdin().is_terminal() {
+		return Ok(mode == ConfirmMode::No);
+	}
+	let actual = std::fs::read_to_string(link.file_path())
+		.map_err(|e| Error::LinkRead(link.alias().to_string(), link.file_path().to_path_buf(), e))?;
+	let diff = format!(
+		"--- {}\n+++ {}\n{}",
+		link.file_path().display(),
+		tr!("prompt.diff_generated"),
+		diff::lines(&actual, expected)
+	);
+	let answers = [(["k", "keep"], true), (["t", "take"], false)];
+	let prompt = tr!("prompt.link_conflict", alias = link.alias());
+	Ok(choose_with_diff(&prompt, &answers, &tr!("prompt.link_conflict_invalid"), &diff).unwrap_or(true))
+}
+
+/// Asks the user to pick one of the answers, each given with the words that
+/// pick it, until a valid one is given. An empty answer picks the first, and
+/// `d` or `diff` shows the diff between the versions being chosen from.
+/// Returns `None` if the answer can't be read.
+fn choose_with_diff<T: Copy, const N: usize>(
+	prompt: &str, answers: &[([&str; N], T)], invalid: &str, diff: &str,
+) -> Option<T> {
+	loop {
+		eprint!("{prompt}");
+		let _ = std::io::stderr().flush();
+		let mut answer = String::new();
+		if let Ok(0) | Err(_) = std::io::stdin().read_line(&mut answer) {
+			return None;
+		}
+		let answer = answer.trim().to_ascii_lowercase();
+		if answer.is_empty() {
+			return answers.first().map(|(_, value)| *value);
+		}
+		if matches!(answer.as_str(), "d" | "diff") {
+			eprint!("{diff}");
+			continue;
+		}
+		match answers.iter().find(|(words, _)| words.contains(&answer.as_str())) {
+			Some((_, value)) => return Some(*value),
+			None => eprintln!("{invalid}"),
+		}
+	}
+}
+
+/// Asks the user which of the given runs, newest first, to run again,
+/// defaulting to the newest. Returns `None` if the answer can't be read.
+fn choose_run(mut runs: Vec<Run>) -> Option<Run> {
+	for (i, run) in runs.iter().enumerate() {
+		eprintln!(
+			"{}",
+			tr!(
+				"prompt.run_entry",
+				index = format!("{:>3}", i + 1),
+				command = run.command_line(),
+				started = run.started,
+				status = run.status
+			)
+		);
+	}
+	loop {
+		eprint!("{}", tr!("prompt.run", count = runs.len()));
+		let _ = std::io::stderr().flush();
+		let mut answer = String::new();
+		if let Ok(0) | Err(_) = std::io::stdin().read_line(&mut answer) {
+			return None;
+		}
+		match answer.trim() {
+			"" => return Some(runs.swap_remove(0)),
+			answer => match answer.parse::<usize>() {
+				Ok(n) if (1..=runs.len()).contains(&n) => return Some(runs.swap_remove(n - 1)),
+				_ => eprintln!("{}", tr!("prompt.run_invalid", count = runs.len())),
+			},
 		}
-		Ok(())
+	}
+}
+
+/// Asks the user which of the given commands, newest first, to create the alias
+/// for, defaulting to the newest. Returns `None` if the answer can't be read.
+fn choose_command(mut commands: Vec<String>) -> Option<String> {
+	for (i, command) in commands.iter().enumerate() {
+		eprintln!(
+			"{}",
+			tr!("prompt.command_entry", index = format!("{:>3}", i + 1), command = command)
+		);
+	}
+	loop {
+		eprint!("{}", tr!("prompt.command", count = commands.len()));
+		let _ = std::io::stderr().flush();
+		let mut answer = String::new();
+		if let Ok(0) | Err(_) = std::io::stdin().read_line(&mut answer) {
+			return None;
+		}
+		match answer.trim() {
+			"" => return Some(commands.swap_remove(0)),
+			answer => match answer.parse::<usize>() {
+				Ok(n) if (1..=commands.len()).contains(&n) => return Some(commands.swap_remove(n - 1)),
+				_ => eprintln!("{}", tr!("prompt.run_invalid", count = commands.len())),
+			},
+		}
+	}
+}
+
+/// Parses a KEY=VALUE pair passed on the command line.
+fn parse_env_pair(s: &str) -> std::result::Result<(String, String), String> {
+	s.split_once('=')
+		.map(|(key, value)| (key.to_string(), value.to_string()))
+		.ok_or_else(|| format!("invalid KEY=VALUE pair: no `=` found in `{s}`"))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn env_pairs_split_at_the_first_equals_sign() {
+		let pair = |key: &str, value: &str| Ok((key.to_string(), value.to_string()));
+		assert_eq!(parse_env_pair("RUST_LOG=debug"), pair("RUST_LOG", "debug"));
+		assert_eq!(parse_env_pair("OPTS=--level=3"), pair("OPTS", "--level=3"));
+		assert_eq!(parse_env_pair("EMPTY="), pair("EMPTY", ""));
+		assert!(parse_env_pair("RUST_LOG").is_err());
 	}
 }
@@ -0,0 +1,378 @@
+//! Tab completion for cmdlink's own subcommands and flags, as printed and
+//! installed by `cmdlink completions`.
+//!
+//! The scripts are generated from the clap definition of the command line, so
+//! they list the subcommands at each level and the flags of the innermost
+//! subcommand typed so far, falling back to file names for anything else.
+//! Completion for the aliases themselves comes from the completion loader, see
+//! [completion](crate::completion).
+
+use std::{
+	fmt::{self, Write as _},
+	io::ErrorKind,
+	path::{Path, PathBuf},
+};
+
+use clap::Command;
+
+use crate::{error::Error, platform_binary::sh_quote, Result};
+
+/// The shells completion scripts can be generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+	/// bash, with the bash-completion package.
+	Bash,
+	/// zsh, with the completion system set up by `compinit`.
+	Zsh,
+	/// The friendly interactive shell.
+	Fish,
+	/// PowerShell 7, or Windows PowerShell.
+	PowerShell,
+}
+
+impl fmt::Display for Shell {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Shell::Bash => "bash",
+			Shell::Zsh => "zsh",
+			Shell::Fish => "fish",
+			Shell::PowerShell => "powershell",
+		})
+	}
+}
+
+impl Shell {
+	/// The shell of the user, from the login shell in `SHELL`, or PowerShell
+	/// on Windows. Returns `None` for shells without completion scripts.
+	pub fn detect() -> Option<Shell> {
+		if cfg!(target_os = "windows") {
+			return Some(Shell::PowerShell);
+		}
+		let shell = PathBuf::from(std::env::var_os("SHELL")?);
+		match shell.file_name()?.to_str()? {
+			"bash" => Some(Shell::Bash),
+			"zsh" => Some(Shell::Zsh),
+			"fish" => Some(Shell::Fish),
+			"pwsh" => Some(Shell::PowerShell),
+			_ => None,
+		}
+	}
+
+	/// Where the shell picks up the completion script from:
+	///
+	/// - bash: the user directory of bash-completion, which loads the script
+	///   the first time `cmdlink` is completed
+	/// - zsh: `~/.zsh/completions/_cmdlink`, which has to be on `fpath`
+	/// - fish: the user's `completions` directory
+	/// - PowerShell: a script next to the user's profile, which the profile
+	///   dot-sources
+	pub fn install_path(self) -> Result<PathBuf> {
+		let home =
+			dirs::home_dir().ok_or_else(|| Error::CompletionInstall(PathBuf::from("~"), ErrorKind::NotFound.into()))?;
+		let xdg = |var: &str, default: &str| std::env::var_os(var).map_or_else(|| home.join(default), PathBuf::from);
+		Ok(match self {
+			Shell::Bash => std::env::var_os("BASH_COMPLETION_USER_DIR")
+				.map_or_else(|| xdg("XDG_DATA_HOME", ".local/share").join("bash-completion"), PathBuf::from)
+				.join("completions")
+				.join("cmdlink"),
+			Shell::Zsh => home.join(".zsh").join("completions").join("_cmdlink"),
+			Shell::Fish => xdg("XDG_CONFIG_HOME", ".config")
+				.join("fish")
+				.join("completions")
+				.join("cmdlink.fish"),
+			Shell::PowerShell => profile_path(&home).with_file_name("cmdlink.completion.ps1"),
+		})
+	}
+}
+
+/// The path of the user's PowerShell profile, for the current host.
+fn profile_path(home: &Path) -> PathBuf {
+	let dir = match cfg!(target_os = "windows") {
+		true => dirs::document_dir()
+			.unwrap_or_else(|| home.join("Documents"))
+			.join("PowerShell"),
+		false => home.join(".config").join("powershell"),
+	};
+	dir.join("Microsoft.PowerShell_profile.ps1")
+}
+
+/// The subcommands and flags available after a sequence of subcommands.
+struct Level {
+	/// The subcommands leading to the level, each preceded by a space, or
+	/// empty for the top level.
+	path: String,
+	/// The subcommands of the level, with what they do.
+	commands: Vec<(String, String)>,
+	/// The long and short flags of the level, with what they do.
+	flags: Vec<(String, Option<char>, String)>,
+}
+
+/// Collects the levels of the command and all its visible subcommands.
+fn levels(command: &Command, path: String, levels: &mut Vec<Level>) {
+	let about = |about: Option<&clap::builder::StyledStr>| {
+		about
+			.map(|about| about.to_string().lines().next().unwrap_or_default().to_string())
+			.unwrap_or_default()
+	};
+	let subcommands: Vec<&Command> = command
+		.get_subcommands()
+		.filter(|subcommand| !subcommand.is_hide_set() && subcommand.get_name() != "help")
+		.collect();
+	levels.push(Level {
+		path: path.clone(),
+		commands: subcommands
+			.iter()
+			.map(|subcommand| (subcommand.get_name().to_string(), about(subcommand.get_about())))
+			.collect(),
+		flags: command
+			.get_arguments()
+			.filter(|arg| !arg.is_positional() && !arg.is_hide_set())
+			.filter_map(|arg| Some((arg.get_long()?.to_string(), arg.get_short(), about(arg.get_help()))))
+			.collect(),
+	});
+	for subcommand in subcommands {
+		self::levels(subcommand, format!("{path} {}", subcommand.get_name()), levels);
+	}
+}
+
+/// Renders the completion script for the command line in the given shell.
+pub fn script(mut command: Command, shell: Shell) -> String {
+	// Building propagates global flags to the subcommands.
+	command.build();
+	let mut all = Vec::new();
+	levels(&command, String::new(), &mut all);
+	match shell {
+		Shell::Bash => bash(&all),
+		Shell::Zsh => zsh(&all),
+		Shell::Fish => fish(&all),
+		Shell::PowerShell => powershell(&all),
+	}
+}
+
+/// Writes the completion script to where the shell picks it up, see
+/// [Shell::install_path], returning the path it was written to. For
+/// PowerShell, the profile is made to load it, unless it already does.
+pub fn install(command: Command, shell: Shell) -> Result<PathBuf> {
+	let path = shell.install_path()?;
+	let error = |path: &Path| {
+		let path = path.to_path_buf();
+		move |e| Error::CompletionInstall(path, e)
+	};
+	std::fs::create_dir_all(path.parent().unwrap_or(Path::new("."))).map_err(error(&path))?;
+	std::fs::write(&path, script(command, shell)).map_err(error(&path))?;
+	if shell == Shell::PowerShell {
+		let profile = path.with_file_name("Microsoft.PowerShell_profile.ps1");
+		let contents = std::fs::read_to_string(&profile).unwrap_or_default();
+		let line = source_line(&path);
+		if !contents.lines().any(|existing| existing.trim() == line) {
+			let separator = if contents.is_empty() || contents.ends_with('\n') {
+				""
+			} else {
+				"\n"
+			};
+			std::fs::write(&profile, format!("{contents}{separator}{line}\n")).map_err(error(&profile))?;
+		}
+	}
+	Ok(path)
+}
+
+/// Removes the completion script installed by [install], and the line loading
+/// it from the PowerShell profile, returning its path if it was installed.
+pub fn uninstall(shell: Shell) -> Result<Option<PathBuf>> {
+	let path = shell.install_path()?;
+	if shell == Shell::PowerShell {
+		let profile = path.with_file_name("Microsoft.PowerShell_profile.ps1");
+		if let Ok(contents) = std::fs::read_to_string(&profile) {
+			let line = source_line(&path);
+			let kept: String = contents
+				.lines()
+				.filter(|existing| existing.trim() != line)
+				.map(|existing| format!("{existing}\n"))
+				.collect();
+			if kept.len() != contents.len() {
+				std::fs::write(&profile, kept).map_err(|e| Error::CompletionInstall(profile.clone(), e))?;
+			}
+		}
+	}
+	match std::fs::remove_file(&path) {
+		Ok(()) => Ok(Some(path)),
+		Err(e) if e.kind() == ErrorKind::NotFound => Ok(None),
+		Err(e) => Err(Error::CompletionInstall(path, e)),
+	}
+}
+
+/// The line of the PowerShell profile loading the completion script.
+fn source_line(path: &Path) -> String { format!(". '{}'", path.display().to_string().replace('\'', "''")) }
+
+/// The flags of a level as words to complete, long ones first.
+fn flag_words(level: &Level) -> Vec<String> {
+	let mut words: Vec<String> = level.flags.iter().map(|(long, ..)| format!("--{long}")).collect();
+	words.extend(
+		level
+			.flags
+			.iter()
+			.filter_map(|(_, short, _)| Some(format!("-{}", (*short)?))),
+	);
+	words
+}
+
+/// The bash script, which bash-completion loads as `cmdlink` is completed.
+fn bash(levels: &[Level]) -> String {
+	let paths: Vec<String> = levels.iter().skip(1).map(|level| sh_quote(&level.path)).collect();
+	let mut script = String::from(
+		"# Generated by cmdlink.\n\
+		 _cmdlink() {\n\
+		 \tlocal cur=\"${COMP_WORDS[COMP_CWORD]}\" path=\"\" word i commands flags\n\
+		 \tfor ((i = 1; i < COMP_CWORD; i++)); do\n\
+		 \t\tword=\"${COMP_WORDS[i]}\"\n\
+		 \t\tcase \"$path $word\" in\n",
+	);
+	let _ = writeln!(script, "\t\t\t{}) path=\"$path $word\" ;;", paths.join("|"));
+	script.push_str("\t\tesac\n\tdone\n\tcase \"$path\" in\n");
+	for level in levels {
+		let commands: Vec<&str> = level.commands.iter().map(|(name, _)| name.as_str()).collect();
+		let _ = writeln!(
+			script,
+			"\t\t{}) commands={}; flags={} ;;",
+			sh_quote(&level.path),
+			sh_quote(&commands.join(" ")),
+			sh_quote(&flag_words(level).join(" "))
+		);
+	}
+	script.push_str(
+		"\tesac\n\
+		 \tCOMPREPLY=()\n\
+		 \tif [[ \"$cur\" == -* ]]; then\n\
+		 \t\tCOMPREPLY=($(compgen -W \"$flags\" -- \"$cur\"))\n\
+		 \telif [[ -n \"$commands\" ]]; then\n\
+		 \t\tCOMPREPLY=($(compgen -W \"$commands\" -- \"$cur\"))\n\
+		 \tfi\n\
+		 }\n\n\
+		 complete -o default -o bashdefault -F _cmdlink cmdlink\n",
+	);
+	script
+}
+
+/// The zsh completion function, autoloaded from `fpath`. `path` and
+/// `commands` are special parameters in zsh, hence the other names.
+fn zsh(levels: &[Level]) -> String {
+	let paths: Vec<String> = levels.iter().skip(1).map(|level| sh_quote(&level.path)).collect();
+	let mut script = String::from(
+		"#compdef cmdlink\n\
+		 # Generated by cmdlink.\n\n\
+		 local path_=\"\" word i\n\
+		 local -a subcommands flags\n\
+		 for ((i = 2; i < CURRENT; i++)); do\n\
+		 \tword=\"${words[i]}\"\n\
+		 \tcase \"$path_ $word\" in\n",
+	);
+	let _ = writeln!(script, "\t\t({}) path_=\"$path_ $word\" ;;", paths.join("|"));
+	script.push_str("\tesac\ndone\ncase \"$path_\" in\n");
+	for level in levels {
+		// The descriptions are shown next to the subcommands.
+		let commands: Vec<String> = level
+			.commands
+			.iter()
+			.map(|(name, about)| sh_quote(&format!("{name}:{}", about.replace(':', "\\:"))))
+			.collect();
+		let flags: Vec<String> = flag_words(level).iter().map(|flag| sh_quote(flag)).collect();
+		let _ = writeln!(
+			script,
+			"\t({}) subcommands=({}); flags=({}) ;;",
+			sh_quote(&level.path),
+			commands.join(" "),
+			flags.join(" ")
+		);
+	}
+	script.push_str(
+		"esac\n\
+		 if [[ \"${words[CURRENT]}\" == -* ]]; then\n\
+		 \tcompadd -- $flags\n\
+		 elif (( ${#subcommands} )); then\n\
+		 \t_describe 'command' subcommands\n\
+		 else\n\
+		 \t_files\n\
+		 fi\n",
+	);
+	script
+}
+
+/// The fish completions, loaded from the user's `completions` directory.
+fn fish(levels: &[Level]) -> String {
+	let quote = |word: &str| format!("'{}'", word.replace('\\', "\\\\").replace('\'', "\\'"));
+	let mut script = String::from("# Generated by cmdlink.\n");
+	for level in levels {
+		let names: Vec<&str> = level.path.split_whitespace().collect();
+		// Subcommands of a level are offered until one of them is typed, and
+		// its flags once its own subcommands were.
+		let mut conditions: Vec<String> = match names.is_empty() {
+			true => vec!["__fish_use_subcommand".to_string()],
+			false => names
+				.iter()
+				.map(|name| format!("__fish_seen_subcommand_from {name}"))
+				.collect(),
+		};
+		let flags_condition = conditions.join("; and ");
+		if !names.is_empty() && !level.commands.is_empty() {
+			let commands: Vec<&str> = level.commands.iter().map(|(name, _)| name.as_str()).collect();
+			conditions.push(format!("not __fish_seen_subcommand_from {}", commands.join(" ")));
+		}
+		let commands_condition = conditions.join("; and ");
+		for (name, about) in &level.commands {
+			let _ = writeln!(
+				script,
+				"complete -c cmdlink -f -n {} -a {} -d {}",
+				quote(&commands_condition),
+				quote(name),
+				quote(about)
+			);
+		}
+		for (long, short, about) in &level.flags {
+			let short = short.map(|short| format!(" -s {short}")).unwrap_or_default();
+			let _ = writeln!(
+				script,
+				"complete -c cmdlink -n {} -l {long}{short} -d {}",
+				quote(&flags_condition),
+				quote(about)
+			);
+		}
+	}
+	script
+}
+
+/// The PowerShell argument completer, dot-sourced from the profile.
+fn powershell(levels: &[Level]) -> String {
+	let quote = |word: &str| format!("'{}'", word.replace('\'', "''"));
+	let mut script = String::from(
+		"# Generated by cmdlink.\n\
+		 Register-ArgumentCompleter -Native -CommandName cmdlink -ScriptBlock {\n\
+		 \tparam($wordToComplete, $commandAst, $cursorPosition)\n\
+		 \t$levels = @{\n",
+	);
+	for level in levels {
+		let commands: Vec<String> = level.commands.iter().map(|(name, _)| quote(name)).collect();
+		let flags: Vec<String> = flag_words(level).iter().map(|flag| quote(flag)).collect();
+		let _ = writeln!(
+			script,
+			"\t\t{} = (@({}), @({}))",
+			quote(&level.path),
+			commands.join(", "),
+			flags.join(", ")
+		);
+	}
+	script.push_str(
+		"\t}\n\
+		 \t$path = ''\n\
+		 \tforeach ($element in $commandAst.CommandElements | Select-Object -Skip 1) {\n\
+		 \t\tif ($element.Extent.EndOffset -ge $cursorPosition) { break }\n\
+		 \t\tif ($levels.ContainsKey(\"$path $element\")) { $path = \"$path $element\" }\n\
+		 \t}\n\
+		 \t$commands, $flags = $levels[$path]\n\
+		 \t$candidates = if ($wordToComplete -like '-*') { $flags } else { $commands }\n\
+		 \t$candidates | Where-Object { $_ -like \"$wordToComplete*\" } | ForEach-Object {\n\
+		 \t\t[System.Management.Automation.CompletionResult]::new($_, $_, 'ParameterValue', $_)\n\
+		 \t}\n\
+		 }\n",
+	);
+	script
+}
@@ -0,0 +1,118 @@
+//! Access to the system clipboard through the platform's clipboard tools:
+//! `clip` and PowerShell on Windows, `pbcopy` and `pbpaste` on macOS, and
+//! `wl-clipboard`, `xclip` or `xsel` elsewhere.
+
+use std::{
+	io::Write,
+	process::{Command, Stdio},
+};
+
+use crate::{error::Error, which, Result};
+
+/// The programs, with their arguments, that copy their input to the clipboard,
+/// in order of preference.
+fn copy_programs() -> &'static [(&'static str, &'static [&'static str])] {
+	if cfg!(target_os = "windows") {
+		&[("clip", &[])]
+	} else if cfg!(target_os = "macos") {
+		&[("pbcopy", &[])]
+	} else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+		&[
+			("wl-copy", &[]),
+			("xclip", &["-selection", "clipboard"]),
+			("xsel", &["--clipboard", "--input"]),
+		]
+	} else {
+		&[("xclip", &["-selection", "clipboard"]), ("xsel", &["--clipboard", "--input"])]
+	}
+}
+
+/// The programs, with their arguments, that print the clipboard, in order of
+/// preference. PowerShell prints in the console code page unless told
+/// otherwise.
+fn paste_programs() -> &'static [(&'static str, &'static [&'static str])] {
+	if cfg!(target_os = "windows") {
+		&[(
+			"powershell",
+			&[
+				"-NoProfile",
+				"-Command",
+				"[Console]::OutputEncoding = [Text.Encoding]::UTF8; Get-Clipboard -Raw",
+			],
+		)]
+	} else if cfg!(target_os = "macos") {
+		&[("pbpaste", &[])]
+	} else if std::env::var_os("WAYLAND_DISPLAY").is_some() {
+		&[
+			("wl-paste", &["--no-newline"]),
+			("xclip", &["-selection", "clipboard", "-out"]),
+			("xsel", &["--clipboard", "--output"]),
+		]
+	} else {
+		&[
+			("xclip", &["-selection", "clipboard", "-out"]),
+			("xsel", &["--clipboard", "--output"]),
+		]
+	}
+}
+
+/// The first of the programs that is installed.
+fn installed(
+	programs: &'static [(&'static str, &'static [&'static str])],
+) -> Result<&'static (&'static str, &'static [&'static str])> {
+	programs
+		.iter()
+		.find(|(program, _)| which::find_executable(program).is_some())
+		.ok_or_else(|| Error::Clipboard("no clipboard tool found".to_string()))
+}
+
+/// Copies the text to the clipboard, with the first clipboard tool that is
+/// installed.
+pub fn copy(text: &str) -> Result<()> {
+	let (program, args) = installed(copy_programs())?;
+	debug!("Copying to the clipboard with {}", program);
+
+	// clip takes its input in the console code page, unless it's UTF-16.
+	let input: Vec<u8> = if cfg!(target_os = "windows") {
+		[0xFEFF]
+			.into_iter()
+			.chain(text.encode_utf16())
+			.flat_map(u16::to_le_bytes)
+			.collect()
+	} else {
+		text.as_bytes().to_vec()
+	};
+	let fail = |e: std::io::Error| Error::Clipboard(format!("failed to run {program}: {e}"));
+	let mut child = Command::new(program)
+		.args(*args)
+		.stdin(Stdio::piped())
+		.stdout(Stdio::null())
+		.spawn()
+		.map_err(fail)?;
+	// The tool reads until its input is closed, when stdin is dropped.
+	if let Some(mut stdin) = child.stdin.take() {
+		stdin.write_all(&input).map_err(fail)?;
+	}
+	let status = child.wait().map_err(fail)?;
+	if !status.success() {
+		return Err(Error::Clipboard(format!("{program} exited with {status}")));
+	}
+	Ok(())
+}
+
+/// The text on the clipboard, read with the first clipboard tool that is
+/// installed.
+pub fn paste() -> Result<String> {
+	let (program, args) = installed(paste_programs())?;
+	debug!("Reading the clipboard with {}", program);
+
+	let output = Command::new(program)
+		.args(*args)
+		.stdin(Stdio::null())
+		.output()
+		.map_err(|e| Error::Clipboard(format!("failed to run {program}: {e}")))?;
+	if !output.status.success() {
+		return Err(Error::Clipboard(format!("{program} exited with {}", output.status)));
+	}
+	Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
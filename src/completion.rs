@@ -0,0 +1,148 @@
+//! Generating a bash and zsh script that registers basic tab completion for the
+//! aliases themselves, so that completion after an alias isn't dead.
+//!
+//! As the script is sourced by the interactive shell, it also defines the
+//! shell functions of aliases jumping to a `dir`, which their links can't do
+//! from a process of their own.
+
+use std::{fmt::Write, path::PathBuf};
+
+use crate::{
+	config::AliasValues,
+	describe,
+	error::Error,
+	paths::CmdlinkPaths,
+	platform_binary::{sh_quote, Link, LinkContext},
+	Config, Result,
+};
+
+/// The file name of the generated script in the project directory.
+pub const LOADER_NAME: &str = "completion-loader.sh";
+
+/// The shell function completing the words declared by the alias'
+/// `completions` for the current argument position. Words starting with `-`
+/// that match none of them complete flags scraped from the `--help` output of
+/// the aliased program instead. Anything else falls back to file and directory
+/// completion.
+const COMPLETE_FUNCTION: &str = r#"_cmdlink_complete() {
+	local cur="${COMP_WORDS[COMP_CWORD]}"
+	local words program
+	words="$(_cmdlink_words "$1" "$COMP_CWORD")"
+	COMPREPLY=()
+	if [[ -n "$words" ]]; then
+		COMPREPLY=($(compgen -W "$words" -- "$cur"))
+		[[ ${#COMPREPLY[@]} -gt 0 || "$cur" != -* ]] && return
+	fi
+	program="$(_cmdlink_program "$1")"
+	if [[ -n "$program" && "$cur" == -* ]]; then
+		COMPREPLY=($(compgen -W "$("$program" --help 2>&1 </dev/null \
+			| grep -oE -- '(^|[[:space:],[])--?[[:alnum:]][[:alnum:]_-]*' \
+			| sed 's/^[[:space:],[]*//' | sort -u)" -- "$cur"))
+	fi
+}
+"#;
+
+/// The path of the generated script in the given project directory.
+pub fn loader_path(paths: &CmdlinkPaths) -> PathBuf { paths.root().join(LOADER_NAME) }
+
+/// Renders the completion script for the enabled aliases.
+pub fn loader(cfg: &Config) -> String {
+	let mut aliases: Vec<(&str, &AliasValues)> = cfg.aliases().filter(|(_, values)| !values.disabled).collect();
+	aliases.sort_by_key(|(alias, _)| *alias);
+
+	let mut script = String::from(
+		"# Generated by cmdlink, changes are overwritten.\n\
+		 # Source this file from ~/.bashrc or ~/.zshrc to complete alias arguments\n\
+		 # and jump to the directories of dir aliases.\n\n\
+		 if [ -n \"$ZSH_VERSION\" ]; then\n\
+		 \tautoload -U +X compinit && compinit\n\
+		 \tautoload -U +X bashcompinit && bashcompinit\n\
+		 fi\n\n",
+	);
+	script.push_str(&completer(&aliases, cfg.links()));
+	for (alias, values) in &aliases {
+		if let Some(function) = jump_function(alias, values, cfg.links()) {
+			script.push('\n');
+			script.push_str(&function);
+		}
+	}
+
+	let completed: Vec<&(&str, &AliasValues)> = aliases.iter().filter(|(_, values)| values.dir.is_none()).collect();
+	if !completed.is_empty() {
+		let names: Vec<String> = completed.iter().map(|(alias, _)| sh_quote(alias)).collect();
+		let _ = writeln!(
+			script,
+			"\ncomplete -o default -o bashdefault -F _cmdlink_complete {}",
+			names.join(" ")
+		);
+	}
+	script
+}
+
+/// The shell functions completing the arguments of the aliases, see
+/// [COMPLETE_FUNCTION], which `complete -F _cmdlink_complete` registers for an
+/// alias.
+pub(crate) fn completer(aliases: &[(&str, &AliasValues)], links: &LinkContext) -> String {
+	let mut script = String::from("_cmdlink_program() {\n\tcase \"$1\" in\n");
+	for (alias, values) in aliases {
+		// Commands referencing undefined variables can't be linked either, so
+		// they simply get file completion.
+		let link = values.link(alias, links);
+		let Some(program) = link
+			.expand(link.cmd())
+			.ok()
+			.and_then(|cmd| describe::program(&cmd).map(str::to_string))
+		else {
+			continue;
+		};
+		let _ = writeln!(script, "\t\t{}) echo {} ;;", sh_quote(alias), sh_quote(&program));
+	}
+	script.push_str("\tesac\n}\n\n");
+	script.push_str("_cmdlink_words() {\n\tcase \"$1 $2\" in\n");
+	for (alias, values) in aliases {
+		for (position, words) in &values.completions {
+			let _ = writeln!(
+				script,
+				"\t\t{}) echo {} ;;",
+				sh_quote(&format!("{alias} {position}")),
+				sh_quote(&words.join(" "))
+			);
+		}
+	}
+	script.push_str("\tesac\n}\n\n");
+	script.push_str(COMPLETE_FUNCTION);
+	script
+}
+
+/// The shell function of an alias jumping to a `dir`, defined under the alias
+/// name, or `None` if the alias doesn't jump anywhere or can't be a function.
+pub(crate) fn jump_function(alias: &str, values: &AliasValues, links: &LinkContext) -> Option<String> {
+	let dir = values.dir.as_ref()?;
+	// Links to undefined variables can't be written either.
+	let link = values.link(alias, links);
+	let dir = link.expand_path(dir).ok()?;
+	if !is_function_name(alias) {
+		return None;
+	}
+	let mut function = format!("function {alias} {{\n\tcd -- {} || return\n", sh_quote(&dir));
+	if let Some(Ok(activate)) = values.activate.as_deref().map(|activate| link.expand(activate)) {
+		let _ = writeln!(function, "\t{activate}");
+	}
+	function.push_str("}\n");
+	Some(function)
+}
+
+/// Whether the alias is a plain word that a shell function can be named
+/// after.
+pub(crate) fn is_function_name(alias: &str) -> bool {
+	alias
+		.chars()
+		.all(|c| c.is_alphanumeric() || matches!(c, '-' | '_' | '.' | '+'))
+}
+
+/// Writes the completion script for the config to [loader_path].
+pub fn write(cfg: &Config) -> Result<PathBuf> {
+	let path = loader_path(cfg.paths());
+	std::fs::write(&path, loader(cfg)).map_err(|e| Error::CompletionLoader(path.clone(), e))?;
+	Ok(path)
+}
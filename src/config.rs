@@ -1,23 +1,209 @@
-use std::{collections::HashMap, sync::mpsc::channel};
+use std::{
+	collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+	fmt::Write,
+	num::NonZeroUsize,
+	path::{Path, PathBuf},
+	sync::mpsc::channel,
+	time::{Duration, SystemTime},
+};
 
 use serde::{Deserialize, Serialize};
-use tabled::{settings::Style, Table};
+use tabled::{
+	settings::{peaker::PriorityMax, Width},
+	Table,
+};
+use toml::value::Datetime;
 
 use crate::{
+	alias::{is_valid_env_key, AliasSpec, UpsertOutcome},
+	autorun,
+	bench::{Backend, Timing},
+	cache::{LinkCache, Stamp},
+	chain, completion,
+	defaults::Defaults,
 	error::Error,
-	platform_binary::{Action, Link, PlatformBinary},
-	Result,
+	hooks::{HookEvent, HookKind, Hooks},
+	import::{ImportReport, Resolution},
+	pack::{InstalledPack, Pack, PackManifest},
+	paths::{self, CmdlinkPaths},
+	platform_binary::{self, Action, Link, LinkBackup, LinkContext, LinkState, LinkStatus, PlatformBinary},
+	runs::Run,
+	settings::{Settings, WindowsExtension},
+	tr, width, Result,
 };
 
 type AliasName = String;
 
+/// Aliases grouped by tag, with untagged aliases under `None`.
+type TagGroups<'a> = BTreeMap<Option<&'a str>, Vec<&'a str>>;
+
+/// Whether or not the command invokes the given program, anywhere in a
+/// pipeline or command list. Programs are matched by their file stem, so
+/// `kubectl` also matches `/usr/local/bin/kubectl` and `kubectl.exe`, and
+/// aliases match the `@name` references to them, see [chain].
+///
+/// Needles that aren't a single word, such as `git commit`, are matched as
+/// plain substrings of the command instead.
+fn invokes(cmd: &str, needle: &str) -> bool {
+	if needle.contains(char::is_whitespace) {
+		return cmd.contains(needle);
+	}
+	cmd.split(|c: char| c.is_whitespace() || matches!(c, '|' | ';' | '&' | '(' | ')' | '`' | '"' | '\''))
+		.filter(|word| !word.is_empty())
+		.map(|word| word.strip_prefix('@').unwrap_or(word))
+		.any(|word| {
+			let stem = Path::new(word).file_stem().and_then(|stem| stem.to_str());
+			word.eq_ignore_ascii_case(needle) || stem.is_some_and(|stem| stem.eq_ignore_ascii_case(needle))
+		})
+}
+
+/// Reports two aliases whose names differ only in case. Their links would be
+/// the same file on the case-insensitive filesystems of Windows and macOS, so
+/// the config would no longer match the links there, and this fails with
+/// [Error::AliasCaseCollision]. Elsewhere only a warning is logged, as the
+/// config may still be shared with those platforms.
+fn report_case_collision(alias: &str, other: &str) -> Result<()> {
+	if cfg!(any(target_os = "windows", target_os = "macos")) {
+		return Err(Error::AliasCaseCollision(alias.to_string(), other.to_string()));
+	}
+	warn!("{}", tr!("config.case_collision", alias = alias, other = other));
+	Ok(())
+}
+
+/// The tables of the config.toml file needed before the whole config is
+/// loaded, see [file_tables].
+#[derive(Default, Deserialize)]
+struct FileTables {
+	#[serde(default)]
+	settings: Settings,
+	#[serde(default)]
+	hooks: Hooks,
+}
+
+/// Reads only the `[settings]` and `[hooks]` tables from the config.toml file
+/// of the given project directory, so that where links go and what's baked
+/// into them is known without loading the whole config. Falls back to the
+/// defaults if the file can't be read.
+pub(crate) fn file_tables(paths: &CmdlinkPaths) -> (Settings, Hooks) {
+	let tables: FileTables = std::fs::read_to_string(paths.config_file())
+		.ok()
+		.and_then(|config_str| toml::from_str(&config_str).ok())
+		.unwrap_or_default();
+	(tables.settings, tables.hooks)
+}
+
+/// Reads only the `[settings]` table from the config.toml file of the given
+/// project directory, see [file_tables].
+pub fn file_settings(paths: &CmdlinkPaths) -> Settings { file_tables(paths).0 }
+
+/// The width long strings are wrapped at when the config is saved.
+const WRAP_WIDTH: usize = 100;
+
+/// The `nice` value used for aliases marked as `low_priority`.
+const LOW_PRIORITY_NICE: i8 = 10;
+
+/// The branch drawn before an entry of a tree, and the indent of its children.
+fn tree_branch(last: bool) -> (&'static str, &'static str) {
+	if last {
+		("└── ", "    ")
+	} else {
+		("├── ", "│   ")
+	}
+}
+
+/// The current time as a TOML datetime, with second precision.
+fn now() -> Option<Datetime> {
+	humantime::format_rfc3339_seconds(SystemTime::now())
+		.to_string()
+		.parse()
+		.ok()
+}
+
 #[derive(Tabled)]
 /// Helper struct to display alias information in a table format.
 struct AliasInfo<'a> {
 	#[tabled(rename = "Alias")]
 	alias: &'a str,
+	#[tabled(rename = "Status")]
+	status: &'static str,
+	#[tabled(rename = "Description")]
+	description: &'a str,
+}
+
+#[derive(Tabled)]
+/// Helper struct to display the command of an alias in a table format.
+struct AliasCommand<'a> {
+	#[tabled(rename = "Alias")]
+	alias: &'a str,
+	#[tabled(rename = "Command")]
+	cmd: &'a str,
+}
+
+#[derive(Tabled)]
+/// Helper struct to display detailed alias information in a table format.
+struct AliasDetails<'a> {
+	#[tabled(rename = "Alias")]
+	alias: &'a str,
+	#[tabled(rename = "Command")]
+	cmd: &'a str,
 	#[tabled(rename = "Description")]
 	description: &'a str,
+	#[tabled(rename = "Tags")]
+	tags: String,
+	#[tabled(rename = "Status")]
+	status: String,
+	#[tabled(rename = "Created")]
+	created: String,
+	#[tabled(rename = "Updated")]
+	updated: String,
+}
+
+#[derive(Tabled)]
+/// Helper struct to display installed pack information in a table format.
+struct PackInfo<'a> {
+	#[tabled(rename = "Pack")]
+	name: &'a str,
+	#[tabled(rename = "Version")]
+	version: &'a str,
+	#[tabled(rename = "Prefix")]
+	prefix: &'a str,
+	#[tabled(rename = "Aliases")]
+	aliases: usize,
+}
+
+#[derive(Tabled)]
+/// Helper struct to display a stale alias in a table format.
+struct StaleInfo<'a> {
+	#[tabled(rename = "Alias")]
+	alias: &'a str,
+	#[tabled(rename = "Last used")]
+	last_used: String,
+	#[tabled(rename = "Command")]
+	cmd: &'a str,
+}
+
+#[derive(Tabled)]
+/// Helper struct to display the timing of a backend in a table format.
+struct BenchInfo {
+	#[tabled(rename = "Backend")]
+	backend: &'static str,
+	#[tabled(rename = "Per call")]
+	per_call: String,
+	#[tabled(rename = "Overhead")]
+	overhead: String,
+}
+
+#[derive(Tabled)]
+/// Helper struct to display a recorded run in a table format.
+struct RunInfo<'a> {
+	#[tabled(rename = "Started")]
+	started: &'a str,
+	#[tabled(rename = "Alias")]
+	alias: &'a str,
+	#[tabled(rename = "Exit code")]
+	status: i32,
+	#[tabled(rename = "Duration")]
+	duration: String,
 }
 
 #[derive(Default, Debug, Serialize, Deserialize)]
@@ -26,165 +212,1890 @@ pub struct Config {
 	#[serde(skip, default)]
 	/// Whether or not the config.toml file has been changed since load.
 	changed: bool,
+	#[serde(skip, default)]
+	/// Whether or not the config was loaded for read-only use, in which case it
+	/// is never saved.
+	read_only: bool,
+	#[serde(skip, default)]
+	/// Hooks queued by operations performed since load, run after saving.
+	pending_hooks: Vec<HookEvent>,
+	#[serde(skip, default)]
+	/// The project directory of the config, and what its links have in common,
+	/// kept in line with the settings and hooks.
+	links: LinkContext,
+	#[serde(default, skip_serializing_if = "Settings::is_empty")]
+	/// Global preferences, configured through the `[settings]` table.
+	settings: Settings,
+	#[serde(default, skip_serializing_if = "Defaults::is_empty")]
+	/// Defaults for newly created aliases, configured through the `[defaults]`
+	/// table.
+	defaults: Defaults,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Defaults shared by the aliases of each group, keyed by group name.
+	groups: BTreeMap<String, Group>,
+	#[serde(serialize_with = "serialize_sorted")]
 	/// List of aliases defined in the config.toml file.
 	aliases: HashMap<AliasName, AliasValues>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Packs installed into the config, keyed by pack name.
+	packs: BTreeMap<String, InstalledPack>,
+	#[serde(default, skip_serializing_if = "Hooks::is_empty")]
+	/// Shell commands run after successful mutating operations.
+	hooks: Hooks,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Default, Debug, Serialize, Deserialize)]
+/// The values stored for a single alias in the config.toml file.
 pub struct AliasValues {
 	#[serde(skip)]
-	pub link: Option<PlatformBinary>,
+	/// The link state of the alias, see [AliasValues::link].
+	pub(crate) link: LinkState,
 	/// An optional description for the alias.
 	pub description: Option<String>,
-	/// The command to be executed when the alias is invoked.
+	#[serde(default, skip_serializing_if = "String::is_empty")]
+	/// The command to be executed when the alias is invoked. Empty for aliases
+	/// opening a `url`, jumping to a `dir` or running a `release`.
 	pub cmd: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A web page opened in the default browser when the alias is invoked,
+	/// instead of running a command. The arguments the alias is invoked with
+	/// are appended as query parameters, see [crate::open::url_with_args].
+	pub url: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A directory the interactive shell changes to when the alias is invoked,
+	/// instead of running a command. A shim can't change the directory of the
+	/// shell it's run from, so this relies on the shell function defined by the
+	/// sourced completion loader on Unix, see [crate::completion]. A leading
+	/// `~` and template variables are expanded.
+	pub dir: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A command run in the shell after changing to `dir`, such as
+	/// `source .venv/bin/activate`.
+	pub activate: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A GitHub release, as `owner/repo@tag`, whose binary is downloaded into
+	/// a cache and run instead of a command, pinning the tool to that version.
+	/// See [crate::release].
+	pub release: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The name of the `release` asset to download, with `*` wildcards, for
+	/// releases whose assets the current platform can't be recognized in.
+	pub asset: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The name of the binary in the `release` asset, the repository name by
+	/// default.
+	pub bin: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The SHA-256 checksum the `release` asset is verified against, instead
+	/// of the one published with the release.
+	pub sha256: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A longer explanation of the alias, shown by `cmdlink show`.
+	pub long_description: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// Example invocations of the alias, shown by `cmdlink show`.
+	pub examples: Vec<String>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// The words offered by tab completion for each argument position of the
+	/// alias, starting at `1`.
+	pub completions: BTreeMap<String, Vec<String>>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// Tags used to group and filter aliases.
+	pub tags: Vec<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The group the alias inherits defaults from, see [Group].
+	pub group: Option<String>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Environment variables exported before the command is run.
+	pub env: BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Environment variables retrieved from the OS keychain each time the alias
+	/// is invoked, as `keyring:<service>/<account>` references.
+	pub secrets: BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A file of `KEY=VALUE` lines loaded each time the alias is invoked, to
+	/// keep machine specific values out of the config. A leading `~` and
+	/// template variables are expanded.
+	pub env_file: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The directory the command runs in. A leading `~` and template variables
+	/// are expanded.
+	pub cwd: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The shell the command runs through on Unix, such as `bash`, for
+	/// commands relying on its syntax.
+	pub shell: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The version of the tool the command needs, checked by the link before
+	/// running it so that an outdated tool fails with a clear error.
+	pub min_version: Option<MinVersion>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Whether or not the link passes its arguments on to the command, which it
+	/// does unless set to `false`.
+	pub forward_args: Option<bool>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A file the output of the command is copied to, for aliases run from
+	/// launchers or schedulers where it would otherwise be lost. A leading `~`
+	/// and template variables are expanded.
+	pub capture: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// How the `capture` file is written, appending to it by default.
+	pub capture_mode: Option<CaptureMode>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// An optional `nice` value the command is run with.
+	pub nice: Option<i8>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	/// Runs the command at low CPU and IO priority, shorthand for `nice = 10`.
+	pub low_priority: bool,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	/// Launches the command without waiting for it on Windows, for GUI apps
+	/// that would otherwise keep a console window open.
+	pub gui: bool,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	/// Registers the link under `App Paths` on Windows, so that the alias can
+	/// be launched from the Run dialog and other programs.
+	pub app_path: bool,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	/// Makes the link a symlink to the program the command runs on Unix, when
+	/// the command is a lone program name or path and the alias needs nothing
+	/// a script would wrap it with, so that no shell is started per call.
+	/// Other aliases keep a script.
+	pub symlink: bool,
+	#[serde(default, skip_serializing_if = "PlatformCommands::is_empty")]
+	/// Platform specific commands used in place of `cmd`.
+	pub platform: PlatformCommands,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The extension of the link file on Windows, overriding the
+	/// `windows_extension` setting.
+	pub windows_extension: Option<WindowsExtension>,
+	#[serde(default, skip_serializing_if = "std::ops::Not::not")]
+	/// Keeps the alias in the config without generating a link for it.
+	pub disabled: bool,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// When the alias was first added.
+	pub created: Option<Datetime>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// When the alias was last changed.
+	pub updated: Option<Datetime>,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Platform specific variants of an alias command, configured through the
+/// `platform` table of an alias.
+pub struct PlatformCommands {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The command used on Windows.
+	pub windows: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The command used on macOS.
+	pub macos: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The command used on Linux.
+	pub linux: Option<String>,
+}
+
+impl PlatformCommands {
+	/// Whether or not no platform variants are defined.
+	pub fn is_empty(&self) -> bool { self.windows.is_none() && self.macos.is_none() && self.linux.is_none() }
+
+	/// The variant for the current platform, if any.
+	pub fn current(&self) -> Option<&str> {
+		if cfg!(target_os = "windows") {
+			self.windows.as_deref()
+		} else if cfg!(target_os = "macos") {
+			self.macos.as_deref()
+		} else {
+			self.linux.as_deref()
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The version check of an alias, configured through its `min_version` table.
+pub struct MinVersion {
+	/// The command printing the version of the tool, such as
+	/// `node --version`.
+	pub cmd: String,
+	/// The versions the alias works with, such as `>=20` or `>=1.2, <2`, see
+	/// [crate::version::Requirement].
+	pub require: String,
+}
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Defaults shared by the aliases of a group, configured through a
+/// `[groups.<name>]` table and inherited by the aliases setting `group` to its
+/// name, unless they set their own.
+pub struct Group {
+	#[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+	/// Environment variables exported for every alias of the group. Variables
+	/// set by the alias itself take precedence.
+	pub env: BTreeMap<String, String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The directory the commands of the group run in.
+	pub cwd: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The shell the commands of the group run through on Unix.
+	pub shell: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// Tags added to every alias of the group.
+	pub tags: Vec<String>,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// How the `capture` file of an alias is written on each run.
+pub enum CaptureMode {
+	/// Appends the output to the file.
+	#[default]
+	Append,
+	/// Overwrites the file, keeping only the output of the last run.
+	Truncate,
+	/// Renames the previous files to `<file>.1` through `<file>.5` before
+	/// writing a new one, keeping the output of the last six runs.
+	Rotate,
+}
+
+impl AliasValues {
+	/// The effective niceness for the alias, taking `low_priority` into
+	/// account when no explicit `nice` value is set.
+	pub fn niceness(&self) -> Option<i8> { self.nice.or(self.low_priority.then_some(LOW_PRIORITY_NICE)) }
+
+	/// The command to run on the current platform, preferring the platform
+	/// variant over `cmd`.
+	pub fn effective_cmd(&self) -> &str { self.platform.current().unwrap_or(&self.cmd) }
+
+	/// What the alias does, for showing to the user: the URL it opens, the
+	/// directory it jumps to, the release it runs, or the command it runs on
+	/// the current platform.
+	pub fn target(&self) -> &str {
+		self.url
+			.as_deref()
+			.or(self.dir.as_deref())
+			.or(self.release.as_deref())
+			.unwrap_or_else(|| self.effective_cmd())
+	}
+
+	/// The tags of the alias, followed by those it inherits from its group.
+	pub fn effective_tags(&self) -> Vec<&str> {
+		let mut tags: Vec<&str> = self.tags.iter().map(String::as_str).collect();
+		for tag in self.link.group.iter().flat_map(|group| &group.tags) {
+			if !tags.contains(&tag.as_str()) {
+				tags.push(tag);
+			}
+		}
+		tags
+	}
+
+	/// The command that invokes the given program or contains the given text,
+	/// checking `cmd` and then the platform variants. See [invokes].
+	pub fn invoking(&self, needle: &str) -> Option<&str> {
+		[
+			Some(&self.cmd),
+			self.platform.windows.as_ref(),
+			self.platform.macos.as_ref(),
+			self.platform.linux.as_ref(),
+		]
+		.into_iter()
+		.flatten()
+		.map(String::as_str)
+		.find(|cmd| invokes(cmd, needle))
+	}
+
+	/// Returns the platform binary backing the alias, among the links of the
+	/// given config, see [Config::links].
+	pub fn link<'a>(&'a self, alias: &'a str, links: &'a LinkContext) -> PlatformBinary<'a> {
+		PlatformBinary::new(alias, self, links)
+	}
+
+	/// The state of the alias' link, see [LinkStatus].
+	pub fn status(&self, alias: &str, links: &LinkContext) -> LinkStatus {
+		if self.disabled {
+			LinkStatus::Disabled
+		} else if !self.link(alias, links).exists() {
+			LinkStatus::Broken
+		} else if !self.link(alias, links).target_found() {
+			LinkStatus::Unresolved
+		} else {
+			LinkStatus::Ok
+		}
+	}
+
+	/// Sets the action to perform on the alias' link when the config is saved.
+	pub fn set_action(&mut self, action: Action) { self.link.action = action; }
+
+	/// Whether or not two aliases share the same definition, ignoring link
+	/// state, whether they're disabled and when they were added or updated.
+	/// The serialized fields are compared, so none can be left out.
+	fn same_definition(&self, other: &AliasValues) -> bool {
+		let definition = |values: &AliasValues| {
+			let mut table = toml::Table::try_from(values).ok()?;
+			for key in ["disabled", "created", "updated"] {
+				table.remove(key);
+			}
+			Some(table)
+		};
+		let ours = definition(self);
+		ours.is_some() && ours == definition(other)
+	}
 }
 
 impl Config {
-	/// Creates an empty Config instance.
-	fn empty() -> Self { Config::default() }
+	/// Creates an empty Config instance in the given project directory.
+	fn empty(paths: &CmdlinkPaths) -> Self {
+		let mut cfg = Config::default();
+		cfg.links = LinkContext::new(paths.clone(), &cfg.settings, &cfg.hooks);
+		cfg
+	}
 
-	/// Creates a new Config instance from the config.toml file.
+	/// Creates a new Config instance from the config.toml file, checking that
+	/// every alias has a link file. With the `auto_refresh` setting, missing
+	/// and outdated links are repaired instead, see [Config::repair_links].
 	///
 	/// If the config.toml file does not exist, it creates a new one with
 	/// default values.
-	pub fn new() -> Result<Self> {
-		let config_file_path = crate::PROJECT_DIR.join("config.toml");
+	pub fn new() -> Result<Self> { Self::new_in(paths::get()) }
+
+	/// Like [Config::new], but for the config in the given project directory
+	/// rather than the one in use.
+	pub fn new_in(paths: &CmdlinkPaths) -> Result<Self> {
+		let config_file_path = paths.config_file();
 
-		// If the config.toml file does not exist, create a new one with default values.
+		// If the config.toml file does not exist, create a new one with default
+		// values.
 		if !config_file_path.exists() {
-			let mut cfg = Config::empty();
-			cfg.save()?;
+			let mut cfg = Config::empty(paths);
+			cfg.write()?;
 			return Ok(cfg);
 		}
 
-		// Otherwise, open the file and read the contents to a Config instance.
-		let config_str = std::fs::read_to_string(config_file_path).map_err(Error::ConfigRead)?;
-		let mut cfg: Self = toml::from_str(&config_str)?;
-		cfg.initialize_links()?;
+		let config_str = Self::read(&config_file_path)?;
+		let mut cfg = Self::parse(paths, &config_file_path, &config_str)?;
+		if cfg.settings.auto_refresh.unwrap_or_default() {
+			cfg.repair_links(&config_str)?;
+		} else {
+			cfg.check_links(&config_str);
+		}
+		Ok(cfg)
+	}
+
+	/// Loads the config.toml file without checking the link files, for callers
+	/// that bring the links in line right away, such as after the config was
+	/// edited. Unlike [Config::read_only], changes are saved as usual.
+	pub fn unchecked() -> Result<Self> { Self::unchecked_in(paths::get()) }
+
+	/// Like [Config::unchecked], but for the config in the given project
+	/// directory rather than the one in use.
+	pub fn unchecked_in(paths: &CmdlinkPaths) -> Result<Self> {
+		let config_file_path = paths.config_file();
+		if !config_file_path.exists() {
+			return Self::new_in(paths);
+		}
+		Self::parse(paths, &config_file_path, &Self::read(&config_file_path)?)
+	}
 
+	/// Loads the config.toml file for read-only use, without touching the
+	/// filesystem beyond reading the file. Link files are not checked, and
+	/// the config is never saved, so this is suited for commands that only
+	/// display information.
+	pub fn read_only() -> Result<Self> { Self::read_only_in(paths::get()) }
+
+	/// Like [Config::read_only], but for the config in the given project
+	/// directory rather than the one in use.
+	pub fn read_only_in(paths: &CmdlinkPaths) -> Result<Self> {
+		let config_file_path = paths.config_file();
+		let mut cfg = if config_file_path.exists() {
+			Self::parse(paths, &config_file_path, &Self::read(&config_file_path)?)?
+		} else {
+			Config::empty(paths)
+		};
+		cfg.read_only = true;
 		Ok(cfg)
 	}
 
-	/// Inserts a new alias to the config.toml file.
-	pub fn create_alias(&mut self, alias: String, cmd: String, description: Option<String>, force: bool) -> Result<()> {
-		let action = if force { Action::Update } else { Action::Create };
-		if force && self.aliases.contains_key(&alias) {
-			info!("Alias already exists, overriding...");
+	/// Reads the contents of a config file.
+	fn read(path: &Path) -> Result<String> {
+		std::fs::read_to_string(path).map_err(|e| Error::ConfigRead(path.to_path_buf(), e))
+	}
+
+	/// Parses the contents of a config file, resolving the references between
+	/// its aliases, whose links go into the given project directory.
+	fn parse(paths: &CmdlinkPaths, path: &Path, config_str: &str) -> Result<Self> {
+		let mut cfg: Self =
+			toml::from_str(config_str).map_err(|e| Error::ConfigParse(path.to_path_buf(), Box::new(e)))?;
+		cfg.check_env_keys()?;
+		cfg.links = LinkContext::new(paths.clone(), &cfg.settings, &cfg.hooks);
+		cfg.link_groups();
+		cfg.link_chains();
+		Ok(cfg)
+	}
+
+	/// Fails with [Error::InvalidEnvKey] if an alias sets, or inherits from its
+	/// group, an environment variable or secret whose name isn't valid, as the
+	/// names are written into the links as they are.
+	fn check_env_keys(&self) -> Result<()> {
+		for (alias, values) in &self.aliases {
+			let group = values.group.as_ref().and_then(|group| self.groups.get(group));
+			if let Some(key) = values
+				.env
+				.keys()
+				.chain(values.secrets.keys())
+				.chain(group.into_iter().flat_map(|group| group.env.keys()))
+				.find(|key| !is_valid_env_key(key))
+			{
+				return Err(Error::InvalidEnvKey(alias.clone(), key.clone()));
+			}
+		}
+		Ok(())
+	}
+
+	/// Moves the link files of all aliases to a new bins directory and stores
+	/// it as the `bins_dir` setting, returning the absolute path of the new
+	/// directory. The old directory is removed if nothing else is left in it.
+	///
+	/// Fails before moving anything if a link file would overwrite a file in
+	/// the new directory. Missing link files are left for `refresh` to
+	/// recreate.
+	pub fn relocate_bins(&mut self, new_dir: &Path) -> Result<PathBuf> {
+		let old_dir = self.links.bins_dir().to_path_buf();
+		let new_dir =
+			std::path::absolute(new_dir).map_err(|e| Error::Relocate(new_dir.to_path_buf(), e.to_string()))?;
+		if new_dir == old_dir {
+			return Err(Error::Relocate(new_dir, "it already is the bins directory".to_string()));
+		}
+		std::fs::create_dir_all(&new_dir).map_err(|e| Error::Relocate(new_dir.clone(), e.to_string()))?;
+
+		let mut moves = Vec::new();
+		for (alias, values) in &self.aliases {
+			let link = values.link(alias, &self.links);
+			let from = link.file_path().to_path_buf();
+			let Some(file_name) = from.file_name().filter(|_| from.exists()) else {
+				continue;
+			};
+			let to = new_dir.join(file_name);
+			if to.exists() {
+				return Err(Error::Relocate(new_dir, format!("'{}' already exists", to.display())));
+			}
+			moves.push((alias, from, to));
+		}
+
+		info!("{}", tr!("refresh.moving", count = moves.len(), dir = new_dir.display()));
+		for (alias, from, to) in moves {
+			// Renaming fails across file systems, in which case the file is
+			// copied.
+			std::fs::rename(&from, &to)
+				.or_else(|_| platform_binary::copy_link(&from, &to).and_then(|_| std::fs::remove_file(&from)))
+				.map_err(|e| Error::LinkUpdate(alias.clone(), to, e))?;
+		}
+		let _ = std::fs::remove_dir(&old_dir);
+
+		self.settings.bins_dir = (new_dir != self.paths().default_bins_dir()).then(|| new_dir.clone());
+		self.update_links();
+		self.changed = true;
+		Ok(new_dir)
+	}
+
+	/// Warns about aliases whose link file is missing.
+	///
+	/// The result is cached alongside a stamp of the config and `bins`
+	/// directory, so the links are only scanned again once either changes.
+	fn check_links(&self, config_str: &str) {
+		let stamp = Stamp::current(config_str, self.paths(), self.links.bins_dir());
+		let cache = LinkCache::load(self.paths(), &stamp).unwrap_or_else(|| {
+			debug!("Link cache is stale, scanning links");
+			let cache = LinkCache::new(stamp, self.scan_missing_links());
+			cache.save(self.paths());
+			cache
+		});
+
+		for (alias, values) in &self.aliases {
+			let missing = cache.is_missing(alias);
+			let _ = values.link.exists.set(!missing);
+			if missing && !values.disabled {
+				warn!("{}", tr!("config.link_missing", alias = alias));
+			}
+		}
+	}
+
+	/// Schedules missing and outdated links to be regenerated without warning
+	/// about them, as enabled by the `auto_refresh` setting.
+	///
+	/// Like [Config::check_links], the links are only checked once the config
+	/// or `bins` directory changed since the last check that found nothing to
+	/// repair.
+	fn repair_links(&mut self, config_str: &str) -> Result<()> {
+		let stamp = Stamp::current(config_str, self.paths(), self.links.bins_dir());
+		let fresh = LinkCache::load(self.paths(), &stamp).is_some_and(|cache| {
+			self.aliases
+				.iter()
+				.all(|(alias, values)| values.disabled || !cache.is_missing(alias))
+		});
+		if fresh {
+			return Ok(());
+		}
+
+		debug!("Link cache is stale, repairing links");
+		if self.sync_links()? == 0 {
+			LinkCache::new(stamp, self.scan_missing_links()).save(self.paths());
+		}
+		Ok(())
+	}
+
+	/// Lists the aliases whose link file is missing, reading the `bins`
+	/// directory once instead of checking every link.
+	fn scan_missing_links(&self) -> BTreeSet<String> {
+		self.scan_links();
+		self.aliases
+			.iter()
+			.filter(|(alias, values)| !values.link(alias, &self.links).exists())
+			.map(|(alias, _)| alias.clone())
+			.collect()
+	}
+
+	/// Finds out which links exist from a single read of the `bins` directory,
+	/// rather than a stat per alias, which adds up on network home directories.
+	/// Links already checked are left as they are, and each link is checked on
+	/// its own if the directory can't be read.
+	fn scan_links(&self) {
+		let Some(present) = self.links.present_links() else {
+			return;
+		};
+		for (alias, values) in &self.aliases {
+			values.link(alias, &self.links).mark_exists(&present);
+		}
+	}
+
+	/// The state of every alias' link, from a single read of the `bins`
+	/// directory rather than a stat per alias, see [Config::scan_links].
+	fn statuses(&self) -> BTreeMap<&str, LinkStatus> {
+		self.scan_links();
+		self.aliases
+			.iter()
+			.map(|(alias, values)| (alias.as_str(), values.status(alias, &self.links)))
+			.collect()
+	}
+
+	/// The global preferences of the `[settings]` table.
+	pub fn settings(&self) -> &Settings { &self.settings }
+
+	/// The project directory the config belongs to.
+	pub fn paths(&self) -> &CmdlinkPaths { self.links.paths() }
+
+	/// What the links of the config have in common, such as the directory
+	/// they're stored in.
+	pub fn links(&self) -> &LinkContext { &self.links }
+
+	/// Brings [Config::links] in line with the settings and hooks after they
+	/// changed.
+	fn update_links(&mut self) { self.links = LinkContext::new(self.paths().clone(), &self.settings, &self.hooks); }
+
+	/// Changes a global setting, or resets it to its default if `value` is
+	/// `None`. See [Settings::set].
+	///
+	/// Changing `windows_extension` renames the link files of the aliases that
+	/// don't override it. Changing `run_history` only takes effect on the links
+	/// once they are refreshed.
+	pub fn set_setting(&mut self, key: &str, value: Option<&str>) -> Result<()> {
+		let old_extension = self.settings.windows_extension;
+		let old_run_history = self.settings.run_history;
+		self.settings.set(key, value)?;
+		debug!("Set {} to {:?}", key, value);
+		if self.settings.run_history != old_run_history {
+			warn!("{}", tr!("config.refresh_needed", key = key));
+		}
+		self.update_links();
+		if cfg!(target_os = "windows") && self.settings.windows_extension != old_extension {
+			self.rename_links(
+				old_extension.unwrap_or_default(),
+				self.settings.windows_extension.unwrap_or_default(),
+			)?;
+		}
+		self.changed = true;
+		Ok(())
+	}
+
+	/// Renames the link files of the aliases without their own
+	/// `windows_extension` from one extension to the other.
+	fn rename_links(&self, from: WindowsExtension, to: WindowsExtension) -> Result<()> {
+		let bins_dir = self.links.bins_dir();
+		let mut renamed = 0;
+		for alias in self
+			.aliases
+			.iter()
+			.filter(|(_, v)| v.windows_extension.is_none())
+			.map(|(a, _)| a)
+		{
+			let old = bins_dir.join(format!("{alias}{}", from.as_str()));
+			let new = bins_dir.join(format!("{alias}{}", to.as_str()));
+			match std::fs::rename(&old, &new) {
+				Ok(()) => renamed += 1,
+				Err(e) if e.kind() == std::io::ErrorKind::NotFound => {},
+				Err(e) => return Err(Error::LinkUpdate(alias.clone(), new, e)),
+			}
+		}
+		info!("{}", tr!("refresh.renamed", count = renamed, extension = to.as_str()));
+		Ok(())
+	}
+
+	/// Validates every alias in the config, see [AliasSpec::validate], that no
+	/// alias names differ only in case, that the groups aliases join exist,
+	/// and that no aliases reference each other in a cycle.
+	pub fn validate(&self) -> Result<()> {
+		self.aliases
+			.iter()
+			.try_for_each(|(alias, values)| AliasSpec::from_values(alias.clone(), values).validate())?;
+		let mut folded: BTreeMap<String, &str> = BTreeMap::new();
+		let mut aliases: Vec<&str> = self.aliases.keys().map(String::as_str).collect();
+		aliases.sort_unstable();
+		for alias in aliases {
+			if let Some(other) = folded.insert(alias.to_lowercase(), alias) {
+				report_case_collision(alias, other)?;
+			}
+		}
+		self.aliases
+			.iter()
+			.try_for_each(|(alias, values)| self.check_group(alias, values))?;
+		self.check_chains()
+	}
+
+	/// Reports another alias whose name differs from the given one only in
+	/// case, see [report_case_collision].
+	fn check_case(&self, alias: &str) -> Result<()> {
+		let folded = alias.to_lowercase();
+		match self
+			.aliases
+			.keys()
+			.find(|other| *other != alias && other.to_lowercase() == folded)
+		{
+			Some(other) => report_case_collision(alias, other),
+			None => Ok(()),
 		}
+	}
+
+	/// Fails with [Error::UnknownGroup] if the alias joins a group that isn't
+	/// defined.
+	fn check_group(&self, alias: &str, values: &AliasValues) -> Result<()> {
+		match &values.group {
+			Some(group) if !self.groups.contains_key(group) => {
+				Err(Error::UnknownGroup(alias.to_string(), group.clone()))
+			},
+			_ => Ok(()),
+		}
+	}
+
+	/// Hands every alias the defaults of its group, which its link inherits,
+	/// see [Group].
+	fn link_groups(&mut self) {
+		for values in self.aliases.values_mut() {
+			values.link.group = values.group.as_ref().and_then(|group| self.groups.get(group)).cloned();
+		}
+	}
+
+	/// The linked aliases referenced as `@name` by each linked alias, in `cmd`
+	/// or any of the platform variants. Disabled aliases and aliases about to
+	/// be removed have no link, so they are left out.
+	fn chains(&self) -> BTreeMap<&str, BTreeSet<&str>> {
+		let linked = |values: &AliasValues| !values.disabled && !matches!(values.link.action, Action::Remove);
+		self.aliases
+			.iter()
+			.filter(|(_, values)| linked(values))
+			.map(|(alias, values)| {
+				let referenced = [
+					Some(&values.cmd),
+					values.platform.windows.as_ref(),
+					values.platform.macos.as_ref(),
+					values.platform.linux.as_ref(),
+				]
+				.into_iter()
+				.flatten()
+				.flat_map(|cmd| chain::references(cmd))
+				.filter_map(|name| {
+					let (name, values) = self.aliases.get_key_value(&name)?;
+					linked(values).then_some(name.as_str())
+				})
+				.collect();
+				(alias.as_str(), referenced)
+			})
+			.collect()
+	}
+
+	/// Fails with [Error::AliasCycle] if aliases reference each other in a
+	/// cycle, whose links would keep running each other forever.
+	fn check_chains(&self) -> Result<()> {
+		match chain::find_cycle(&self.chains()) {
+			Some(cycle) => Err(Error::AliasCycle(cycle)),
+			None => Ok(()),
+		}
+	}
+
+	/// Points the `@name` references of every alias to the link files of the
+	/// aliases they name, see [chain]. Aliases referencing an alias whose link
+	/// is about to change are scheduled to be rewritten along with it.
+	fn link_chains(&mut self) {
+		let links: BTreeMap<String, PathBuf> = self
+			.aliases
+			.iter()
+			.filter(|(_, values)| !values.disabled && !matches!(values.link.action, Action::Remove))
+			.map(|(alias, values)| (alias.clone(), values.link(alias, &self.links).file_path().to_path_buf()))
+			.collect();
+		let changed: HashSet<String> = self
+			.aliases
+			.iter()
+			.filter(|(_, values)| !matches!(values.link.action, Action::None))
+			.map(|(alias, _)| alias.clone())
+			.collect();
 
-		let link = Some(PlatformBinary::new(alias.clone(), cmd.clone(), action));
-		self.aliases.insert(alias, AliasValues { link, description, cmd });
+		for (alias, values) in self.aliases.iter_mut() {
+			let referenced = chain::references(values.effective_cmd());
+			values.link.chained = referenced
+				.iter()
+				.filter_map(|name| Some((name.clone(), links.get(name)?.clone())))
+				.collect();
+			if values.disabled || !matches!(values.link.action, Action::None) {
+				continue;
+			}
+			for name in referenced.iter().filter(|name| changed.contains(*name)) {
+				if !links.contains_key(name) {
+					warn!("{}", tr!("chain.unlinked", alias = alias, name = name));
+				}
+				debug!("Rewriting link for alias {} referencing {}", alias, name);
+				values.set_action(Action::Update);
+			}
+		}
+	}
+
+	/// Returns the values for the given alias, if it exists.
+	pub fn alias(&self, alias: &str) -> Option<&AliasValues> { self.aliases.get(alias) }
+
+	/// Returns an iterator over all aliases and their values.
+	pub fn aliases(&self) -> impl Iterator<Item = (&str, &AliasValues)> {
+		self.aliases.iter().map(|(alias, values)| (alias.as_str(), values))
+	}
+
+	/// Inserts a new alias to the config.toml file, filling in the fields it
+	/// leaves unset from the `[defaults]` table, see [Defaults].
+	///
+	/// Fails without changing anything if the alias already exists, unless
+	/// `force` is set, in which case the existing alias is overwritten.
+	pub fn create_alias(&mut self, spec: AliasSpec, force: bool) -> Result<()> {
+		if self.aliases.contains_key(&spec.name) {
+			if !force {
+				let path = self.links.link_path(&spec.name);
+				return Err(Error::LinkAlreadyExists(spec.name, path));
+			}
+			info!("{}", tr!("add.overriding"));
+		}
+		let action = if force { Action::Update } else { Action::Create };
+
+		let spec = self.defaults.apply(spec);
+		spec.validate()?;
+		let (alias, mut values) = spec.into_values();
+		self.stamp(&alias, &mut values);
+		values.set_action(action);
+		self.insert_alias(alias, values)
+	}
+
+	/// Inserts or updates an alias, reporting what changed. Unlike
+	/// [Config::create_alias], existing aliases are overwritten without
+	/// requiring a force flag, and identical definitions are left untouched.
+	pub fn upsert(&mut self, spec: AliasSpec) -> Result<UpsertOutcome> {
+		let (alias, mut values) = spec.into_values();
+
+		let outcome = match self.aliases.get(&alias) {
+			Some(existing) => {
+				if existing.same_definition(&values) && existing.link(&alias, &self.links).exists() {
+					return Ok(UpsertOutcome::Unchanged);
+				}
+				UpsertOutcome::Updated
+			},
+			None => UpsertOutcome::Created,
+		};
+
+		let action = match outcome {
+			UpsertOutcome::Created => Action::Create,
+			_ => Action::Update,
+		};
+		debug!("Upserting alias \"{}\": {:?}", alias, outcome);
+		self.stamp(&alias, &mut values);
+		values.set_action(action);
+		self.insert_alias(alias, values)?;
+		Ok(outcome)
+	}
+
+	/// Inserts an alias, marking the config as changed. Fails without changing
+	/// anything if its name differs from another alias only in case on Windows
+	/// or macOS, if it joins an unknown group, or if it would complete a cycle
+	/// of `@name` references.
+	fn insert_alias(&mut self, alias: String, values: AliasValues) -> Result<()> {
+		self.check_case(&alias)?;
+		self.check_group(&alias, &values)?;
+		let replaced = self.aliases.insert(alias.clone(), values);
+		if let Err(why) = self.check_chains() {
+			match replaced {
+				Some(replaced) => self.aliases.insert(alias, replaced),
+				None => self.aliases.remove(&alias),
+			};
+			return Err(why);
+		}
+		self.queue_hook(HookKind::PostAdd, Some(&alias));
 		self.changed = true;
 		Ok(())
 	}
 
-	/// Removes an alias, marking the config as changed.
+	/// Records when an alias about to be inserted was created and updated,
+	/// keeping the creation time of the alias it replaces, if any.
+	fn stamp(&self, alias: &str, values: &mut AliasValues) {
+		match self.aliases.get(alias) {
+			Some(existing) => {
+				values.created = existing.created;
+				values.updated = now();
+			},
+			None => values.created = now(),
+		}
+	}
+
+	/// Removes an alias, marking the config as changed. Fails with
+	/// [Error::UnknownAlias] if the alias doesn't exist.
 	pub fn remove_alias(&mut self, alias: &str) -> Result<()> {
-		if let Some(old_alias) = self.aliases.get_mut(alias) {
-			// SAFETY: all links are initialized in Config creation
-			let link = unsafe { old_alias.link.as_mut().unwrap_unchecked() };
-			link.set_action(Action::Remove);
-			self.changed = true;
-		} else {
-			warn!("Alias \"{}\" did not exist in the config", alias);
+		let old_alias = self
+			.aliases
+			.get_mut(alias)
+			.ok_or_else(|| Error::UnknownAlias(alias.to_string()))?;
+		old_alias.set_action(Action::Remove);
+		self.changed = true;
+		self.queue_hook(HookKind::PostRemove, Some(alias));
+		Ok(())
+	}
+
+	/// Installs the aliases of a pack under the given prefix (the pack's
+	/// default prefix if none is given), reporting what happened to each alias.
+	///
+	/// Aliases that already exist are handled as decided by `resolve`, see
+	/// [Config::import]. Fails without changing anything if the pack is already
+	/// installed or `resolve` fails.
+	pub fn install_pack(
+		&mut self, pack: &Pack, source: &str, prefix: Option<&str>,
+		resolve: impl FnMut(&AliasSpec, &AliasSpec) -> Result<Resolution>,
+	) -> Result<ImportReport> {
+		let name = &pack.pack.name;
+		if self.packs.contains_key(name) {
+			return Err(Error::PackAlreadyInstalled(name.clone()));
+		}
+
+		let prefix = prefix.unwrap_or(pack.default_prefix()).to_string();
+		let report = self.import(pack.specs(&prefix)?, resolve)?;
+		let installed = report.imported();
+		info!(
+			"{}",
+			tr!(
+				"pack.installed",
+				name = name,
+				version = pack.pack.version,
+				count = installed.len()
+			)
+		);
+		self.packs.insert(
+			name.clone(),
+			InstalledPack {
+				source: source.to_string(),
+				version: pack.pack.version.clone(),
+				prefix,
+				aliases: installed,
+			},
+		);
+		self.changed = true;
+		Ok(report)
+	}
+
+	/// Merges the aliases of another config file into this one, handling
+	/// aliases defined in both as decided by `resolve`, see [Config::import].
+	///
+	/// Only alias definitions are merged; the other file's settings, hooks and
+	/// packs are ignored.
+	pub fn merge(
+		&mut self, path: &Path, resolve: impl FnMut(&AliasSpec, &AliasSpec) -> Result<Resolution>,
+	) -> Result<ImportReport> {
+		let other = Self::parse(self.paths(), path, &Self::read(path)?)?;
+		let mut specs: Vec<AliasSpec> = other
+			.aliases
+			.iter()
+			.map(|(alias, values)| AliasSpec::from_values(alias.clone(), values))
+			.collect();
+		specs.sort_by(|a, b| a.name.cmp(&b.name));
+		for spec in &specs {
+			spec.validate()?;
+		}
+		info!("{}", tr!("import.merging", count = specs.len(), path = path.display()));
+		self.import(specs, resolve)
+	}
+
+	/// Upserts the given specs in bulk, calling `resolve` with the existing
+	/// alias and the spec for each one that collides with an existing alias to
+	/// decide whether it's skipped, overwrites the alias or is imported under a
+	/// new name. Specs identical to the alias they collide with are not
+	/// conflicts.
+	///
+	/// All conflicts are resolved before anything is changed, so an error from
+	/// `resolve` leaves the config untouched.
+	pub fn import(
+		&mut self, specs: Vec<AliasSpec>, mut resolve: impl FnMut(&AliasSpec, &AliasSpec) -> Result<Resolution>,
+	) -> Result<ImportReport> {
+		let mut report = ImportReport::default();
+		let mut taken: HashSet<String> = self
+			.aliases
+			.keys()
+			.cloned()
+			.chain(specs.iter().map(|spec| spec.name.clone()))
+			.collect();
+
+		let mut planned = Vec::with_capacity(specs.len());
+		for mut spec in specs {
+			let existing = self
+				.aliases
+				.get(&spec.name)
+				.map(|existing| AliasSpec::from_values(spec.name.clone(), existing));
+			let Some(existing) = existing.filter(|existing| *existing != spec) else {
+				planned.push((spec, None));
+				continue;
+			};
+			match resolve(&existing, &spec)? {
+				Resolution::Skip => report.skipped.push(spec.name),
+				Resolution::Overwrite => planned.push((spec, None)),
+				Resolution::Rename => {
+					let renamed = (2..)
+						.map(|n| format!("{}-{n}", spec.name))
+						.find(|name| !taken.contains(name))
+						.expect("a free name exists");
+					taken.insert(renamed.clone());
+					let original = std::mem::replace(&mut spec.name, renamed);
+					planned.push((spec, Some(original)));
+				},
+			}
+		}
+
+		for (spec, original) in planned {
+			let name = spec.name.clone();
+			let outcome = self.upsert(spec)?;
+			match original {
+				Some(original) => report.renamed.push((original, name)),
+				None => report.record(name, outcome),
+			}
+		}
+		Ok(report)
+	}
+
+	/// Re-fetches an installed pack from its original source, updating its
+	/// aliases and removing any that are no longer part of the pack.
+	pub fn update_pack(&mut self, name: &str) -> Result<()> {
+		let record = self
+			.packs
+			.get(name)
+			.cloned()
+			.ok_or_else(|| Error::PackNotInstalled(name.to_string()))?;
+		let pack = Pack::load(&record.source)?;
+		let specs = pack.specs(&record.prefix)?;
+
+		// New aliases must not clobber aliases that the pack doesn't own.
+		if let Some(spec) = specs
+			.iter()
+			.find(|spec| !record.aliases.contains(&spec.name) && self.aliases.contains_key(&spec.name))
+		{
+			return Err(Error::LinkAlreadyExists(spec.name.clone(), self.links.link_path(&spec.name)));
 		}
+
+		// Aliases of the pack may have been removed by hand since.
+		for stale in record
+			.aliases
+			.iter()
+			.filter(|alias| !specs.iter().any(|spec| &spec.name == *alias))
+		{
+			if self.aliases.contains_key(stale) {
+				self.remove_alias(stale)?;
+			}
+		}
+		let installed = self.upsert_all(specs)?;
+		info!(
+			"{}",
+			tr!(
+				"pack.updated",
+				name = name,
+				from = record.version,
+				to = pack.pack.version,
+				count = installed.len()
+			)
+		);
+		self.packs.insert(
+			name.to_string(),
+			InstalledPack {
+				version: pack.pack.version,
+				aliases: installed,
+				..record
+			},
+		);
+		self.changed = true;
 		Ok(())
 	}
 
-	/// Prints all the aliases defined in the config.toml file.
-	pub fn display_aliases(&self) {
+	/// Removes an installed pack along with all of its aliases.
+	pub fn remove_pack(&mut self, name: &str) -> Result<()> {
+		let record = self
+			.packs
+			.remove(name)
+			.ok_or_else(|| Error::PackNotInstalled(name.to_string()))?;
+		// Aliases of the pack may have been removed by hand since.
+		for alias in &record.aliases {
+			if self.aliases.contains_key(alias) {
+				self.remove_alias(alias)?;
+			}
+		}
+		info!("{}", tr!("pack.removed", name = name, count = record.aliases.len()));
+		self.changed = true;
+		Ok(())
+	}
+
+	/// Bundles the aliases carrying any of the given tags, plus any explicitly
+	/// named aliases, into a new pack with the given manifest.
+	pub fn create_pack(&self, mut manifest: PackManifest, tags: &[String], aliases: &[String]) -> Result<Pack> {
+		let selected: BTreeMap<_, _> = self
+			.aliases
+			.iter()
+			.filter(|(name, values)| {
+				aliases.contains(name) || values.effective_tags().iter().any(|tag| tags.iter().any(|t| t == tag))
+			})
+			.map(|(name, values)| AliasSpec::from_values(name.clone(), values).into_values())
+			.collect();
+		if selected.is_empty() {
+			return Err(Error::EmptyPack(manifest.name));
+		}
+		if let Some(missing) = aliases.iter().find(|alias| !self.aliases.contains_key(*alias)) {
+			warn!("{}", tr!("pack.missing_alias", alias = missing));
+		}
+
+		// Packs rely on the alias format of the cmdlink version that created
+		// them.
+		manifest
+			.compatibility
+			.min_version
+			.get_or_insert_with(|| env!("CARGO_PKG_VERSION").to_string());
+		debug!("Created pack \"{}\" with {} aliases", manifest.name, selected.len());
+		Ok(Pack {
+			pack: manifest,
+			aliases: selected,
+		})
+	}
+
+	/// Returns an iterator over the installed packs.
+	pub fn packs(&self) -> impl Iterator<Item = (&str, &InstalledPack)> {
+		self.packs.iter().map(|(name, pack)| (name.as_str(), pack))
+	}
+
+	/// Upserts all the given specs, returning their sorted alias names.
+	fn upsert_all(&mut self, specs: Vec<AliasSpec>) -> Result<Vec<String>> {
+		let mut names = Vec::with_capacity(specs.len());
+		for spec in specs {
+			names.push(spec.name.clone());
+			self.upsert(spec)?;
+		}
+		names.sort();
+		Ok(names)
+	}
+
+	/// Prints all the aliases defined in the config.toml file. The long format
+	/// includes the full command, tags, link status and timestamps of each
+	/// alias.
+	///
+	/// On a terminal, the widest columns are truncated so that the table fits,
+	/// unless `full` is set, in which case they're wrapped across lines
+	/// instead. With `broken`, only the aliases whose link is missing or whose
+	/// program can't be found are listed.
+	pub fn display_aliases(&self, long: bool, full: bool, broken: bool) {
+		let statuses = self.statuses();
+		if !self.has_shown_aliases(broken, &statuses) {
+			return;
+		}
+		info!("{}", tr!("display.available"));
+
+		let shown = self
+			.aliases
+			.iter()
+			.map(|(alias, v)| (alias, v, statuses[alias.as_str()]))
+			.filter(|(_, _, status)| !broken || status.is_broken());
+		let mut table = if long {
+			Table::new(shown.map(|(alias, v, status)| AliasDetails {
+				alias,
+				cmd: v.target(),
+				description: v.description.as_deref().unwrap_or_default(),
+				tags: v.effective_tags().join(", "),
+				status: format!("{} {}", status.icon(), status),
+				created: v.created.map(|d| d.to_string()).unwrap_or_default(),
+				updated: v.updated.map(|d| d.to_string()).unwrap_or_default(),
+			}))
+		} else {
+			Table::new(shown.map(|(alias, v, status)| AliasInfo {
+				alias,
+				status: status.icon(),
+				description: v.description.as_deref().unwrap_or_else(|| v.target()),
+			}))
+		};
+		self.settings.table_style.unwrap_or_default().apply(&mut table);
+		if let Some(columns) = width::terminal_width() {
+			if full {
+				table.with(Width::wrap(columns).priority(PriorityMax).keep_words(true));
+			} else {
+				table.with(Width::truncate(columns).priority(PriorityMax).suffix("…"));
+			}
+		}
+
+		println!("{}", width::render(table));
+		eprintln!("{}", self.summary(&statuses));
+	}
+
+	/// Prints all the aliases as a tree, grouped by the pack they were
+	/// installed from and then by tag. Aliases with several tags are listed
+	/// under each of them, and the pack level is left out when no packs are
+	/// installed. With `broken`, only the aliases whose link is missing or
+	/// whose program can't be found are listed.
+	pub fn display_tree(&self, broken: bool) {
+		let statuses = self.statuses();
+		if !self.has_shown_aliases(broken, &statuses) {
+			return;
+		}
+		let shown = |alias: &&str| !broken || statuses[*alias].is_broken();
+
+		let mut groups: Vec<(&str, TagGroups)> = Vec::new();
+		let mut packed = BTreeSet::new();
+		for (name, pack) in &self.packs {
+			let aliases = pack
+				.aliases
+				.iter()
+				.map(String::as_str)
+				.filter(|alias| self.aliases.contains_key(*alias));
+			packed.extend(aliases.clone());
+			let aliases = aliases.filter(shown);
+			groups.push((name, self.by_tag(aliases)));
+		}
+		let rest = self
+			.aliases
+			.keys()
+			.map(String::as_str)
+			.filter(|alias| !packed.contains(alias))
+			.filter(shown);
+		let no_pack = tr!("display.no_pack");
+		groups.push((&no_pack, self.by_tag(rest)));
+		groups.retain(|(_, tags)| !tags.is_empty());
+
+		let mut tree = String::new();
+		if self.packs.is_empty() {
+			self.write_tags(&mut tree, &groups[0].1, None, &statuses);
+		} else {
+			for (group, tags) in &groups {
+				let _ = writeln!(tree, "{group}");
+				self.write_tags(&mut tree, tags, Some(""), &statuses);
+			}
+		}
+		print!("{tree}");
+		eprintln!("{}", self.summary(&statuses));
+	}
+
+	/// Whether there are any aliases to display, only counting broken ones if
+	/// `broken` is set, telling the user otherwise.
+	fn has_shown_aliases(&self, broken: bool, statuses: &BTreeMap<&str, LinkStatus>) -> bool {
 		if self.aliases.is_empty() {
-			info!("No aliases available.");
+			eprintln!("{}", tr!("display.no_aliases"));
+			return false;
+		}
+		if broken && !statuses.values().any(|status| status.is_broken()) {
+			eprintln!("{}", tr!("display.none_broken"));
+			return false;
+		}
+		true
+	}
+
+	/// Groups aliases by tag.
+	fn by_tag<'a>(&'a self, aliases: impl Iterator<Item = &'a str>) -> TagGroups<'a> {
+		let mut tags = TagGroups::new();
+		for alias in aliases {
+			let values = &self.aliases[alias];
+			let alias_tags = values.effective_tags();
+			if alias_tags.is_empty() {
+				tags.entry(None).or_default().push(alias);
+			}
+			for tag in alias_tags {
+				tags.entry(Some(tag)).or_default().push(alias);
+			}
+		}
+		tags.values_mut().for_each(|aliases| aliases.sort_unstable());
+		tags
+	}
+
+	/// Writes the tags and their aliases as branches of a tree at the given
+	/// indent, or as roots without one, noting the description and any link
+	/// problems of each alias.
+	fn write_tags(
+		&self, tree: &mut String, tags: &TagGroups, indent: Option<&str>, statuses: &BTreeMap<&str, LinkStatus>,
+	) {
+		// Untagged aliases sort first, but read better at the end.
+		let untagged = tr!("display.untagged");
+		let tags: Vec<(&str, &Vec<&str>)> = tags
+			.iter()
+			.filter_map(|(tag, aliases)| Some(((*tag)?, aliases)))
+			.chain(tags.get(&None).map(|aliases| (untagged.as_str(), aliases)))
+			.collect();
+		for (idx, (tag, aliases)) in tags.iter().enumerate() {
+			let (branch, tag_indent) = match indent {
+				Some(indent) => {
+					let (branch, tag_indent) = tree_branch(idx == tags.len() - 1);
+					(format!("{indent}{branch}"), format!("{indent}{tag_indent}"))
+				},
+				None => (String::new(), String::new()),
+			};
+			let _ = writeln!(tree, "{branch}{tag}");
+			for (idx, alias) in aliases.iter().enumerate() {
+				let (branch, _) = tree_branch(idx == aliases.len() - 1);
+				let values = &self.aliases[*alias];
+				let _ = write!(tree, "{tag_indent}{branch}{alias}");
+				if let Some(description) = &values.description {
+					let _ = write!(tree, " - {description}");
+				}
+				match statuses[*alias] {
+					LinkStatus::Ok => tree.push('\n'),
+					status => {
+						let _ = writeln!(tree, " ({status})");
+					},
+				}
+			}
+		}
+	}
+
+	/// Prints the aliases whose command invokes the given program or contains
+	/// the given text, e.g. to find the aliases to fix after a tool is renamed.
+	pub fn display_dependents(&self, needle: &str) {
+		let mut dependents: Vec<AliasCommand> = self
+			.aliases
+			.iter()
+			.filter_map(|(alias, v)| v.invoking(needle).map(|cmd| AliasCommand { alias, cmd }))
+			.collect();
+		if dependents.is_empty() {
+			eprintln!("{}", tr!("why.none", needle = needle));
 			return;
 		}
-		info!("Available aliases:");
+		dependents.sort_by_key(|dependent| dependent.alias);
 
-		let alias_iter = self.aliases.iter().map(|(alias, v)| AliasInfo {
-			alias,
-			description: v.description.as_deref().unwrap_or(&v.cmd),
+		let mut table = Table::new(dependents);
+		self.settings.table_style.unwrap_or_default().apply(&mut table);
+		println!("{}", width::render(table));
+	}
+
+	/// Prints the time per call of each backend timed by `cmdlink bench`, and
+	/// its overhead over running the command directly.
+	pub fn display_bench(&self, timings: &[Timing]) {
+		let millis = |duration: Duration| format!("{:.3} ms", duration.as_secs_f64() * 1000.0);
+		let mut table = Table::new(timings.iter().map(|timing| BenchInfo {
+			backend: timing.backend.name(),
+			per_call: millis(timing.per_call),
+			overhead: match timing.backend {
+				Backend::Direct => "-".to_string(),
+				_ => millis(timing.overhead),
+			},
+		}));
+		self.settings.table_style.unwrap_or_default().apply(&mut table);
+		println!("{}", width::render(table));
+	}
+
+	/// Prints recorded runs of aliases, oldest first. Mentions the
+	/// `run_history` setting if there are none and it's disabled.
+	pub fn display_runs(&self, runs: &[Run]) {
+		if runs.is_empty() {
+			eprintln!("{}", tr!("history.none"));
+			if !self.settings.run_history.unwrap_or_default() {
+				eprintln!("{}", tr!("history.disabled"));
+			}
+			return;
+		}
+
+		let mut table = Table::new(runs.iter().map(|run| RunInfo {
+			started: &run.started,
+			alias: &run.alias,
+			status: run.status,
+			duration: humantime::format_duration(run.duration()).to_string(),
+		}));
+		self.settings.table_style.unwrap_or_default().apply(&mut table);
+		println!("{}", width::render(table));
+	}
+
+	/// Lists the aliases that weren't used since the given time, least
+	/// recently used first, along with when they were last used if known.
+	///
+	/// An alias counts as used when one of the given runs invoked it, or else
+	/// when it was last added or updated, so aliases recorded before the
+	/// `run_history` setting was enabled aren't reported right away. Aliases
+	/// without either are always reported.
+	pub fn stale_aliases(&self, runs: &[Run], since: SystemTime) -> Vec<(String, Option<SystemTime>)> {
+		let parse = |time: &str| humantime::parse_rfc3339_weak(time).ok();
+		let mut last_runs: HashMap<&str, SystemTime> = HashMap::new();
+		for run in runs {
+			if let Some(started) = parse(&run.started) {
+				let last = last_runs.entry(&run.alias).or_insert(started);
+				*last = (*last).max(started);
+			}
+		}
+
+		let mut stale: Vec<(String, Option<SystemTime>)> = self
+			.aliases
+			.iter()
+			.map(|(alias, v)| {
+				let last_used = last_runs.get(alias.as_str()).copied().or_else(|| {
+					v.updated
+						.or(v.created)
+						.and_then(|datetime| parse(&datetime.to_string()))
+				});
+				(alias.clone(), last_used)
+			})
+			.filter(|(_, last_used)| last_used.is_none_or(|last_used| last_used < since))
+			.collect();
+		stale.sort();
+		stale.sort_by_key(|(_, last_used)| *last_used);
+		stale
+	}
+
+	/// Prints the aliases found by [Config::stale_aliases], with when they were
+	/// last used and their command.
+	pub fn display_stale(&self, stale: &[(String, Option<SystemTime>)]) {
+		let mut table = Table::new(stale.iter().map(|(alias, last_used)| {
+			StaleInfo {
+				alias,
+				last_used: last_used
+					.map(|time| humantime::format_rfc3339_seconds(time).to_string())
+					.unwrap_or_else(|| tr!("stale.unknown")),
+				cmd: self
+					.aliases
+					.get(alias)
+					.map(AliasValues::effective_cmd)
+					.unwrap_or_default(),
+			}
+		}));
+		self.settings.table_style.unwrap_or_default().apply(&mut table);
+		println!("{}", width::render(table));
+	}
+
+	/// Prints everything known about an alias: its descriptions, command,
+	/// examples, tags and link status. Fails with [Error::UnknownAlias] if the
+	/// alias doesn't exist.
+	pub fn show_alias(&self, alias: &str) -> Result<()> {
+		let values = self
+			.aliases
+			.get(alias)
+			.ok_or_else(|| Error::UnknownAlias(alias.to_string()))?;
+
+		match &values.description {
+			Some(description) => println!("{alias} - {description}"),
+			None => println!("{alias}"),
+		}
+		println!("\n    {}", values.target());
+		if let Some(long_description) = &values.long_description {
+			println!();
+			for line in long_description.trim_end().lines() {
+				println!("{line}");
+			}
+		}
+		if !values.examples.is_empty() {
+			println!("\n{}", tr!("display.examples"));
+			for example in &values.examples {
+				println!("    {example}");
+			}
+		}
+		println!();
+		let tags = values.effective_tags();
+		if !tags.is_empty() {
+			println!("{}", tr!("display.tags", tags = tags.join(", ")));
+		}
+		if let Some(group) = &values.group {
+			println!("{}", tr!("display.group", group = group));
+		}
+		if let Some(min_version) = &values.min_version {
+			println!(
+				"{}",
+				tr!("display.min_version", require = min_version.require, cmd = min_version.cmd)
+			);
+		}
+		println!("{}", tr!("display.status", status = values.status(alias, &self.links)));
+		Ok(())
+	}
+
+	/// A one line summary of the health of the aliases, counting broken,
+	/// unresolved and disabled aliases among the given statuses.
+	fn summary(&self, statuses: &BTreeMap<&str, LinkStatus>) -> String {
+		let (mut broken, mut unresolved, mut disabled) = (0, 0, 0);
+		for status in statuses.values() {
+			match status {
+				LinkStatus::Broken => broken += 1,
+				LinkStatus::Unresolved => unresolved += 1,
+				LinkStatus::Disabled => disabled += 1,
+				LinkStatus::Ok => {},
+			}
+		}
+		let total = self.aliases.len();
+		let id = if total == 1 {
+			"display.summary_one"
+		} else {
+			"display.summary"
+		};
+		tr!(id, total = total, broken = broken, unresolved = unresolved, disabled = disabled)
+	}
+
+	/// Serializes the config as it was loaded, as TOML or as JSON.
+	pub fn serialize(&self, json: bool) -> Result<String> {
+		if json {
+			// Round trip through TOML text so datetimes are parsed as such.
+			let value: toml::Value = toml::from_str(&toml::to_string(self)?)
+				.map_err(|e| Error::ConfigParse(self.paths().config_file(), Box::new(e)))?;
+			Ok(serde_json::to_string_pretty(&toml_to_json(value))?)
+		} else {
+			Ok(toml::to_string_pretty(self)?)
+		}
+	}
+
+	/// Serializes the config in the canonical style it's saved in: aliases
+	/// sorted by name, keys in a fixed order, and long strings wrapped over
+	/// several lines.
+	pub fn to_canonical(&self) -> Result<String> { Ok(wrap_long_strings(&toml::to_string(self)?)) }
+
+	/// Rewrites the config.toml file of the given project directory in the
+	/// canonical style, see [Config::to_canonical], returning whether it had to
+	/// change. With `check` the file is left as is, and an error is returned if
+	/// it would change.
+	pub fn format_file(paths: &CmdlinkPaths, check: bool) -> Result<bool> {
+		let config_file_path = paths.config_file();
+		let current = Self::read(&config_file_path)?;
+		let mut cfg = Self::parse(paths, &config_file_path, &current)?;
+		// The file is written below, never on drop.
+		cfg.read_only = true;
+
+		let canonical = cfg.to_canonical()?;
+		if canonical == current {
+			return Ok(false);
+		}
+		if check {
+			return Err(Error::ConfigUnformatted(config_file_path));
+		}
+		std::fs::write(&config_file_path, canonical).map_err(|e| Error::ConfigWrite(config_file_path, e))?;
+		Ok(true)
+	}
+
+	/// Prints all the installed packs.
+	pub fn display_packs(&self) {
+		if self.packs.is_empty() {
+			eprintln!("{}", tr!("pack.none"));
+			return;
+		}
+
+		let pack_iter = self.packs.iter().map(|(name, pack)| PackInfo {
+			name,
+			version: &pack.version,
+			prefix: &pack.prefix,
+			aliases: pack.aliases.len(),
 		});
-		let mut table = Table::new(alias_iter);
-		table.with(Style::rounded()); // TODO: explore styling changes
+		let mut table = Table::new(pack_iter);
+		self.settings.table_style.unwrap_or_default().apply(&mut table);
 
-		println!("{}", table);
+		println!("{}", width::render(table));
 	}
 
 	/// Refreshes all the bad links, setting the action to Create for any links
-	/// that do not exist.
-	pub fn refresh_links(&mut self) -> Result<()> {
-		info!("Refreshing command links...");
-
-		for alias_values in self.aliases.values_mut() {
-			if let Some(link) = alias_values.link.as_mut() {
-				if !link.exists() {
-					debug!("Bad link for alias: {}", link.alias());
-					link.set_action(Action::Create);
+	/// that do not exist and to Update for those whose contents are outdated,
+	/// e.g. after the `pre_exec` or `post_exec` hooks changed. When `force` is
+	/// set, every link is rewritten from the current settings instead.
+	///
+	/// Links whose file differs from what would be generated may have been
+	/// edited by hand, so `keep` is asked whether to leave each of them as it
+	/// is, with the link and the contents it would get.
+	///
+	/// Files in the `bins` directory that don't belong to any alias are
+	/// reported, and deleted when `prune` is set.
+	pub fn refresh_links(
+		&mut self, force: bool, prune: bool, mut keep: impl FnMut(&PlatformBinary, &str) -> Result<bool>,
+	) -> Result<()> {
+		info!("{}", tr!("refresh.refreshing"));
+
+		for orphan in self.orphaned_files()? {
+			if prune {
+				std::fs::remove_file(&orphan).map_err(|e| Error::OrphanRemoval(orphan.display().to_string(), e))?;
+				info!("{}", tr!("refresh.pruned", path = orphan.display()));
+			} else {
+				warn!("{}", tr!("refresh.orphaned", path = orphan.display()));
+			}
+		}
+
+		if !force {
+			self.scan_links();
+		}
+		for (alias, alias_values) in self.aliases.iter_mut().filter(|(_, v)| !v.disabled) {
+			if force {
+				trace!("Rewriting link for alias: {}", alias);
+				alias_values.set_action(Action::Update);
+			} else if !alias_values.link(alias, &self.links).exists() {
+				debug!("Bad link for alias: {}", alias);
+				alias_values.set_action(Action::Create);
+			} else if alias_values.link(alias, &self.links).is_outdated()? {
+				let link = alias_values.link(alias, &self.links);
+				let kept = keep(&link, &link.contents()?)?;
+				if kept {
+					info!("{}", tr!("refresh.kept", alias = alias));
+					continue;
 				}
+				debug!("Outdated link for alias: {}", alias);
+				alias_values.set_action(Action::Update);
 			}
 		}
 		self.changed = true;
+		self.queue_hook(HookKind::PostRefresh, None);
 		Ok(())
 	}
 
-	/// Saves the current Config instance to the config.toml file.
-	fn save(&mut self) -> Result<()> {
-		self.save_links()?;
-		let config_file_path = crate::PROJECT_DIR.join("config.toml");
-		let cfg_bytes = toml::to_string(&self)?.into_bytes();
-		std::fs::write(config_file_path, cfg_bytes).map_err(Error::ConfigWrite)
+	/// Returns the files in the `bins` directory that don't correspond to any
+	/// alias, such as leftovers from removed aliases or manually added files.
+	///
+	/// A bins directory set with `relocate` may be shared with other programs,
+	/// such as `~/.local/bin`, so no files are considered orphaned there.
+	pub fn orphaned_files(&self) -> Result<Vec<PathBuf>> {
+		if self.settings.bins_dir.is_some() {
+			return Ok(Vec::new());
+		}
+		self.unmanaged_files()
 	}
 
-	/// Saves link changes, if any, to the platform binary files.
-	fn save_links(&mut self) -> Result<()> {
-		let (tx, rx) = channel();
+	/// Returns the files in the bins directory that don't correspond to any
+	/// alias, such as hand-written scripts that `cmdlink adopt` can turn into
+	/// aliases. The backups and temporary files of links are left out.
+	pub fn unmanaged_files(&self) -> Result<Vec<PathBuf>> {
+		let bins_dir = self.links.bins_dir();
+		let entries = std::fs::read_dir(bins_dir).map_err(|e| Error::BinsRead(bins_dir.to_path_buf(), e))?;
 
-		for alias_values in self.aliases.values_mut() {
-			// Safetey: all links are initialized in Config creation
-			let link = unsafe { alias_values.link.as_mut().unwrap_unchecked() };
-			if !matches!(link.action(), Action::None) {
-				link.perform_action()?;
+		let mut orphans = Vec::new();
+		for entry in entries {
+			let path = entry.map_err(|e| Error::BinsRead(bins_dir.to_path_buf(), e))?.path();
+			if !path.is_file() || platform_binary::is_link_copy(&path) {
+				continue;
 			}
-			if matches!(link.action(), Action::Remove) {
-				debug!("Removing link for alias: {}", link.alias());
-				let _ = tx.send(link.alias().to_string());
+			if !platform_binary::alias_of(&path).is_some_and(|alias| self.aliases.contains_key(alias)) {
+				orphans.push(path);
 			}
 		}
-		drop(tx);
-		while let Ok(alias) = rx.recv() {
-			trace!("Removed link for alias: {}", alias);
-			self.aliases.remove(&alias);
+		orphans.sort();
+		Ok(orphans)
+	}
+
+	/// Brings the links in line with the config, recreating missing links and
+	/// rewriting links whose contents no longer match their alias. Returns the
+	/// number of links that were scheduled for changes.
+	///
+	/// Unlike [Config::refresh_links], the config is only marked as changed if
+	/// any link actually needs work.
+	pub fn sync_links(&mut self) -> Result<usize> {
+		let mut scheduled = 0;
+		self.scan_links();
+		for (alias, alias_values) in self.aliases.iter_mut().filter(|(_, v)| !v.disabled) {
+			let link = alias_values.link(alias, &self.links);
+			let action = if !link.exists() {
+				debug!("Missing link for alias: {}", alias);
+				Action::Create
+			} else if link.is_outdated()? {
+				debug!("Outdated link for alias: {}", alias);
+				Action::Update
+			} else {
+				continue;
+			};
+			alias_values.set_action(action);
+			scheduled += 1;
+		}
+
+		if scheduled > 0 {
+			info!("{}", tr!("refresh.regenerating", count = scheduled));
+			self.changed = true;
+			self.queue_hook(HookKind::PostRefresh, None);
 		}
+		Ok(scheduled)
+	}
+
+	/// Queues a hook to run once the config has been saved successfully.
+	fn queue_hook(&mut self, kind: HookKind, alias: Option<&str>) {
+		self.pending_hooks.push(HookEvent {
+			kind,
+			alias: alias.map(str::to_string),
+		});
+	}
 
+	/// Saves the config if anything changed, applying the pending link changes
+	/// and running the queued hooks once it's saved. Commands save explicitly
+	/// so that failures reach the user; the config also saves when dropped,
+	/// where failures can only be logged.
+	pub fn save(&mut self) -> Result<()> {
+		if !self.changed || self.read_only {
+			return Ok(());
+		}
+		// A failed save isn't retried when the config is dropped.
+		self.changed = false;
+		self.write()?;
+		info!("{}", tr!("config.saved"));
+		for event in std::mem::take(&mut self.pending_hooks) {
+			self.hooks.run(&event, self.links.paths().root());
+		}
 		Ok(())
 	}
 
-	/// Initializes the links for all aliases defined in the config.toml file.
-	fn initialize_links(&mut self) -> Result<()> {
-		for (alias, AliasValues { link, cmd, .. }) in self.aliases.iter_mut() {
-			let platform_binary = PlatformBinary::new(alias.to_string(), cmd.to_string(), Action::None);
+	/// Writes the current Config instance to the config.toml file, and rewrites
+	/// the completion loader and the AutoRun script if it was installed.
+	fn write(&mut self) -> Result<()> {
+		self.check_chains()?;
+		self.link_groups();
+		self.link_chains();
+		let backup = self.save_links()?;
+		let config_file_path = self.paths().config_file();
+		let saved = self.to_canonical().and_then(|cfg_str| {
+			std::fs::write(&config_file_path, cfg_str).map_err(|e| Error::ConfigWrite(config_file_path, e))
+		});
+		match saved {
+			Ok(()) => backup.discard(),
+			Err(why) => {
+				backup.restore();
+				return Err(why);
+			},
+		}
+		completion::write(self)?;
+		autorun::refresh(self)
+	}
+
+	/// Saves link changes, if any, to the platform binary files, returning a
+	/// backup of the links they replaced so that [Config::write] can undo them
+	/// should the config fail to save.
+	///
+	/// Links are written in parallel across the available cores. A failing link
+	/// doesn't stop the others, all failures are reported together instead,
+	/// after the links that were changed are restored from the backup.
+	fn save_links(&mut self) -> Result<LinkBackup> {
+		let pending: Vec<PlatformBinary> = self
+			.aliases
+			.iter()
+			.filter(|(_, alias_values)| !matches!(alias_values.link.action, Action::None))
+			.map(|(alias, alias_values)| alias_values.link(alias, &self.links))
+			.collect();
+		let backup = LinkBackup::new(&pending)?;
+		if pending.is_empty() {
+			return Ok(backup);
+		}
+
+		let (tx, rx) = channel();
+		let workers = std::thread::available_parallelism().map_or(1, NonZeroUsize::get);
+		let chunk_size = pending.len().div_ceil(workers);
+		std::thread::scope(|scope| {
+			for chunk in pending.chunks(chunk_size) {
+				let tx = tx.clone();
+				scope.spawn(move || {
+					for link in chunk {
+						let _ = tx.send((link.alias().to_string(), link.action(), link.perform_action()));
+					}
+				});
+			}
+		});
+		drop(tx);
 
-			if !platform_binary.exists() {
-				warn!(
-					"Platform binary file for alias \"{}\" not found. Either the binary files were deleted, or the config was updated manually. Run [refresh] command to refresh config and create links.",
-					alias
-				);
+		let mut errors = Vec::new();
+		for (alias, action, result) in rx {
+			match result {
+				Err(why) => errors.push(why),
+				Ok(()) if matches!(action, Action::Remove) => {
+					trace!("Removed link for alias: {}", alias);
+					self.aliases.remove(&alias);
+				},
+				Ok(()) => trace!("Saved link for alias: {}", alias),
 			}
-			*link = Some(platform_binary);
 		}
 
-		Ok(())
+		if errors.is_empty() {
+			return Ok(backup);
+		}
+		backup.restore();
+		match errors.len() {
+			1 => Err(errors.remove(0)),
+			_ => Err(Error::LinkBatch(errors)),
+		}
 	}
 }
 
-impl Drop for Config {
-	fn drop(&mut self) {
-		if self.changed {
-			if let Err(why) = self.save() {
-				error!("Config Save Error: {why}");
+/// Serializes a map sorted by key, so that saving the config doesn't reorder
+/// it.
+fn serialize_sorted<S: serde::Serializer, V: Serialize>(
+	map: &HashMap<String, V>, serializer: S,
+) -> std::result::Result<S::Ok, S::Error> {
+	map.iter().collect::<BTreeMap<_, _>>().serialize(serializer)
+}
+
+/// Wraps `key = "value"` lines longer than [WRAP_WIDTH] over several lines, as
+/// multi-line strings whose line ending backslashes leave the value unchanged.
+///
+/// Lines are only broken after a single space, as any whitespace following a
+/// line ending backslash is trimmed. Should the wrapped TOML still parse to a
+/// different value, the input is returned as is.
+fn wrap_long_strings(toml: &str) -> String {
+	let mut wrapped = String::with_capacity(toml.len());
+	for line in toml.lines() {
+		let value = line
+			.split_once(" = \"")
+			.filter(|(_, value)| line.chars().count() > WRAP_WIDTH && !value.starts_with('"'))
+			.and_then(|(key, value)| Some((key, value.strip_suffix('"')?)));
+		let Some((key, value)) = value else {
+			wrapped.push_str(line);
+			wrapped.push('\n');
+			continue;
+		};
+
+		// Runs of spaces stay on the line before the break.
+		let mut pieces: Vec<String> = Vec::new();
+		for piece in value.split_inclusive(' ') {
+			match pieces.last_mut() {
+				Some(last) if piece.starts_with(' ') => last.push_str(piece),
+				_ => pieces.push(piece.to_string()),
+			}
+		}
+		let mut lines = vec![String::new()];
+		for piece in pieces {
+			let current = lines.last_mut().expect("lines is never empty");
+			if !current.is_empty() && current.chars().count() + piece.chars().count() > WRAP_WIDTH {
+				lines.push(piece);
 			} else {
-				info!("Configuration changes saved successfully");
+				current.push_str(&piece);
 			}
 		}
+		wrapped.push_str(&format!("{key} = \"\"\"\n{}\"\"\"\n", lines.join("\\\n")));
+	}
+
+	let same_value = |a: &str, b: &str| toml::from_str::<toml::Value>(a).ok() == toml::from_str::<toml::Value>(b).ok();
+	if same_value(toml, &wrapped) {
+		wrapped
+	} else {
+		debug!("Wrapping long strings changed the config, leaving them unwrapped");
+		toml.to_string()
+	}
+}
+
+/// Converts a TOML value into JSON, rendering datetimes as strings.
+fn toml_to_json(value: toml::Value) -> serde_json::Value {
+	match value {
+		toml::Value::String(s) => s.into(),
+		toml::Value::Integer(i) => i.into(),
+		toml::Value::Float(f) => f.into(),
+		toml::Value::Boolean(b) => b.into(),
+		toml::Value::Datetime(d) => d.to_string().into(),
+		toml::Value::Array(values) => values.into_iter().map(toml_to_json).collect(),
+		toml::Value::Table(table) => table.into_iter().map(|(k, v)| (k, toml_to_json(v))).collect(),
+	}
+}
+
+impl Drop for Config {
+	fn drop(&mut self) {
+		if let Err(why) = self.save() {
+			error!("{}", tr!("config.save_failed", error = why));
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::alias::AliasBuilder;
+
+	/// An empty project directory in the temp directory, with a bins directory.
+	fn temp_paths(name: &str) -> CmdlinkPaths {
+		let paths = CmdlinkPaths::new(std::env::temp_dir().join(format!("cmdlink-{name}-{}", std::process::id())));
+		let _ = std::fs::remove_dir_all(paths.root());
+		std::fs::create_dir_all(paths.default_bins_dir()).unwrap();
+		paths
+	}
+
+	#[test]
+	fn configs_keep_to_their_project_directory() {
+		let (a, b) = (temp_paths("config-a"), temp_paths("config-b"));
+		let mut cfg_a = Config::new_in(&a).unwrap();
+		let mut cfg_b = Config::new_in(&b).unwrap();
+		cfg_a
+			.upsert(AliasBuilder::new("in-a", "echo a").build().unwrap())
+			.unwrap();
+		cfg_b
+			.upsert(AliasBuilder::new("in-b", "echo {cmdlink_dir}").build().unwrap())
+			.unwrap();
+		cfg_a.save().unwrap();
+		cfg_b.save().unwrap();
+
+		let (link_a, link_b) = (cfg_a.links().link_path("in-a"), cfg_b.links().link_path("in-b"));
+		assert_eq!(link_a.parent(), Some(a.default_bins_dir().as_path()));
+		assert_eq!(link_b.parent(), Some(b.default_bins_dir().as_path()));
+		assert!(link_a.is_file() && link_b.is_file());
+		assert!(!b.default_bins_dir().join(link_a.file_name().unwrap()).exists());
+		assert!(!a.default_bins_dir().join(link_b.file_name().unwrap()).exists());
+		let contents = std::fs::read_to_string(&link_b).unwrap();
+		assert!(contents.contains(&b.root().display().to_string()));
+
+		let reloaded = Config::read_only_in(&a).unwrap();
+		assert!(reloaded.alias("in-a").is_some() && reloaded.alias("in-b").is_none());
+		assert_eq!(reloaded.paths(), &a);
+		for paths in [a, b] {
+			std::fs::remove_dir_all(paths.root()).unwrap();
+		}
+	}
+
+	#[test]
+	fn unmanaged_files_leave_out_link_copies() {
+		let paths = temp_paths("unmanaged");
+		let mut cfg = Config::new_in(&paths).unwrap();
+		cfg.upsert(AliasBuilder::new("managed", "echo hi").build().unwrap())
+			.unwrap();
+		cfg.save().unwrap();
+
+		let bins = paths.default_bins_dir();
+		let extension = cfg.links().extension(None);
+		for name in [
+			"script".to_string(),
+			format!(".managed{extension}.bak"),
+			format!(".managed{extension}.tmp"),
+		] {
+			std::fs::write(bins.join(name), "").unwrap();
+		}
+		assert_eq!(cfg.unmanaged_files().unwrap(), [bins.join("script")]);
+		std::fs::remove_dir_all(paths.root()).unwrap();
+	}
+
+	#[test]
+	fn same_definition_ignores_state_and_timestamps() {
+		let values = || AliasValues {
+			cmd: "git status".to_string(),
+			nice: Some(5),
+			..AliasValues::default()
+		};
+		let stamped = AliasValues {
+			disabled: true,
+			created: "2026-01-01".parse().ok(),
+			..values()
+		};
+		assert!(values().same_definition(&stamped));
+		let niced = AliasValues {
+			nice: Some(10),
+			..values()
+		};
+		assert!(!values().same_definition(&niced));
+	}
+	#[test]
+	fn invalid_env_keys_are_rejected_on_load() {
+		let paths = CmdlinkPaths::new(std::env::temp_dir().join("cmdlink-env-keys"));
+		let parse = |config: &str| Config::parse(&paths, &paths.config_file(), config);
+		assert!(parse("[aliases.ok]\ncmd = \"env\"\nenv = { _GOOD_1 = \"x\" }\n").is_ok());
+		for config in [
+			"[aliases.bad]\ncmd = \"env\"\nenv = { \"X=1; rm -rf ~; Y\" = \"x\" }\n",
+			"[aliases.bad]\ncmd = \"env\"\nsecrets = { 1TOKEN = \"api/token\" }\n",
+			"[groups.g]\nenv = { \"A-B\" = \"x\" }\n\n[aliases.bad]\ncmd = \"env\"\ngroup = \"g\"\n",
+		] {
+			assert!(
+				matches!(parse(config), Err(Error::InvalidEnvKey(alias, _)) if alias == "bad"),
+				"{config}"
+			);
+		}
 	}
 }
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::alias::AliasSpec;
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// Defaults for newly created aliases, configured through the `[defaults]`
+/// table. They fill in the fields an alias is created without, so that team
+/// conventions apply without having to be repeated on every `cmdlink add`.
+pub struct Defaults {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The description of aliases created without one. `{alias}` and `{cmd}`
+	/// are replaced with the alias name and command.
+	pub description: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// The tags of aliases created without any.
+	pub tags: Vec<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Whether or not the links of new aliases pass their arguments on to the
+	/// command.
+	pub forward_args: Option<bool>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The shell the commands of new aliases run through on Unix.
+	pub shell: Option<String>,
+}
+
+impl Defaults {
+	/// Whether or not no defaults are configured.
+	pub fn is_empty(&self) -> bool { *self == Defaults::default() }
+
+	/// Fills in the fields the spec leaves unset with the defaults.
+	pub fn apply(&self, mut spec: AliasSpec) -> AliasSpec {
+		if spec.description.is_none() {
+			spec.description = self
+				.description
+				.as_ref()
+				.map(|template| template.replace("{alias}", &spec.name).replace("{cmd}", &spec.cmd));
+		}
+		if spec.tags.is_empty() {
+			spec.tags = self.tags.clone();
+		}
+		if spec.forward_args.is_none() {
+			spec.forward_args = self.forward_args;
+		}
+		if spec.shell.is_none() {
+			spec.shell = self.shell.clone();
+		}
+		spec
+	}
+}
@@ -0,0 +1,106 @@
+//! Deriving alias descriptions from the `--help` output of their commands.
+
+use std::{
+	io::Read,
+	process::{Command, Stdio},
+	thread,
+	time::{Duration, Instant},
+};
+
+/// How long the command is given to print its help before it's killed.
+const HELP_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Runs the program of the command with `--help`, falling back to `-h`, and
+/// returns the first summary line of its output, if any.
+///
+/// Usage lines, option lists and their indented continuations are skipped, so
+/// for most tools this is the one line description following the usage.
+pub fn from_help(cmd: &str) -> Option<String> {
+	let program = program(cmd)?;
+	["--help", "-h"]
+		.into_iter()
+		.find_map(|flag| help_output(program, flag).as_deref().and_then(summary))
+}
+
+/// The program of a command line, honoring quotes around it.
+pub(crate) fn program(cmd: &str) -> Option<&str> {
+	let cmd = cmd.trim_start();
+	match cmd.chars().next()? {
+		quote @ ('"' | '\'') => cmd[1..].split(quote).next(),
+		_ => cmd.split_whitespace().next(),
+	}
+	.filter(|program| !program.is_empty())
+}
+
+/// Runs the program with the given help flag, returning its stdout, or its
+/// stderr if stdout is empty and the program succeeded. Programs that don't
+/// exit within [HELP_TIMEOUT] are killed.
+fn help_output(program: &str, flag: &str) -> Option<String> {
+	debug!("Running `{program} {flag}` for a description");
+	let mut child = Command::new(program)
+		.arg(flag)
+		.stdin(Stdio::null())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.ok()?;
+
+	// The output is drained on separate threads, so that programs with long
+	// help texts don't block on a full pipe.
+	let read = |mut pipe: Box<dyn Read + Send>| {
+		thread::spawn(move || {
+			let mut output = Vec::new();
+			let _ = pipe.read_to_end(&mut output);
+			String::from_utf8_lossy(&output).into_owned()
+		})
+	};
+	let stdout = read(Box::new(child.stdout.take()?));
+	let stderr = read(Box::new(child.stderr.take()?));
+
+	let deadline = Instant::now() + HELP_TIMEOUT;
+	while child.try_wait().ok()?.is_none() {
+		if Instant::now() >= deadline {
+			debug!("`{program} {flag}` timed out");
+			let _ = child.kill();
+			let _ = child.wait();
+			return None;
+		}
+		thread::sleep(Duration::from_millis(20));
+	}
+
+	// Help printed to stderr is only trusted if the program succeeded, as it's
+	// an error message otherwise.
+	let succeeded = child.wait().ok()?.success();
+	let stdout = stdout.join().ok()?;
+	if stdout.trim().is_empty() && succeeded {
+		stderr.join().ok()
+	} else {
+		Some(stdout)
+	}
+}
+
+/// Picks the first summary sentence out of help output. Sentences wrapped
+/// across lines are joined back together.
+///
+/// Only unindented lines are considered, skipping usage lines, options and
+/// section headings such as `Options:`, which don't describe anything.
+fn summary(output: &str) -> Option<String> {
+	let is_text =
+		|line: &&str| !line.trim().is_empty() && !line.starts_with(char::is_whitespace) && !line.starts_with('-');
+	let mut lines = output.lines();
+	let first = lines.by_ref().find(|line| {
+		let heading = line.ends_with(':') && line.split_whitespace().count() <= 5;
+		is_text(line) && !heading && !line.to_ascii_lowercase().starts_with("usage")
+	})?;
+
+	let paragraph = std::iter::once(first)
+		.chain(lines.take_while(is_text))
+		.map(str::trim)
+		.collect::<Vec<_>>()
+		.join(" ");
+	let sentence = match paragraph.find(". ") {
+		Some(end) => &paragraph[..=end],
+		None => &paragraph,
+	};
+	Some(sentence.trim_end_matches(':').to_string())
+}
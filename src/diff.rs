@@ -0,0 +1,40 @@
+//! Line diffs, shown when asking the user to choose between two versions of a
+//! link or alias definition.
+
+/// The lines of `old` and `new`, prefixed with `-` if only in `old`, `+` if
+/// only in `new`, and a space if in both, in the order of a longest common
+/// subsequence.
+pub fn lines(old: &str, new: &str) -> String {
+	let old: Vec<&str> = old.lines().collect();
+	let new: Vec<&str> = new.lines().collect();
+
+	// common[i][j] is the length of the longest common subsequence of
+	// old[i..] and new[j..].
+	let mut common = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+	for i in (0..old.len()).rev() {
+		for j in (0..new.len()).rev() {
+			common[i][j] = if old[i] == new[j] {
+				common[i + 1][j + 1] + 1
+			} else {
+				common[i + 1][j].max(common[i][j + 1])
+			};
+		}
+	}
+
+	let mut diff = String::new();
+	let (mut i, mut j) = (0, 0);
+	while i < old.len() || j < new.len() {
+		if i < old.len() && j < new.len() && old[i] == new[j] {
+			diff.push_str(&format!(" {}\n", old[i]));
+			i += 1;
+			j += 1;
+		} else if j < new.len() && (i == old.len() || common[i][j + 1] > common[i + 1][j]) {
+			diff.push_str(&format!("+{}\n", new[j]));
+			j += 1;
+		} else {
+			diff.push_str(&format!("-{}\n", old[i]));
+			i += 1;
+		}
+	}
+	diff
+}
@@ -0,0 +1,98 @@
+//! Exporting the config in the layout of a dotfiles manager, as done by
+//! `cmdlink export --dotfiles`.
+
+use std::path::{Component, Path, PathBuf};
+
+use crate::{error::Error, paths, Config, Result};
+
+/// The dotfiles managers the config can be exported for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Layout {
+	/// A GNU stow package named `cmdlink`, mirroring the home directory.
+	Stow,
+	/// A chezmoi source directory, with `dot_` prefixed names.
+	Chezmoi,
+}
+
+/// The script regenerating the links once the config is in place. Links are
+/// not exported, as they contain machine specific paths.
+const BOOTSTRAP: &str = "#!/bin/sh\n# Regenerates the cmdlink links from the config.\nexec cmdlink refresh\n";
+
+/// Writes the config and a bootstrap script into `dir` in the given layout,
+/// returning the paths of the written files.
+///
+/// For stow, `stow -d <dir> -t ~ cmdlink` links the config into place, after
+/// which the linked `bootstrap.sh` regenerates the links. For chezmoi, the
+/// bootstrap is a `run_onchange_` script that `chezmoi apply` runs whenever the
+/// config changes.
+pub fn export(cfg: &Config, dir: &Path, layout: Layout) -> Result<Vec<PathBuf>> {
+	let home =
+		dirs::home_dir().ok_or_else(|| Error::Export(dir.to_path_buf(), "home directory not found".to_string()))?;
+	let project_dir = paths::get().root().strip_prefix(&home).map_err(|_| {
+		Error::Export(
+			dir.to_path_buf(),
+			format!(
+				"the project directory {} is not in your home directory",
+				paths::get().root().display()
+			),
+		)
+	})?;
+
+	let config = cfg.to_canonical()?;
+	let files = match layout {
+		Layout::Stow => {
+			let package = dir.join("cmdlink").join(project_dir);
+			vec![
+				(package.join("config.toml"), config),
+				(package.join("bootstrap.sh"), BOOTSTRAP.to_string()),
+			]
+		},
+		Layout::Chezmoi => {
+			let source = chezmoi_path(project_dir).join("config.toml");
+			let include = source.to_string_lossy().replace('\\', "/");
+			let script = BOOTSTRAP.replacen(
+				"\nexec",
+				&format!("\n# config.toml hash: {{{{ include \"{include}\" | sha256sum }}}}\nexec"),
+				1,
+			);
+			vec![
+				(dir.join(source), config),
+				(dir.join("run_onchange_after_cmdlink-refresh.sh.tmpl"), script),
+			]
+		},
+	};
+
+	for (path, contents) in &files {
+		let write = || {
+			std::fs::create_dir_all(path.parent().unwrap_or(dir))?;
+			std::fs::write(path, contents)
+		};
+		write().map_err(|e| Error::Export(path.clone(), e.to_string()))?;
+	}
+	#[cfg(target_family = "unix")]
+	if layout == Layout::Stow {
+		use std::os::unix::fs::PermissionsExt;
+
+		let bootstrap = &files[1].0;
+		std::fs::set_permissions(bootstrap, std::fs::Permissions::from_mode(0o755))
+			.map_err(|e| Error::Export(bootstrap.clone(), e.to_string()))?;
+	}
+	Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+/// The chezmoi source path for a path relative to the home directory, which
+/// spells leading dots of file names as `dot_`.
+fn chezmoi_path(path: &Path) -> PathBuf {
+	path.components()
+		.map(|component| match component {
+			Component::Normal(name) => {
+				let name = name.to_string_lossy();
+				match name.strip_prefix('.') {
+					Some(rest) => format!("dot_{rest}"),
+					None => name.into_owned(),
+				}
+			},
+			other => other.as_os_str().to_string_lossy().into_owned(),
+		})
+		.collect()
+}
@@ -1,27 +1,385 @@
+use std::path::PathBuf;
+
 use thiserror::Error;
 
+use crate::{i18n, settings::PathScope, tr};
+
 #[derive(Error, Debug)]
 /// Error container for all Cmdlink errors
 pub enum Error {
-	#[error("Failed to create project directory: {0}")]
-	ProjectDirCreation(#[source] std::io::Error),
-	#[error("Failed to read config file: {0}")]
-	ConfigRead(#[source] std::io::Error),
-	#[error("Error writing config data: {0}")]
-	ConfigWrite(#[source] std::io::Error),
-	#[error("Failed to parse config file: {0}")]
-	ConfigParse(#[from] toml::de::Error),
+	#[error("Failed to create project directory '{}': {}", .0.display(), .1)]
+	ProjectDirCreation(PathBuf, #[source] std::io::Error),
+	#[error("Failed to read config file '{}': {}", .0.display(), .1)]
+	ConfigRead(PathBuf, #[source] std::io::Error),
+	#[error("Failed to write config file '{}': {}", .0.display(), .1)]
+	ConfigWrite(PathBuf, #[source] std::io::Error),
+	#[error("Failed to parse config file '{}': {}", .0.display(), .1)]
+	ConfigParse(PathBuf, #[source] Box<toml::de::Error>),
+	#[error("Config file '{}' is not formatted", .0.display())]
+	ConfigUnformatted(PathBuf),
+	#[error("Unknown setting '{0}'")]
+	UnknownSetting(String),
+	#[error("Invalid value for setting '{0}': {1}")]
+	InvalidSetting(String, String),
 	#[error("Failed to serialize config data: {0}")]
 	ConfigSerialize(#[from] toml::ser::Error),
-	#[error("Failed to create link for alias '{0}': {1}")]
-	LinkCreation(String, #[source] std::io::Error),
+	#[error("Failed to serialize config data as JSON: {0}")]
+	JsonSerialize(#[from] serde_json::Error),
+	#[error("Failed to create link for alias '{}' at '{}': {}", .0, .1.display(), .2)]
+	LinkCreation(String, PathBuf, #[source] std::io::Error),
 	#[error("Alias '{0}' already exists")]
-	LinkAlreadyExists(String),
-	#[error("Failed to update link for alias '{0}': {1}")]
-	LinkUpdate(String, #[source] std::io::Error),
-	#[error("Failed to remove link for alias '{0}': {1}")]
-	LinkRemoval(String, #[source] std::io::Error),
+	LinkAlreadyExists(String, PathBuf),
+	#[error("Failed to update link for alias '{}' at '{}': {}", .0, .1.display(), .2)]
+	LinkUpdate(String, PathBuf, #[source] std::io::Error),
+	#[error("Failed to remove link for alias '{}' at '{}': {}", .0, .1.display(), .2)]
+	LinkRemoval(String, PathBuf, #[source] std::io::Error),
+	#[error("Failed to read bins directory '{}': {}", .0.display(), .1)]
+	BinsRead(PathBuf, #[source] std::io::Error),
+	#[error("Failed to remove orphaned file '{0}': {1}")]
+	OrphanRemoval(String, #[source] std::io::Error),
+	#[error("Failed to read link for alias '{}' at '{}': {}", .0, .1.display(), .2)]
+	LinkRead(String, PathBuf, #[source] std::io::Error),
+	#[error("{} link operations failed:\n{}", .0.len(), list_errors(.0))]
+	LinkBatch(Vec<Error>),
+	#[error("Invalid alias '{0}': {1}")]
+	InvalidAlias(String, &'static str),
+	#[error("Invalid environment variable name '{1}' for alias '{0}'")]
+	InvalidEnvKey(String, String),
+	#[error("No such subcommand '{0}', and no 'cmdlink-{0}' plugin was found on PATH")]
+	PluginNotFound(String),
+	#[error("Failed to run plugin '{0}': {1}")]
+	PluginSpawn(String, #[source] std::io::Error),
+	#[error("Failed to fetch pack '{0}': {1}")]
+	PackFetch(String, #[source] std::io::Error),
+	#[error("Failed to parse pack '{0}': {1}")]
+	PackParse(String, #[source] Box<toml::de::Error>),
+	#[error("Pack '{0}' is already installed, use `pack update` to update it")]
+	PackAlreadyInstalled(String),
+	#[error("Pack '{0}' is not installed")]
+	PackNotInstalled(String),
+	#[error("Pack '{0}' is not compatible: {1}")]
+	PackIncompatible(String, String),
+	#[error("Failed to write pack file '{0}': {1}")]
+	PackWrite(String, #[source] std::io::Error),
+	#[error("No aliases matched the selection for pack '{0}'")]
+	EmptyPack(String),
+	#[error("Undefined environment variable '{1}' referenced by alias '{0}'")]
+	UndefinedVariable(String, String),
+	#[error("Invalid secret reference '{0}', expected 'keyring:<service>/<account>'")]
+	InvalidSecretRef(String),
+	#[error("Failed to retrieve secret '{0}' from the OS keychain: {1}")]
+	SecretLookup(String, String),
+	#[error("Failed to locate the cmdlink executable: {0}")]
+	CurrentExe(#[source] std::io::Error),
+	#[error("Failed to watch for changes: {0}")]
+	Watch(#[from] notify::Error),
+	#[error("Failed to manage background service: {0}")]
+	Service(String),
+	#[error("Failed to update PATH: {0}")]
+	UserPath(String),
+	#[error("{0} needs user input, but cmdlink is running non-interactively: {1}")]
+	NonInteractive(&'static str, &'static str),
+	#[error("Alias '{0}' does not exist")]
+	UnknownAlias(String),
+	#[error("Failed to open '{0}': {1}")]
+	Open(String, String),
+	#[error("Failed to access the clipboard: {0}")]
+	Clipboard(String),
+	#[error("Failed to fetch release '{0}': {1}")]
+	Release(String, String),
+	#[error("{1}")]
+	ToolVersion(String, String),
+	#[error("Failed to check the tool version of alias '{0}' with `{1}`: {2}")]
+	ToolVersionUnknown(String, String, String),
+	#[error("Failed to relocate the bins directory to '{}': {}", .0.display(), .1)]
+	Relocate(PathBuf, String),
+	#[error("Failed to move the project directory to '{}': {}", .0.display(), .1)]
+	MigrateHome(PathBuf, String),
+	#[error("Failed to update the cmd.exe AutoRun value: {0}")]
+	AutoRun(String),
+	#[error("Failed to write the AutoRun script '{}': {}", .0.display(), .1)]
+	AutoRunScript(PathBuf, #[source] std::io::Error),
+	#[error("Failed to write the completion loader '{}': {}", .0.display(), .1)]
+	CompletionLoader(PathBuf, #[source] std::io::Error),
+	#[error("Failed to install the completion script '{}': {}", .0.display(), .1)]
+	CompletionInstall(PathBuf, #[source] std::io::Error),
+	#[error("Failed to detect your shell from $SHELL")]
+	ShellUnknown,
+	#[error("Failed to export to '{}': {}", .0.display(), .1)]
+	Export(PathBuf, String),
+	#[error("Permission denied updating {0}: {1}")]
+	PathAccessDenied(PathScope, String),
+	#[error("Failed to run alias '{0}': {1}")]
+	AliasRun(String, #[source] std::io::Error),
+	#[error("Failed to access run history '{}': {}", .0.display(), .1)]
+	RunHistory(PathBuf, #[source] std::io::Error),
+	#[error("Failed to remove '{}': {}", .0.display(), .1)]
+	Gc(PathBuf, #[source] std::io::Error),
+	#[error("No alias runs were recorded yet")]
+	NoRuns,
+	#[error("No commands were found in the shell history")]
+	EmptyHistory,
+	#[error("Aliases reference each other in a cycle: {}", .0.join(" -> "))]
+	AliasCycle(Vec<String>),
+	#[error("Alias '{0}' belongs to unknown group '{1}'")]
+	UnknownGroup(String, String),
+	#[error("Alias '{0}' differs from alias '{1}' only in case, so both would share one link file")]
+	AliasCaseCollision(String, String),
+	#[error("Failed to back up link '{}': {}", .0.display(), .1)]
+	LinkBackup(PathBuf, #[source] std::io::Error),
+	#[error("Link for alias '{}' at '{}' is longer than the 259 characters Windows can run", .0, .1.display())]
+	LinkPathTooLong(String, PathBuf),
+	#[error("Link for alias '{}' at '{}' is locked by another process", .0, .1.display())]
+	LinkLocked(String, PathBuf),
+}
+
+impl Error {
+	/// A stable, machine-readable code identifying the kind of error.
+	pub fn code(&self) -> &'static str {
+		match self {
+			Error::ProjectDirCreation(..) => "project_dir_creation",
+			Error::ConfigRead(..) => "config_read",
+			Error::ConfigWrite(..) => "config_write",
+			Error::ConfigParse(..) => "config_parse",
+			Error::ConfigUnformatted(_) => "config_unformatted",
+			Error::UnknownSetting(_) => "unknown_setting",
+			Error::InvalidSetting(..) => "invalid_setting",
+			Error::ConfigSerialize(_) => "config_serialize",
+			Error::JsonSerialize(_) => "json_serialize",
+			Error::LinkCreation(..) => "link_creation",
+			Error::LinkAlreadyExists(..) => "link_already_exists",
+			Error::LinkUpdate(..) => "link_update",
+			Error::LinkRemoval(..) => "link_removal",
+			Error::BinsRead(..) => "bins_read",
+			Error::OrphanRemoval(..) => "orphan_removal",
+			Error::LinkRead(..) => "link_read",
+			Error::LinkBatch(_) => "link_batch",
+			Error::InvalidAlias(..) => "invalid_alias",
+			Error::InvalidEnvKey(..) => "invalid_env_key",
+			Error::PluginNotFound(_) => "plugin_not_found",
+			Error::PluginSpawn(..) => "plugin_spawn",
+			Error::PackFetch(..) => "pack_fetch",
+			Error::PackParse(..) => "pack_parse",
+			Error::PackAlreadyInstalled(_) => "pack_already_installed",
+			Error::PackNotInstalled(_) => "pack_not_installed",
+			Error::PackIncompatible(..) => "pack_incompatible",
+			Error::PackWrite(..) => "pack_write",
+			Error::EmptyPack(_) => "empty_pack",
+			Error::UndefinedVariable(..) => "undefined_variable",
+			Error::InvalidSecretRef(_) => "invalid_secret_ref",
+			Error::SecretLookup(..) => "secret_lookup",
+			Error::CurrentExe(_) => "current_exe",
+			Error::Watch(_) => "watch",
+			Error::Service(_) => "service",
+			Error::UserPath(_) => "user_path",
+			Error::NonInteractive(..) => "non_interactive",
+			Error::UnknownAlias(_) => "unknown_alias",
+			Error::Open(..) => "open",
+			Error::Clipboard(_) => "clipboard",
+			Error::Release(..) => "release",
+			Error::ToolVersion(..) => "tool_version",
+			Error::ToolVersionUnknown(..) => "tool_version_unknown",
+			Error::Relocate(..) => "relocate",
+			Error::MigrateHome(..) => "migrate_home",
+			Error::AutoRun(_) => "autorun",
+			Error::AutoRunScript(..) => "autorun_script",
+			Error::CompletionLoader(..) => "completion_loader",
+			Error::CompletionInstall(..) => "completion_install",
+			Error::ShellUnknown => "shell_unknown",
+			Error::Export(..) => "export",
+			Error::PathAccessDenied(..) => "path_access_denied",
+			Error::AliasRun(..) => "alias_run",
+			Error::RunHistory(..) => "run_history",
+			Error::Gc(..) => "gc",
+			Error::NoRuns => "no_runs",
+			Error::EmptyHistory => "empty_history",
+			Error::AliasCycle(_) => "alias_cycle",
+			Error::UnknownGroup(..) => "unknown_group",
+			Error::AliasCaseCollision(..) => "alias_case_collision",
+			Error::LinkBackup(..) => "link_backup",
+			Error::LinkPathTooLong(..) => "link_path_too_long",
+			Error::LinkLocked(..) => "link_locked",
+		}
+	}
+
+	/// The alias the error relates to, if any.
+	pub fn alias(&self) -> Option<&str> {
+		match self {
+			Error::LinkCreation(alias, ..)
+			| Error::LinkAlreadyExists(alias, _)
+			| Error::LinkUpdate(alias, ..)
+			| Error::LinkRemoval(alias, ..)
+			| Error::LinkRead(alias, ..)
+			| Error::InvalidAlias(alias, _)
+			| Error::InvalidEnvKey(alias, _)
+			| Error::UndefinedVariable(alias, _)
+			| Error::UnknownAlias(alias)
+			| Error::AliasRun(alias, _)
+			| Error::UnknownGroup(alias, _)
+			| Error::AliasCaseCollision(alias, _)
+			| Error::LinkPathTooLong(alias, _)
+			| Error::LinkLocked(alias, _)
+			| Error::ToolVersion(alias, _)
+			| Error::ToolVersionUnknown(alias, ..) => Some(alias),
+			Error::AliasCycle(cycle) => cycle.first().map(String::as_str),
+			_ => None,
+		}
+	}
+
+	/// The file the error relates to, if any.
+	pub fn path(&self) -> Option<PathBuf> {
+		match self {
+			Error::ProjectDirCreation(path, _)
+			| Error::ConfigRead(path, _)
+			| Error::ConfigWrite(path, _)
+			| Error::ConfigParse(path, _)
+			| Error::ConfigUnformatted(path)
+			| Error::LinkCreation(_, path, _)
+			| Error::LinkUpdate(_, path, _)
+			| Error::LinkRemoval(_, path, _)
+			| Error::LinkRead(_, path, _)
+			| Error::BinsRead(path, _)
+			| Error::Relocate(path, _)
+			| Error::MigrateHome(path, _)
+			| Error::AutoRunScript(path, _)
+			| Error::CompletionLoader(path, _)
+			| Error::CompletionInstall(path, _)
+			| Error::Export(path, _)
+			| Error::RunHistory(path, _)
+			| Error::Gc(path, _)
+			| Error::LinkBackup(path, _)
+			| Error::LinkPathTooLong(_, path)
+			| Error::LinkAlreadyExists(_, path)
+			| Error::LinkLocked(_, path) => Some(path.clone()),
+			Error::OrphanRemoval(path, _) | Error::PackWrite(path, _) | Error::Open(path, _) => {
+				Some(PathBuf::from(path))
+			},
+			_ => None,
+		}
+	}
+
+	/// A suggested fix for the error, if there is one.
+	pub fn help(&self) -> Option<&'static str> {
+		Some(match self {
+			Error::ConfigRead(..) | Error::ConfigWrite(..) => "check that you have permission to access the config file",
+			Error::ConfigParse(..) => "fix the config file by hand, or through `cmdlink config edit`",
+			Error::ConfigUnformatted(_) => "run `cmdlink config fmt` to format it",
+			Error::UnknownSetting(_) => "run `cmdlink config get` to list the settings",
+			Error::LinkCreation(..)
+			| Error::LinkUpdate(..)
+			| Error::LinkRemoval(..)
+			| Error::LinkRead(..)
+			| Error::LinkBackup(..) => {
+				"check the permissions of the bins directory, then run `cmdlink refresh`"
+			},
+			Error::LinkPathTooLong(..) => {
+				"move the bins directory somewhere shorter with `cmdlink relocate <dir>`, or shorten the alias"
+			},
+			Error::LinkLocked(..) => {
+				"close programs running the alias, or exclude the bins directory from antivirus scans, then run `cmdlink refresh`"
+			},
+			Error::LinkAlreadyExists(..) => "pass --force to overwrite the existing alias",
+			Error::BinsRead(..) => "check that the bins directory exists and is readable",
+			Error::OrphanRemoval(..) | Error::Gc(..) => "check the permissions of the file, or remove it by hand",
+			Error::InvalidEnvKey(..) => {
+				"environment variable names may only contain letters, digits and underscores, and cannot start with a digit"
+			},
+			Error::ShellUnknown => "pass the shell with --shell",
+			Error::PluginNotFound(_) => "run `cmdlink --help` to list the built-in subcommands",
+			Error::PackAlreadyInstalled(_) => "run `cmdlink pack update <name>` to update it",
+			Error::PackNotInstalled(_) => "run `cmdlink pack list` to list the installed packs",
+			Error::EmptyPack(_) => "select aliases with --from-tag or --alias",
+			Error::UndefinedVariable(..) => {
+				"set the variable, or escape it as `$${VAR}` to have the shell expand it at run time"
+			},
+			Error::InvalidSecretRef(_) => "secret references are written as keyring:<service>/<account>",
+			Error::SecretLookup(..) => "check that the secret is stored in the OS keychain",
+			Error::UnknownAlias(_) => "run `cmdlink display` to list the aliases",
+			Error::Clipboard(_) => "on Linux, install wl-clipboard, xclip or xsel",
+			Error::Release(..) => {
+				"check that the release exists and curl is installed, or pick the asset with `asset` and its checksum with `sha256`"
+			},
+			Error::ToolVersion(..) => "install a version of the tool the alias supports, or change its `min_version`",
+			Error::ToolVersionUnknown(..) => {
+				"check that the tool is installed and on PATH, or fix the `cmd` of the alias' `min_version`"
+			},
+			Error::AliasCaseCollision(..) => {
+				"rename one of the aliases, as file names on Windows and macOS ignore case"
+			},
+			Error::UnknownGroup(..) => "define the group in a `[groups.<name>]` table of the config",
+			Error::AliasCycle(_) => "remove one of the `@` references, or escape it as `@@` to pass a literal `@`",
+			Error::EmptyHistory => "pass the command with --cmd instead",
+			Error::NoRuns => "enable recording with `cmdlink config set run_history true`, then run `cmdlink refresh`",
+			Error::PathAccessDenied(..) => {
+				"run cmdlink from an elevated prompt on Windows, or with sudo elsewhere, to change the machine PATH"
+			},
+			_ => return None,
+		})
+	}
+
+	/// Renders the error with its code, related alias and file, and suggested
+	/// fix, for display on the terminal.
+	pub fn diagnostic(&self) -> Diagnostic<'_> { Diagnostic(self) }
+
+	/// Renders the error as a JSON object with its code, message, related alias
+	/// and file, and suggested fix. Batches of errors list their errors under
+	/// `errors`.
+	pub fn to_json(&self) -> serde_json::Value {
+		let mut value = serde_json::json!({
+			"code": self.code(),
+			"message": self.to_string(),
+			"alias": self.alias(),
+			"path": self.path().map(|path| path.display().to_string()),
+			"help": self.help(),
+		});
+		if let Error::LinkBatch(errors) = self {
+			value["errors"] = errors.iter().map(Error::to_json).collect();
+		}
+		value
+	}
 }
 
+/// An [Error] rendered with its code, related alias and file, and suggested
+/// fix, see [Error::diagnostic].
+pub struct Diagnostic<'a>(&'a Error);
+
+impl std::fmt::Display for Diagnostic<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		let error = self.0;
+		let code = error.code();
+		let alias = error.alias();
+		let path = error.path();
+		// Translated messages may use the alias, path and English message.
+		let shown_path = path.as_ref().map(|path| path.display().to_string()).unwrap_or_default();
+		let translate = |message: &str| {
+			i18n::format(
+				message,
+				&[
+					("alias", &alias.unwrap_or_default()),
+					("path", &shown_path),
+					("message", &error),
+				],
+			)
+		};
+
+		let message = i18n::message(&format!("error.{code}")).map_or_else(|| error.to_string(), translate);
+		write!(f, "{}[{code}]: {message}", tr!("diagnostic.error"))?;
+		if let Some(alias) = alias {
+			write!(f, "\n  {}: {alias}", tr!("diagnostic.alias"))?;
+		}
+		if let Some(path) = &path {
+			write!(f, "\n  {}: {}", tr!("diagnostic.path"), path.display())?;
+		}
+		let help = i18n::message(&format!("help.{code}")).map(translate);
+		if let Some(help) = help.as_deref().or(error.help()) {
+			write!(f, "\n  {}: {help}", tr!("diagnostic.help"))?;
+		}
+		Ok(())
+	}
+}
+
+/// Formats a list of errors as an indented bullet list.
+fn list_errors(errors: &[Error]) -> String { errors.iter().map(|e| format!("  - {e}")).collect::<Vec<_>>().join("\n") }
+
 /// Cmdlink result type
 pub type Result<T> = std::result::Result<T, Error>;
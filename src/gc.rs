@@ -0,0 +1,223 @@
+//! Trimming the files cmdlink accumulates in the project directory over time,
+//! as done by `cmdlink gc`.
+//!
+//! Three kinds of files grow without bound: the run history, release binaries
+//! cached for aliases that no longer use them, and copies of link files left
+//! in the bins directory by a batch of link changes that was interrupted. The
+//! first two are trimmed to the `gc_keep` most recent entries, removing those
+//! beyond them once they're older than `gc_max_age`. Leftover link copies are
+//! always removed, unless a batch may still be using them. Releases in use by
+//! an alias are never removed.
+
+use std::{
+	path::{Path, PathBuf},
+	time::{Duration, SystemTime},
+};
+
+use crate::{error::Error, platform_binary, release::Release, runs, settings::Settings, Config, Result};
+
+/// The number of entries of each kind kept regardless of their age, unless the
+/// `gc_keep` setting says otherwise.
+pub const DEFAULT_KEEP: usize = 10;
+
+/// How old entries have to be to be removed, unless the `gc_max_age` setting
+/// says otherwise.
+pub const DEFAULT_MAX_AGE: Duration = Duration::from_secs(90 * 24 * 60 * 60);
+
+/// How long a link copy may belong to a batch of link changes that's still
+/// being applied by another cmdlink process.
+const LINK_COPY_GRACE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How much of the run history and the release cache is kept.
+pub struct Retention {
+	/// The number of most recent entries kept regardless of their age.
+	pub keep: usize,
+	/// How old entries beyond those have to be to be removed.
+	pub max_age: Duration,
+}
+
+impl Default for Retention {
+	fn default() -> Self {
+		Retention {
+			keep: DEFAULT_KEEP,
+			max_age: DEFAULT_MAX_AGE,
+		}
+	}
+}
+
+impl Retention {
+	/// The retention configured by the `gc_keep` and `gc_max_age` settings.
+	pub fn from_settings(settings: &Settings) -> Result<Self> {
+		let max_age = match &settings.gc_max_age {
+			Some(max_age) => humantime::parse_duration(max_age)
+				.map_err(|e| Error::InvalidSetting("gc_max_age".to_string(), e.to_string()))?,
+			None => DEFAULT_MAX_AGE,
+		};
+		Ok(Retention {
+			keep: settings.gc_keep.unwrap_or(DEFAULT_KEEP),
+			max_age,
+		})
+	}
+
+	/// The time entries have to be older than to be removed.
+	fn cutoff(&self, now: SystemTime) -> SystemTime { now.checked_sub(self.max_age).unwrap_or(SystemTime::UNIX_EPOCH) }
+
+	/// The entries to remove among the given ones, with the time each was last
+	/// changed: all but the `keep` most recent, if they're older than
+	/// `max_age`.
+	fn expired<T>(&self, mut entries: Vec<(T, SystemTime)>, now: SystemTime) -> Vec<T> {
+		entries.sort_by_key(|(_, modified)| std::cmp::Reverse(*modified));
+		let cutoff = self.cutoff(now);
+		entries
+			.into_iter()
+			.skip(self.keep)
+			.filter(|(_, modified)| *modified < cutoff)
+			.map(|(entry, _)| entry)
+			.collect()
+	}
+}
+
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+/// What [collect] removed, or would remove.
+pub struct Garbage {
+	/// The number of lines dropped from the run history.
+	pub runs: usize,
+	/// The directories of the cached releases removed.
+	pub releases: Vec<PathBuf>,
+	/// The leftover link copies removed from the bins directory.
+	pub link_copies: Vec<PathBuf>,
+}
+
+impl Garbage {
+	/// Whether or not there was nothing to remove.
+	pub fn is_empty(&self) -> bool { self.runs == 0 && self.releases.is_empty() && self.link_copies.is_empty() }
+}
+
+/// Removes what the retention doesn't keep, or only finds it if `dry_run` is
+/// set.
+pub fn collect(cfg: &Config, retention: Retention, dry_run: bool) -> Result<Garbage> {
+	let now = SystemTime::now();
+	let garbage = Garbage {
+		runs: runs::trim(cfg.paths(), retention.keep, retention.cutoff(now), dry_run)?,
+		releases: retention.expired(unused_releases(cfg), now),
+		link_copies: leftover_link_copies(cfg, now),
+	};
+	if dry_run {
+		return Ok(garbage);
+	}
+	for dir in &garbage.releases {
+		std::fs::remove_dir_all(dir).map_err(|e| Error::Gc(dir.clone(), e))?;
+		debug!("Removed cached release {}", dir.display());
+		// The owner and repository directories go once they're empty.
+		for parent in dir.ancestors().skip(1).take(2) {
+			if std::fs::remove_dir(parent).is_err() {
+				break;
+			}
+		}
+	}
+	for path in &garbage.link_copies {
+		std::fs::remove_file(path).map_err(|e| Error::Gc(path.clone(), e))?;
+		debug!("Removed leftover link copy {}", path.display());
+	}
+	Ok(garbage)
+}
+
+/// The cached releases no alias uses, with when they were downloaded.
+fn unused_releases(cfg: &Config) -> Vec<(PathBuf, SystemTime)> {
+	let used: Vec<PathBuf> = cfg
+		.aliases()
+		.filter_map(|(_, values)| Release::parse(values.release.as_deref()?))
+		.map(|release| release.cache_dir(cfg.paths()))
+		.collect();
+	subdirs(&cfg.paths().releases_dir())
+		.iter()
+		.flat_map(|owner| subdirs(owner))
+		.flat_map(|repo| subdirs(&repo))
+		.filter(|tag| !used.contains(tag))
+		.filter_map(|tag| Some((tag.clone(), modified(&tag)?)))
+		.collect()
+}
+
+/// The copies of link files left in the bins directory that no batch of link
+/// changes can still be using. The bins directory may be shared with other
+/// programs once moved with `relocate`, so it's left alone then.
+fn leftover_link_copies(cfg: &Config, now: SystemTime) -> Vec<PathBuf> {
+	if cfg.settings().bins_dir.is_some() {
+		return Vec::new();
+	}
+	let Ok(entries) = std::fs::read_dir(cfg.links().bins_dir()) else {
+		return Vec::new();
+	};
+	let mut copies: Vec<PathBuf> = entries
+		.filter_map(|entry| Some(entry.ok()?.path()))
+		.filter(|path| platform_binary::is_link_copy(path))
+		.filter(|path| modified(path).is_some_and(|modified| modified + LINK_COPY_GRACE < now))
+		.collect();
+	copies.sort();
+	copies
+}
+
+/// The directories within a directory, none if it can't be read.
+fn subdirs(dir: &Path) -> Vec<PathBuf> {
+	let Ok(entries) = std::fs::read_dir(dir) else {
+		return Vec::new();
+	};
+	entries
+		.filter_map(|entry| Some(entry.ok()?.path()))
+		.filter(|path| path.is_dir())
+		.collect()
+}
+
+/// When a file was last modified, without following symlinks.
+fn modified(path: &Path) -> Option<SystemTime> { std::fs::symlink_metadata(path).and_then(|m| m.modified()).ok() }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+	#[test]
+	fn expired_keeps_the_most_recent_entries() {
+		let now = SystemTime::UNIX_EPOCH + 1000 * DAY;
+		let retention = Retention {
+			keep: 2,
+			max_age: 90 * DAY,
+		};
+		let entries = vec![
+			("old", now - 400 * DAY),
+			("recent", now - 10 * DAY),
+			("older", now - 500 * DAY),
+			("oldest", now - 600 * DAY),
+			("fresh", now - DAY),
+		];
+		assert_eq!(retention.expired(entries.clone(), now), ["old", "older", "oldest"]);
+
+		let retention = Retention { keep: 4, ..retention };
+		assert_eq!(retention.expired(entries.clone(), now), ["oldest"]);
+
+		let retention = Retention {
+			keep: 0,
+			max_age: 450 * DAY,
+		};
+		assert_eq!(retention.expired(entries, now), ["older", "oldest"]);
+	}
+
+	#[test]
+	fn retention_reads_the_settings() {
+		assert_eq!(Retention::from_settings(&Settings::default()).unwrap(), Retention::default());
+		let settings = Settings {
+			gc_keep: Some(3),
+			gc_max_age: Some("2weeks".to_string()),
+			..Settings::default()
+		};
+		assert_eq!(
+			Retention::from_settings(&settings).unwrap(),
+			Retention {
+				keep: 3,
+				max_age: 14 * DAY
+			}
+		);
+	}
+}
@@ -0,0 +1,148 @@
+//! The dependency graph between aliases, as rendered by `cmdlink graph`.
+
+use std::{
+	collections::{BTreeMap, BTreeSet},
+	fmt::{self, Write},
+	path::Path,
+};
+
+use crate::Config;
+
+/// Which aliases invoke other aliases, and which programs are invoked by more
+/// than one alias.
+#[derive(Debug, Default)]
+pub struct Graph {
+	/// The aliases invoked by each alias that invokes any.
+	invokes: BTreeMap<String, BTreeSet<String>>,
+	/// The aliases invoking each program shared by more than one alias.
+	shared: BTreeMap<String, BTreeSet<String>>,
+}
+
+impl Graph {
+	/// Analyzes the aliases of the config. Aliases invoking themselves, such as
+	/// `ls = "ls --color"`, are not considered dependencies, and programs that
+	/// are aliases show up as dependencies rather than shared programs.
+	pub fn new(cfg: &Config) -> Self {
+		let mut graph = Graph::default();
+		for (alias, values) in cfg.aliases() {
+			for (other, _) in cfg.aliases().filter(|(other, _)| *other != alias) {
+				if values.invoking(other).is_some() {
+					graph
+						.invokes
+						.entry(alias.to_string())
+						.or_default()
+						.insert(other.to_string());
+				}
+			}
+			for program in programs(values.effective_cmd()) {
+				if cfg.alias(&program).is_none() {
+					graph.shared.entry(program).or_default().insert(alias.to_string());
+				}
+			}
+		}
+		graph.shared.retain(|_, aliases| aliases.len() > 1);
+		graph
+	}
+
+	/// Whether or not no alias invokes another and no program is shared.
+	pub fn is_empty(&self) -> bool { self.invokes.is_empty() && self.shared.is_empty() }
+
+	/// Renders the graph in the Graphviz DOT language. Programs are drawn as
+	/// boxes, linked to the aliases invoking them with dashed edges.
+	pub fn to_dot(&self) -> String {
+		let mut dot = String::from("digraph cmdlink {\n");
+		for (alias, invoked) in &self.invokes {
+			for other in invoked {
+				let _ = writeln!(dot, "\t{alias:?} -> {other:?};");
+			}
+		}
+		for (program, aliases) in &self.shared {
+			let _ = writeln!(dot, "\t\"program:{program}\" [label={program:?}, shape=box];");
+			for alias in aliases {
+				let _ = writeln!(dot, "\t{alias:?} -> \"program:{program}\" [style=dashed];");
+			}
+		}
+		dot.push('}');
+		dot
+	}
+
+	/// Marks the alias and all the aliases it invokes, directly or not, as
+	/// visited.
+	fn visit<'a>(&'a self, alias: &'a str, visited: &mut BTreeSet<&'a str>) {
+		if !visited.insert(alias) {
+			return;
+		}
+		for other in self.invokes.get(alias).into_iter().flatten() {
+			self.visit(other, visited);
+		}
+	}
+
+	/// Writes the aliases invoked by `alias` as an indented tree, stopping at
+	/// aliases already on the current path so that cycles terminate.
+	fn write_tree(&self, f: &mut fmt::Formatter<'_>, alias: &str, indent: &str, path: &mut Vec<String>) -> fmt::Result {
+		let Some(invoked) = self.invokes.get(alias) else {
+			return Ok(());
+		};
+		path.push(alias.to_string());
+		for (idx, other) in invoked.iter().enumerate() {
+			let last = idx == invoked.len() - 1;
+			let (branch, next_indent) = if last {
+				("└── ", "    ")
+			} else {
+				("├── ", "│   ")
+			};
+			if path.contains(other) {
+				writeln!(f, "{indent}{branch}{other} (cycle)")?;
+				continue;
+			}
+			writeln!(f, "{indent}{branch}{other}")?;
+			self.write_tree(f, other, &format!("{indent}{next_indent}"), path)?;
+		}
+		path.pop();
+		Ok(())
+	}
+}
+
+impl fmt::Display for Graph {
+	/// Renders the alias chains as trees rooted at the aliases no other alias
+	/// invokes, followed by the shared programs.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		// Aliases that no other alias invokes come first, and any aliases left
+		// unvisited afterwards are only part of cycles.
+		let invoked: BTreeSet<&String> = self.invokes.values().flatten().collect();
+		let (roots, rest): (Vec<&String>, Vec<&String>) =
+			self.invokes.keys().partition(|alias| !invoked.contains(alias));
+		let mut visited = BTreeSet::new();
+		for root in roots.into_iter().chain(rest) {
+			if visited.contains(root.as_str()) {
+				continue;
+			}
+			self.visit(root, &mut visited);
+			writeln!(f, "{root}")?;
+			self.write_tree(f, root, "", &mut Vec::new())?;
+		}
+
+		if !self.shared.is_empty() {
+			if !self.invokes.is_empty() {
+				writeln!(f)?;
+			}
+			writeln!(f, "Shared programs:")?;
+			for (program, aliases) in &self.shared {
+				writeln!(f, "  {program}: {}", aliases.iter().cloned().collect::<Vec<_>>().join(", "))?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// The programs a command runs, by file stem, taking the first word of each
+/// command in pipelines and command lists, e.g. `kubectl` and `grep` for
+/// `/usr/local/bin/kubectl get pods | grep web`.
+fn programs(cmd: &str) -> BTreeSet<String> {
+	cmd.split(['|', ';', '&'])
+		.filter_map(|command| {
+			let word = command.split_whitespace().next()?.trim_matches(['"', '\'', '(', ')']);
+			Some(Path::new(word).file_stem()?.to_str()?.to_string())
+		})
+		.collect()
+}
@@ -0,0 +1,251 @@
+//! Finding frequently typed commands in shell history files, which `cmdlink
+//! suggest` proposes as aliases, and recently typed ones for `cmdlink add
+//! --from-history`.
+
+use std::{
+	collections::HashMap,
+	path::{Path, PathBuf},
+};
+
+/// The history file formats of the supported shells.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shell {
+	/// One command per line, optionally preceded by `#<timestamp>` lines.
+	Bash,
+	/// Like bash, with an optional `: <start>:<elapsed>;` prefix per command.
+	Zsh,
+	/// YAML-like entries with the command on a `- cmd: ` line.
+	Fish,
+	/// One command per line, as written by PSReadLine.
+	PowerShell,
+}
+
+impl Shell {
+	/// Guesses the format of a history file from its name, falling back to the
+	/// plain bash format.
+	pub fn of(path: &Path) -> Shell {
+		let name = path.file_name().and_then(|name| name.to_str()).unwrap_or_default();
+		if name.contains("zsh") || name == ".zhistory" {
+			Shell::Zsh
+		} else if name == "fish_history" {
+			Shell::Fish
+		} else if name == "ConsoleHost_history.txt" {
+			Shell::PowerShell
+		} else {
+			Shell::Bash
+		}
+	}
+}
+
+/// A command that was typed repeatedly.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frequent {
+	/// The command, with runs of whitespace collapsed.
+	pub cmd: String,
+	/// How many times the command was typed.
+	pub count: usize,
+}
+
+/// The history files of the supported shells that exist for the current user,
+/// including the file named by `HISTFILE`.
+pub fn files() -> Vec<PathBuf> {
+	let mut files: Vec<PathBuf> = std::env::var_os("HISTFILE").map(PathBuf::from).into_iter().collect();
+	if let Some(home) = dirs::home_dir() {
+		let zdotdir = std::env::var_os("ZDOTDIR").map_or_else(|| home.clone(), PathBuf::from);
+		let data_dir = std::env::var_os("XDG_DATA_HOME").map_or_else(|| home.join(".local/share"), PathBuf::from);
+		files.extend([
+			home.join(".bash_history"),
+			zdotdir.join(".zsh_history"),
+			zdotdir.join(".zhistory"),
+			data_dir.join("fish").join("fish_history"),
+		]);
+	}
+	if let Some(app_data) = dirs::data_dir().filter(|_| cfg!(target_os = "windows")) {
+		files.push(app_data.join(r"Microsoft\Windows\PowerShell\PSReadLine\ConsoleHost_history.txt"));
+	}
+	files.sort();
+	files.dedup();
+	files.retain(|file| file.is_file());
+	files
+}
+
+/// Reads the commands recorded in all the history files, oldest first as far
+/// as it can be told: the files are read in order of modification, as their
+/// entries aren't reliably timestamped.
+pub fn read_all(files: &[PathBuf]) -> Vec<String> {
+	let mut files = files.to_vec();
+	files.sort_by_key(|file| std::fs::metadata(file).and_then(|meta| meta.modified()).ok());
+	files.iter().flat_map(|file| read(file)).collect()
+}
+
+/// Reads the commands recorded in a history file, oldest first. Files that
+/// can't be read yield no commands.
+pub fn read(path: &Path) -> Vec<String> {
+	match std::fs::read(path) {
+		Ok(bytes) => parse(&String::from_utf8_lossy(&bytes), Shell::of(path)),
+		Err(e) => {
+			debug!("Skipping history file {}: {}", path.display(), e);
+			Vec::new()
+		},
+	}
+}
+
+/// Parses the contents of a history file in the given format.
+pub fn parse(contents: &str, shell: Shell) -> Vec<String> {
+	if shell == Shell::Fish {
+		return contents
+			.lines()
+			.filter_map(|line| line.strip_prefix("- cmd: "))
+			.map(str::to_string)
+			.collect();
+	}
+
+	let mut commands = Vec::new();
+	let mut pending = String::new();
+	for line in contents.lines() {
+		let is_timestamp = line.len() > 1 && line.starts_with('#') && line[1..].bytes().all(|b| b.is_ascii_digit());
+		if shell == Shell::Bash && is_timestamp {
+			continue;
+		}
+		let line = if pending.is_empty() {
+			strip_zsh_metadata(line)
+		} else {
+			line
+		};
+
+		// Commands spanning several lines end each but the last in a backslash.
+		match line.strip_suffix('\\') {
+			Some(part) => {
+				pending.push_str(part);
+				pending.push('\n');
+			},
+			None => {
+				pending.push_str(line);
+				commands.push(std::mem::take(&mut pending));
+			},
+		}
+	}
+	commands
+}
+
+/// Strips the `: <start>:<elapsed>;` prefix zsh writes with the
+/// `EXTENDED_HISTORY` option.
+fn strip_zsh_metadata(line: &str) -> &str {
+	let Some((metadata, cmd)) = line.strip_prefix(": ").and_then(|rest| rest.split_once(';')) else {
+		return line;
+	};
+	let is_metadata = metadata
+		.split_once(':')
+		.is_some_and(|(start, elapsed)| [start, elapsed].iter().all(|n| n.bytes().all(|b| b.is_ascii_digit())));
+	if is_metadata {
+		cmd
+	} else {
+		line
+	}
+}
+
+/// Counts how often each command was typed, returning the commands typed at
+/// least `min_count` times that are at least `min_len` characters long, most
+/// frequent first.
+///
+/// Runs of whitespace are collapsed, so `git  status` and `git status` count as
+/// the same command. Multi-line commands are left out, as they rarely make for
+/// good aliases.
+pub fn frequent(commands: impl IntoIterator<Item = String>, min_len: usize, min_count: usize) -> Vec<Frequent> {
+	let mut counts: HashMap<String, usize> = HashMap::new();
+	for cmd in commands.into_iter().filter(|cmd| !cmd.contains('\n')) {
+		let cmd = cmd.split_whitespace().collect::<Vec<_>>().join(" ");
+		if cmd.chars().count() >= min_len {
+			*counts.entry(cmd).or_default() += 1;
+		}
+	}
+
+	let mut frequent: Vec<Frequent> = counts
+		.into_iter()
+		.filter(|(_, count)| *count >= min_count)
+		.map(|(cmd, count)| Frequent { cmd, count })
+		.collect();
+	frequent.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.cmd.cmp(&b.cmd)));
+	frequent
+}
+
+/// The last `limit` distinct commands, newest first. Multi-line commands and
+/// invocations of cmdlink itself, such as the `cmdlink add` being run, are left
+/// out.
+pub fn recent(commands: Vec<String>, limit: usize) -> Vec<String> {
+	let mut recent: Vec<String> = Vec::new();
+	for cmd in commands.into_iter().rev() {
+		let cmd = cmd.trim();
+		let is_cmdlink = cmd.split_whitespace().next() == Some("cmdlink");
+		if cmd.is_empty() || cmd.contains('\n') || is_cmdlink || recent.iter().any(|seen| seen == cmd) {
+			continue;
+		}
+		recent.push(cmd.to_string());
+		if recent.len() == limit {
+			break;
+		}
+	}
+	recent
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn shells_are_told_apart_by_file_name() {
+		assert_eq!(Shell::of(Path::new("/home/me/.zsh_history")), Shell::Zsh);
+		assert_eq!(Shell::of(Path::new("/home/me/.zhistory")), Shell::Zsh);
+		assert_eq!(Shell::of(Path::new("/home/me/.local/share/fish/fish_history")), Shell::Fish);
+		assert_eq!(Shell::of(Path::new("ConsoleHost_history.txt")), Shell::PowerShell);
+		assert_eq!(Shell::of(Path::new("/home/me/.bash_history")), Shell::Bash);
+	}
+
+	#[test]
+	fn bash_history_skips_timestamps() {
+		let contents = "#1700000000\ngit status\n#1700000001\ndocker ps \\\n  -a\n";
+		assert_eq!(parse(contents, Shell::Bash), ["git status", "docker ps \n  -a"]);
+	}
+
+	#[test]
+	fn zsh_history_strips_extended_metadata() {
+		let contents = ": 1700000000:0;git status\n: 1700000001:12;make \\\n: not metadata\nls\n: a:b;echo\n";
+		assert_eq!(
+			parse(contents, Shell::Zsh),
+			["git status", "make \n: not metadata", "ls", ": a:b;echo"]
+		);
+	}
+
+	#[test]
+	fn fish_history_reads_the_cmd_entries() {
+		let contents = "- cmd: git status\n  when: 1700000000\n- cmd: ls -la\n  when: 1700000001\n";
+		assert_eq!(parse(contents, Shell::Fish), ["git status", "ls -la"]);
+	}
+
+	#[test]
+	fn frequent_commands_are_counted_most_frequent_first() {
+		let commands = [
+			"git status",
+			"git  status",
+			"ls",
+			"cargo build",
+			"cargo build",
+			"git status",
+			"make \\\nall",
+			"ls",
+			"ls",
+		]
+		.map(String::from);
+		let frequent = frequent(commands, 3, 2);
+		let frequent: Vec<(&str, usize)> = frequent.iter().map(|f| (f.cmd.as_str(), f.count)).collect();
+		assert_eq!(frequent, [("git status", 3), ("cargo build", 2)]);
+	}
+
+	#[test]
+	fn recent_commands_are_distinct_and_newest_first() {
+		let commands =
+			["ls", "git status", "cmdlink add gs", "make \\\nall", "ls", "  ", "cargo test"].map(String::from);
+		assert_eq!(recent(commands.to_vec(), 10), ["cargo test", "ls", "git status"]);
+		assert_eq!(recent(commands.to_vec(), 2), ["cargo test", "ls"]);
+	}
+}
@@ -0,0 +1,115 @@
+use std::{path::Path, process::Command};
+
+use serde::{Deserialize, Serialize};
+
+use crate::tr;
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+/// Shell commands executed by cmdlink after successful mutating operations,
+/// configured through the `[hooks]` table.
+pub struct Hooks {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Runs after an alias is added or updated.
+	pub post_add: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Runs after an alias is removed.
+	pub post_remove: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Runs after the links are refreshed.
+	pub post_refresh: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A shell snippet baked into every link, run before the alias' command,
+	/// e.g. to source a company wrapper.
+	pub pre_exec: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A shell snippet baked into every link, run after the alias' command
+	/// with its exit code in `CMDLINK_STATUS`, e.g. to record audit info.
+	pub post_exec: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The kind of operation that triggered a hook.
+pub enum HookKind {
+	PostAdd,
+	PostRemove,
+	PostRefresh,
+}
+
+impl HookKind {
+	/// The name of the hook, as used in the config file.
+	pub fn name(&self) -> &'static str {
+		match self {
+			HookKind::PostAdd => "post_add",
+			HookKind::PostRemove => "post_remove",
+			HookKind::PostRefresh => "post_refresh",
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+/// A hook queued to run once the config has been saved.
+pub(crate) struct HookEvent {
+	pub kind: HookKind,
+	/// The alias affected by the operation, if any.
+	pub alias: Option<String>,
+}
+
+impl Hooks {
+	/// Whether or not no hooks are configured.
+	pub fn is_empty(&self) -> bool {
+		self.post_add.is_none()
+			&& self.post_remove.is_none()
+			&& self.post_refresh.is_none()
+			&& self.pre_exec.is_none()
+			&& self.post_exec.is_none()
+	}
+
+	/// Returns the command configured for the given hook kind.
+	pub fn command(&self, kind: HookKind) -> Option<&str> {
+		match kind {
+			HookKind::PostAdd => self.post_add.as_deref(),
+			HookKind::PostRemove => self.post_remove.as_deref(),
+			HookKind::PostRefresh => self.post_refresh.as_deref(),
+		}
+	}
+
+	/// Runs the hook for the given event, if configured, for the config in the
+	/// given project directory.
+	///
+	/// Hooks run after the operation has already succeeded, so failures are
+	/// logged rather than returned.
+	pub(crate) fn run(&self, event: &HookEvent, root: &Path) {
+		let Some(cmd) = self.command(event.kind) else {
+			return;
+		};
+		debug!("Running {} hook: {}", event.kind.name(), cmd);
+
+		let status = shell_command(cmd)
+			.env("CMDLINK_HOOK", event.kind.name())
+			.env("CMDLINK_ALIAS", event.alias.as_deref().unwrap_or_default())
+			.env("CMDLINK_HOME", root)
+			.status();
+		match status {
+			Ok(status) if status.success() => {},
+			Ok(status) => warn!("{}", tr!("hook.exited", hook = event.kind.name(), status = status)),
+			Err(why) => warn!("{}", tr!("hook.failed", hook = event.kind.name(), error = why)),
+		}
+	}
+}
+
+/// Creates a command that runs the given command line through the platform
+/// shell.
+pub(crate) fn shell_command(cmd: &str) -> Command {
+	#[cfg(target_os = "windows")]
+	{
+		let mut command = Command::new("cmd");
+		command.arg("/C").arg(cmd);
+		command
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		let mut command = Command::new("sh");
+		command.arg("-c").arg(cmd);
+		command
+	}
+}
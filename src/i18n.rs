@@ -0,0 +1,114 @@
+//! Translations of the messages printed by cmdlink.
+//!
+//! Messages are looked up by id, such as `remove.missing`, through the [tr]
+//! macro. The built-in English messages live in `locales/en.toml` of the
+//! cmdlink sources, and can be overridden by catalogs in the `locales`
+//! directory of the project directory, named after the locale they translate,
+//! e.g. `de.toml` or `pt_BR.toml`. The locale is taken from the `locale`
+//! setting, or the `LC_ALL`, `LC_MESSAGES` and `LANG` environment variables.
+//!
+//! A `pt_BR` locale reads `pt_BR.toml` over `pt.toml` over the English
+//! messages, so catalogs only need to translate the messages they care about.
+//!
+//! [tr]: crate::tr
+
+use std::{collections::HashMap, fmt::Display, path::PathBuf, sync::LazyLock};
+
+use crate::{config, paths};
+
+/// The name of the directory catalogs are read from, in the project directory.
+pub const LOCALES_DIR: &str = "locales";
+
+/// The built-in English catalog.
+const ENGLISH: &str = include_str!("../locales/en.toml");
+
+/// The messages of the current locale, keyed by id.
+static CATALOG: LazyLock<HashMap<String, String>> = LazyLock::new(|| {
+	let mut messages = parse(ENGLISH).unwrap_or_default();
+	for name in locale().map(|locale| catalog_names(&locale)).unwrap_or_default() {
+		let path = locales_dir().join(format!("{name}.toml"));
+		let Ok(contents) = std::fs::read_to_string(&path) else {
+			continue;
+		};
+		match parse(&contents) {
+			Ok(catalog) => messages.extend(catalog),
+			Err(why) => warn!("Ignoring {}: {}", path.display(), why),
+		}
+	}
+	messages
+});
+
+/// Translates a message by id, replacing `{name}` placeholders with the given
+/// values.
+///
+/// ```
+/// let message = cmdlink::tr!("remove.missing", alias = "gs");
+/// assert_eq!(message, "Alias \"gs\" doesn't exist, nothing to remove");
+/// ```
+#[macro_export]
+macro_rules! tr {
+	($id:expr $(, $name:ident = $value:expr)* $(,)?) => {
+		$crate::i18n::translate($id, &[$((stringify!($name), &$value as &dyn ::std::fmt::Display)),*])
+	};
+}
+
+/// The directory catalogs are read from.
+pub fn locales_dir() -> PathBuf { paths::get().root().join(LOCALES_DIR) }
+
+/// The locale messages are shown in, e.g. `pt_BR`, or `None` for English.
+pub fn locale() -> Option<String> {
+	let locale = config::file_settings(paths::get()).locale.or_else(|| {
+		["LC_ALL", "LC_MESSAGES", "LANG"]
+			.into_iter()
+			.find_map(|var| std::env::var(var).ok().filter(|value| !value.is_empty()))
+	})?;
+	// Drop the encoding and modifier, e.g. `.UTF-8` and `@euro`.
+	let locale = locale.split(['.', '@']).next().unwrap_or_default().replace('-', "_");
+	(!locale.is_empty() && locale != "C" && locale != "POSIX").then_some(locale)
+}
+
+/// The message with the given id in the current locale, if there is one.
+pub fn message(id: &str) -> Option<&'static str> { CATALOG.get(id).map(String::as_str) }
+
+/// Translates a message by id, see [tr](crate::tr). Unknown ids are returned
+/// as is.
+pub fn translate(id: &str, args: &[(&str, &dyn Display)]) -> String { format(message(id).unwrap_or(id), args) }
+
+/// Replaces the `{name}` placeholders of a message with the given values.
+pub fn format(message: &str, args: &[(&str, &dyn Display)]) -> String {
+	args.iter().fold(message.to_string(), |message, (name, value)| {
+		message.replace(&format!("{{{name}}}"), &value.to_string())
+	})
+}
+
+/// The catalogs read for a locale, from the least to the most specific.
+fn catalog_names(locale: &str) -> Vec<String> {
+	match locale.split_once('_') {
+		Some((language, _)) => vec![language.to_string(), locale.to_string()],
+		None => vec![locale.to_string()],
+	}
+}
+
+/// Parses a catalog, flattening its tables into dotted ids.
+fn parse(contents: &str) -> Result<HashMap<String, String>, toml::de::Error> {
+	fn flatten(prefix: &str, table: toml::Table, messages: &mut HashMap<String, String>) {
+		for (key, value) in table {
+			let id = if prefix.is_empty() {
+				key
+			} else {
+				format!("{prefix}.{key}")
+			};
+			match value {
+				toml::Value::String(message) => {
+					messages.insert(id, message);
+				},
+				toml::Value::Table(table) => flatten(&id, table, messages),
+				_ => {},
+			}
+		}
+	}
+
+	let mut messages = HashMap::new();
+	flatten("", contents.parse()?, &mut messages);
+	Ok(messages)
+}
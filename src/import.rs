@@ -0,0 +1,80 @@
+//! Resolving conflicts between imported aliases and existing ones.
+
+use std::fmt;
+
+use crate::{alias::UpsertOutcome, tr};
+
+/// How an imported alias that collides with an existing alias is handled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resolution {
+	/// Keeps the existing alias and drops the imported one.
+	Skip,
+	/// Replaces the existing alias with the imported one.
+	Overwrite,
+	/// Imports the alias under the first free name with a numeric suffix, e.g.
+	/// `gs-2`.
+	Rename,
+}
+
+/// What an import did with each of the aliases it was given.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportReport {
+	/// Aliases that didn't exist before.
+	pub created: Vec<String>,
+	/// Existing aliases that were replaced.
+	pub overwritten: Vec<String>,
+	/// Existing aliases that already had the imported definition.
+	pub unchanged: Vec<String>,
+	/// Imported aliases that were dropped in favor of existing ones.
+	pub skipped: Vec<String>,
+	/// Imported aliases that were renamed, as `(original, new)` pairs.
+	pub renamed: Vec<(String, String)>,
+}
+
+impl ImportReport {
+	/// Records the outcome of upserting an imported alias.
+	pub(crate) fn record(&mut self, name: String, outcome: UpsertOutcome) {
+		match outcome {
+			UpsertOutcome::Created => self.created.push(name),
+			UpsertOutcome::Updated => self.overwritten.push(name),
+			UpsertOutcome::Unchanged => self.unchanged.push(name),
+		}
+	}
+
+	/// The sorted names of all the aliases that are now defined by the import,
+	/// including renamed ones under their new names.
+	pub fn imported(&self) -> Vec<String> {
+		let mut names: Vec<String> = self
+			.created
+			.iter()
+			.chain(&self.overwritten)
+			.chain(&self.unchanged)
+			.chain(self.renamed.iter().map(|(_, new)| new))
+			.cloned()
+			.collect();
+		names.sort();
+		names
+	}
+}
+
+impl fmt::Display for ImportReport {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let mut line = |label: &str, names: Vec<String>| {
+			if names.is_empty() {
+				return Ok(());
+			}
+			writeln!(f, "{label} ({}): {}", names.len(), names.join(", "))
+		};
+		line(&tr!("import.created"), self.created.clone())?;
+		line(&tr!("import.overwritten"), self.overwritten.clone())?;
+		line(&tr!("import.unchanged"), self.unchanged.clone())?;
+		line(&tr!("import.skipped"), self.skipped.clone())?;
+		line(
+			&tr!("import.renamed"),
+			self.renamed
+				.iter()
+				.map(|(old, new)| format!("{old} -> {new}"))
+				.collect(),
+		)
+	}
+}
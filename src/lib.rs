@@ -0,0 +1,1244 @@
+//! # cmdlink
+//!
+//! `cmdlink` is a cross-platform command-line application built with Rust that
+//! allows you to create and manage aliases for system commands. These aliases
+//! are saved in a configuration file and linked to scripts in the `bins`
+//! directory. This tool is designed to streamline command management and
+//! improve command-line efficiency.
+//!
+//!
+//! ## Features
+//!
+//! - **Add Aliases**: Create new command aliases with custom descriptions.
+//! - **Remove Aliases**: Easily delete aliases and their associated binaries.
+//! - **Display Aliases**: View all active aliases and their descriptions.
+//!
+//! ## Installation
+//!
+//! 1. Clone the repository: ```bash git clone https://github.com/ehuff700/cmdlink.git
+//!    cd cmdlink ```
+//! 2. Build the application using Cargo. During the build process, cmdlink will
+//!    setup all necessary directories: ```bash cargo build --release ```
+//! 3. Run the application by navigating to the build directory: ```bash
+//!    ./target/release/cmdlink ```
+//!
+//! Or, optionally, if you prefer not to build from source:
+//! ```bash
+//! cargo install cmdlink
+//! ```
+//!
+//! Then run `cmdlink init` to set up the project directory and add the bins
+//! directory to your PATH. On Windows the user `Path` is edited in the registry
+//! directly, without an elevation prompt, and on other platforms an `export`
+//! line is added to `~/.profile`. You are asked before your PATH is changed,
+//! pass `--path` or `--no-path` to decide up front.
+//!
+//! On shared machines, such as lab computers, `cmdlink init --machine` adds the
+//! bins directory to the PATH of every user instead: the machine `Path` under
+//! `HKLM` on Windows, which needs an elevated prompt, or
+//! `/etc/profile.d/cmdlink.sh` elsewhere, which needs root. Setting
+//! `path_scope = "machine"` makes `relocate` update the machine PATH as well.
+//!
+//! ```bash
+//! cmdlink init
+//! ```
+//!
+//! To keep cmdlink self-contained, e.g. on a USB stick or in a per-toolchain
+//! folder, run `cmdlink init --portable`. This creates a `cmdlink.portable`
+//! marker file next to the executable, and from then on the config and bins
+//! directory are kept next to the executable instead of in `~/.cmdlink`.
+//! Placing the marker file by hand works too.
+//!
+//! For provisioning scripts and CI images, pass `--non-interactive` or set
+//! `CMDLINK_NONINTERACTIVE=1`. cmdlink then never waits for input, and fails
+//! with an error naming the flag to use instead whenever it would otherwise
+//! ask, e.g. `cmdlink init --path`.
+//!
+//! ## Usage
+//!
+//! Run cmdlink with the following commands to manage your aliases:
+//!
+//! ### **Add a New Alias**
+//!
+//! ```bash
+//! cmdlink add <alias> -c <command> [--desc <description>] [--tag <tag>...] [--env <KEY=VALUE>...] [--force]
+//! ```
+//! Arguments:
+//!
+//! ```text
+//! <alias>: The name of the alias you wish to create.
+//! --cmd <command>: The command that the alias will execute.
+//! --from-clipboard: Takes the command from the clipboard instead of --cmd, trimmed of surrounding whitespace.
+//! --from-history[=N]: Picks the command from the last N (default 20) distinct commands of your shell history instead of --cmd.
+//! --url <url>: Opens a web page in the browser instead of running a command, see the Configuration section.
+//! --dir <path>: Changes the shell to a directory instead of running a command, see the Configuration section.
+//! --activate <command> (optional): A command run in the shell after changing to --dir.
+//! --release <owner/repo@tag>: Runs the binary of a pinned GitHub release instead of a command, see the Configuration section.
+//! --asset <pattern> (optional): The release asset to download, with `*` wildcards.
+//! --bin <name> (optional): The name of the binary in the release asset.
+//! --sha256 <hex> (optional): The checksum to verify the release asset against.
+//! --desc <description> (optional): A short description of the alias.
+//! --tag <tag> (optional, repeatable): A tag used to group the alias.
+//! --env <KEY=VALUE> (optional, repeatable): An environment variable exported before the command runs.
+//! --secret <KEY=keyring:service/account> (optional, repeatable): An environment variable retrieved from the OS keychain at run time.
+//! --env-file <path> (optional): A file of KEY=VALUE lines loaded each time the alias is invoked.
+//! --capture <path> (optional): A file the output of the alias is appended to each time it runs.
+//! --group <name> (optional): A group from the config to inherit defaults from.
+//! --cwd <path> (optional): The directory the command runs in.
+//! --shell <shell> (optional): The shell the command runs through on Unix, such as bash.
+//! --min-version <requirement> (optional): The versions of the tool the command works with, such as >=20, checked before it runs.
+//! --version-cmd <command> (optional): The command printing the version of the tool, `<program> --version` by default.
+//! --no-forward-args (optional): Runs the command without the arguments the alias is invoked with.
+//! --auto-desc (optional): Uses the first summary line of the command's --help output as the description.
+//! --force (optional): Overwrites the alias if it already exists. Without it, adding an existing alias fails and leaves the config untouched.
+//! ```
+//!
+//! Names differing from an existing alias only in case, such as `Gst` and
+//! `gst`, are rejected on Windows and macOS, where both would share one link
+//! file, and cause a warning elsewhere.
+//!
+//! Example:
+//!
+//! ```bash
+//! cmdlink add myalias --cmd "echo Hello, World!" --desc "Prints a greeting" --force
+//! ```
+//!
+//! ### **Remove an Alias**
+//!
+//! Removes an existing alias and its binary link. Removing an alias that
+//! doesn't exist fails, unless `--ignore-missing` is passed, e.g. in
+//! provisioning scripts that should be safe to run again.
+//!
+//! ```bash
+//! cmdlink remove <alias> [--ignore-missing]
+//! ```
+//!
+//! Arguments:
+//!
+//! ```text
+//! <alias>: The name of the alias you wish to remove.
+//! --ignore-missing (optional): Succeeds without doing anything if the alias doesn't exist.
+//! ```
+//!
+//! Example:
+//!
+//! ```text
+//! cmdlink remove myalias
+//! ```
+//!
+//! ### Display All Aliases
+//!
+//! Displays all existing aliases along with their link status and
+//! descriptions, followed by a summary of how many aliases are broken, have a
+//! missing target, or are disabled. The Status column shows:
+//!
+//! - `✔` ok: the link exists and the program it runs was found
+//! - `✘` broken: the link is missing; run `cmdlink refresh` to recreate it
+//! - `!` target not found: the program of the command isn't on PATH or at its
+//!   path, or the directory of a `dir` alias or the cached binary of a
+//!   `release` is gone
+//! - `–` disabled: no link is expected
+//!
+//! Shell builtins, programs given by a relative path and commands run through
+//! another `shell` can't be checked, so they count as found.
+//!
+//! ```text
+//! cmdlink display [--long | --tree] [--full] [--broken]
+//! ```
+//!
+//! Pass `--long` to show the full command, tags, link status spelled out and
+//! when each alias was created and last updated.
+//!
+//! Pass `--broken` to list only the aliases that are broken or whose target
+//! wasn't found, i.e. what `cmdlink refresh` or a cleanup should address. It
+//! combines with `--long` and `--tree`.
+//!
+//! Columns are sized by how wide text is drawn in the terminal, so descriptions
+//! with CJK characters or emoji, including joined sequences such as 👨‍👩‍👧, stay
+//! aligned.
+//!
+//! On a terminal, the widest columns, usually the command, are truncated with
+//! `…` so that the table fits its width. Pass `--full` to wrap them across
+//! lines within their cells instead, showing the complete text. Output that is
+//! piped or redirected is never truncated.
+//!
+//! Pass `--tree` to group the aliases by the pack they were installed from and
+//! then by tag, which keeps large configs navigable. Aliases with several tags
+//! are listed under each of them:
+//!
+//! ```text
+//! git
+//! ├── gl - Short git log
+//! └── gs - Short git status
+//! (untagged)
+//! └── ll
+//! ```
+//!
+//! ### Show an Alias
+//!
+//! Prints the description, command, long description, examples, tags and link
+//! status of a single alias.
+//!
+//! ```text
+//! cmdlink show gs
+//! ```
+//!
+//! ### Copy a Command
+//!
+//! Copies the command of an alias to the clipboard, e.g. to paste it into a
+//! script or ticket. This uses `clip` on Windows, `pbcopy` on macOS, and
+//! `wl-copy`, `xclip` or `xsel` elsewhere, whichever is installed.
+//!
+//! ```text
+//! cmdlink copy-cmd gs
+//! ```
+//!
+//! ### Test an Alias
+//!
+//! Prints the link of an alias, then runs it with tracing turned on (`sh -x`,
+//! or `echo on` for cmd.exe) and the given sample arguments, showing exactly
+//! which command and arguments the target receives. This catches quoting bugs
+//! before they bite in a real shell. The alias' command really runs, and
+//! cmdlink exits with its exit code:
+//!
+//! ```text
+//! $ cmdlink test greet "a b"
+//! /home/me/.cmdlink/bins/greet:
+//! #!/bin/sh
+//! exec printf '<%s>\n' "$@"
+//!
+//! Running greet with tracing:
+//! + exec printf <%s>\n a b
+//! <a b>
+//! ```
+//!
+//! ### Run an Alias in a Sandbox
+//!
+//! Runs an alias through cmdlink, passing the remaining arguments on. With
+//! `--sandbox`, the alias runs with a minimal PATH of the system directories
+//! and without the inherited environment, except for a small allowlist such as
+//! `HOME`, `LANG` and `TERM` (`SYSTEMROOT`, `TEMP` and friends on Windows).
+//! This helps when trying out aliases from imported packs or remote configs:
+//!
+//! ```text
+//! cmdlink run --sandbox [--allow-env <VAR>]... [--temp-dir] <alias> [args]...
+//! ```
+//!
+//! `--allow-env` keeps another variable, and can be repeated. `--allow-env
+//! PATH` keeps the full PATH. `--temp-dir` runs the alias in a new temporary
+//! directory, removed afterwards. Variables set by the alias itself through
+//! `env` and `secrets` are still exported. A sandbox limits what the alias
+//! sees, but is no security boundary: the alias still runs as you.
+//!
+//! ### Measure Shim Overhead
+//!
+//! Times 100 calls (or `-n <runs>`) of an alias through each way of running
+//! it, from a loop in a single shell, and shows the time per call and what each
+//! adds to running the command directly. This helps deciding how to run
+//! aliases called in hot loops of scripts:
+//!
+//! ```text
+//! cmdlink bench [alias] [-n <runs>]
+//! ```
+//!
+//! The backends are `direct`, the command itself; `function`, a shell function
+//! running the command, as the completion loader defines for `dir` aliases
+//! (Unix only); `script`, the link file; and `dispatcher`,
+//! `cmdlink run <alias>`, which loads the config on every call. Without an
+//! alias, a trivial one that does nothing is timed, which leaves out the
+//! dispatcher as it isn't in the config. The output of the alias is discarded.
+//!
+//! ### Run History
+//!
+//! With `run_history = true` in the settings, every alias records when it ran,
+//! its exit code and how long it took, which helps when diagnosing a flaky
+//! script. `cmdlink history` shows the 20 most recent runs, or those of a
+//! single alias:
+//!
+//! ```text
+//! cmdlink history [alias] [--failed] [-n <limit>] [--json]
+//! ```
+//!
+//! `--failed` only shows runs with a non-zero exit code, and `--json` prints
+//! them as JSON for other tools. Durations are measured to the second, or to
+//! the hundredth of a second on Windows. The history is kept in `runs.jsonl` in
+//! the project directory, which drops its oldest runs as it grows. Run `cmdlink
+//! refresh` after changing the setting so the existing links start or stop
+//! recording. Runs include the arguments the alias was given, so avoid
+//! recording aliases that take secrets as arguments.
+//!
+//! `cmdlink last` runs the most recently recorded invocation again with the
+//! same arguments, much like `!!` in a shell, but also for aliases run from
+//! scripts, editors or other shells. With `--pick`, it lists the last 10
+//! invocations (or `-n <limit>`) and asks which one to run:
+//!
+//! ```text
+//! cmdlink last [--pick [-n <limit>]]
+//! ```
+//!
+//!
+//! ### Find Stale Aliases
+//!
+//! Lists the aliases that weren't used within the given number of days (90 by
+//! default), least recently used first, to clean up aliases that are no longer
+//! needed:
+//!
+//! ```text
+//! cmdlink stale [--days <n>] [--remove]
+//! ```
+//!
+//! An alias counts as used when the run history (see `run_history`) recorded a
+//! run of it, or else when it was last added or updated. Aliases with neither
+//! are always listed. `--remove` asks whether to remove each listed alias.
+//!
+//! ### Clean Up Old Files
+//!
+//! Trims the files cmdlink accumulates in the project directory: old runs in
+//! the run history, binaries cached for `release` aliases that no longer use
+//! them, and link backups left in the bins directory by an interrupted change:
+//!
+//! ```text
+//! cmdlink gc [--dry-run]
+//! ```
+//!
+//! The 10 most recent runs and unused releases are always kept, and those
+//! beyond them are removed once they're older than 90 days. `gc_keep` and
+//! `gc_max_age` in the settings change both. Releases in use by an alias are
+//! never removed. `--dry-run` only reports what would be removed.
+//!
+//! ### Refresh Links
+//!
+//! Refreshes all alias links based on the configuration file, updating binaries
+//! in the bins directory.
+//!
+//! ```text
+//! cmdlink refresh [--force] [--prune]
+//! ```
+//!
+//! Pass `--force` to rewrite every binary from the current settings, not just
+//! the missing and outdated ones.
+//! Files in the bins directory that don't belong to any alias are reported,
+//! and deleted when `--prune` is passed.
+//!
+//! An outdated binary may have been tuned by hand, so on a terminal `refresh`
+//! asks for each one whether to keep it, take the version generated from the
+//! config, or show the diff between them first. Elsewhere the binary is
+//! regenerated, unless the `confirm` setting is `no`.
+//!
+//! ### Alias Packs
+//!
+//! Packs are curated bundles of aliases that can be installed in one go, e.g.
+//! to onboard new teammates with a standard toolbox. A pack is a TOML file with
+//! a `[pack]` manifest and an `[aliases]` table in the same format as
+//! config.toml:
+//!
+//! ```toml
+//! [pack]
+//! name = "git-essentials"
+//! version = "1.0.0"
+//! prefix = "g"
+//!
+//! [aliases]
+//! st = { cmd = "git status", description = "Short status" }
+//! ```
+//!
+//! ```bash
+//! cmdlink pack install git-essentials          # from ~/.cmdlink/packs/git-essentials.toml
+//! cmdlink pack install ./team.toml --prefix t  # from a file, under a custom prefix
+//! cmdlink pack install https://example.com/pack.toml
+//! cmdlink pack install ./team.toml --on-conflict rename
+//! cmdlink pack update git-essentials
+//! cmdlink pack remove git-essentials
+//! cmdlink pack list
+//! cmdlink pack create work-tools --from-tag work --version 1.0.0
+//! ```
+//!
+//! Aliases are installed under `<prefix>-<alias>`, where the prefix defaults to
+//! the pack's `prefix` or name.
+//!
+//! By default an install fails without changing anything if any of its aliases
+//! already exists. `--on-conflict` picks how collisions are handled instead,
+//! and a report of what happened to each alias is printed once the install is
+//! done:
+//!
+//! - `skip` keeps the existing alias; the pack doesn't own it.
+//! - `overwrite` replaces the existing alias, the same as `--force`.
+//! - `rename` installs the pack's alias under the first free `<alias>-<n>`
+//!   name, e.g. `gs-2`.
+//! - `prompt` asks which of the above to do for each collision, and can show a
+//!   diff of the two definitions first. It fails when cmdlink runs
+//!   non-interactively.
+//!
+//! `pack create` bundles the selected aliases, including their descriptions and
+//! platform variants, into `~/.cmdlink/packs/<name>.toml` (or `--output`). The
+//! manifest records the pack's `name`, `version` and `[pack.compatibility]`
+//! requirements (`min_version` and `platforms`), which are checked on install.
+//!
+//! ### Open the Project Directory
+//!
+//! Opens `~/.cmdlink` (or the bins directory with `--bins`) in the platform
+//! file manager, or in `$VISUAL`/`$EDITOR` with `--editor`.
+//!
+//! ```text
+//! cmdlink open [--bins] [--editor]
+//! ```
+//!
+//! ### Print Paths
+//!
+//! Prints the bins directory, e.g. for `export PATH` one-liners, or the path
+//! of an alias' binary. Only the path is printed, so the output can be used in
+//! scripts.
+//!
+//! ```text
+//! cmdlink path [alias]
+//! ```
+//!
+//! ### Diagnose PATH Problems
+//!
+//! If aliases aren't found, `env` checks whether the bins directory exists,
+//! whether it is on the PATH of the current terminal and on the PATH new
+//! terminals get, and which shell startup files mention it. It also lists the
+//! system directories that come before it on PATH, whose programs take
+//! precedence over aliases with the same name.
+//!
+//! ```text
+//! cmdlink env
+//! ```
+//!
+//! ### Find Aliases Using a Command
+//!
+//! Lists every alias whose command invokes a program, e.g. to find the aliases
+//! to fix after a tool is renamed or removed. Programs are found anywhere in
+//! the command, including pipelines and full paths such as
+//! `/usr/local/bin/kubectl`, and platform variants are searched too. Text with
+//! spaces, such as `"get pods"`, is matched as a plain substring of the command
+//! instead.
+//!
+//! ```text
+//! cmdlink why kubectl
+//! ```
+//!
+//! ### Alias Dependency Graph
+//!
+//! Shows which aliases invoke other aliases, as trees rooted at the aliases
+//! nothing else invokes, followed by the programs used by more than one alias.
+//! Check it before removing an alias that others may rely on. `--dot` prints
+//! the graph in the Graphviz DOT language instead, e.g. for
+//! `cmdlink graph --dot | dot -Tsvg > aliases.svg`.
+//!
+//! ```text
+//! cmdlink graph [--dot]
+//! ```
+//!
+//! ### Relocate the Bins Directory
+//!
+//! Moves the binaries of all aliases to a new directory, e.g. one that is
+//! already on your PATH such as `~/.local/bin`, and stores it as the
+//! `bins_dir` setting. The old directory is removed from your PATH and the new
+//! one added, in the same way as `cmdlink init`; pass `--no-path` to leave PATH
+//! alone.
+//!
+//! ```text
+//! cmdlink relocate ~/.local/bin [--no-path]
+//! ```
+//!
+//! A relocated bins directory may be shared with other programs, so `refresh`
+//! doesn't report or prune files in it that don't belong to an alias.
+//!
+//! cmd.exe can't run batch files whose path is longer than 259 characters,
+//! which deep home directories can reach. cmdlink refuses to write such links
+//! with a `link_path_too_long` error rather than leaving links that fail to
+//! run; relocating the bins directory to a shorter path, e.g. `C:\bin`, fixes
+//! it.
+//!
+//! Antivirus scanners and batch files that are still running hold links open
+//! on Windows, so updating or removing a link is retried a few times over about
+//! a second and a half before failing with a `link_locked` error naming the
+//! locked file.
+//!
+//! ### Move the Project Directory
+//!
+//! Moves the whole `~/.cmdlink` directory, with the config, links and
+//! everything else in it, e.g. onto another drive or into a synced folder. The
+//! links are regenerated so that paths into the old location, such as those of
+//! chained aliases, point to the new one, and the bins directory is replaced on
+//! your PATH unless `--no-path` is passed or the bins directory was relocated
+//! elsewhere. The new location is recorded in `cmdlink/home` in your config
+//! directory (e.g. `~/.config`) for future runs.
+//!
+//! ```text
+//! cmdlink migrate-home /mnt/data/cmdlink [--no-path]
+//! ```
+//!
+//! The new directory must not exist yet or be empty. Shell profiles that source
+//! the completion loader have to be updated by hand. In portable mode the
+//! project directory is the directory of the executable, so move that instead.
+//!
+//! ### Adopt Existing Scripts
+//!
+//! Hand-written scripts in the bins directory can be turned into aliases.
+//! `adopt` extracts the command and exported variables from scripts that run a
+//! single command, asks before adopting each one, and replaces the script with
+//! a regular link. Scripts doing more than that are left alone.
+//!
+//! ```text
+//! cmdlink adopt
+//! ```
+//!
+//! ### Export to Dotfiles
+//!
+//! `export --dotfiles` writes the config into a dotfiles directory, along with
+//! a script that regenerates the links on other machines. The default `stow`
+//! layout is a `cmdlink` package for GNU stow: `stow -d <dir> -t ~ cmdlink`
+//! links the config into place, and the linked `bootstrap.sh` runs `cmdlink
+//! refresh`. The `chezmoi` layout uses chezmoi's `dot_` naming and a
+//! `run_onchange_` script, so `chezmoi apply` refreshes the links whenever the
+//! config changes.
+//!
+//! ```text
+//! cmdlink export --dotfiles <dir> [--layout stow|chezmoi]
+//! ```
+//!
+//! For machines without network access, `export --archive` bundles the config
+//! and the generated links into a zip archive, along with an install script for
+//! the current platform. Unpacking it and running `install.sh` (or
+//! `install.cmd` on Windows) puts them into `~/.cmdlink`, and regenerates the
+//! links if cmdlink is installed there. The script refuses to overwrite an
+//! existing config; use `cmdlink merge` for those machines instead.
+//!
+//! ```text
+//! cmdlink export --archive cmdlink-bundle.zip
+//! ```
+//!
+//! For teammates who want the aliases without installing cmdlink,
+//! `export --omz-plugin` writes an oh-my-zsh plugin named after the given
+//! directory. Aliases that only rename a command become zsh aliases. Aliases
+//! with `env`, `cwd`, `shell`, `nice`, `forward_args = false` or `completions`
+//! become functions that run the command in a subshell, and `dir` aliases
+//! become functions that jump to the directory. Argument completion is wired up
+//! the same way as in the completion loader. Aliases that need cmdlink to run
+//! are left out with a warning, such as those with `secrets`, `env_file`,
+//! `capture`, `min_version`, a `url` or a `release`. The `pre_exec` and
+//! `post_exec` hooks and run history aren't exported. Add the plugin's name to
+//! `plugins=(...)` in `~/.zshrc` to enable it:
+//!
+//! ```text
+//! cmdlink export --omz-plugin "$ZSH_CUSTOM/plugins/team-aliases"
+//! ```
+//!
+//! ### Alias Report
+//!
+//! `report` renders every alias as a document for a team wiki, grouped by tag,
+//! with its descriptions, command, platform variants and examples. Environment
+//! variables and secrets are left out. The default format is Markdown, while
+//! `html` produces a standalone page. The report is printed unless `-o` gives a
+//! file to write it to.
+//!
+//! ```text
+//! cmdlink report [--format md|html] [-o <path>]
+//! ```
+//!
+//! ### Merge Configs
+//!
+//! Merges the aliases of another cmdlink config file into yours and generates
+//! binaries for the new ones, e.g. when consolidating the configs of two
+//! machines. Aliases defined differently in both files are handled with the
+//! same `--on-conflict` strategies as `pack install`; by default yours are
+//! kept (`skip`), while `overwrite` takes theirs. A report of what happened to
+//! each alias is printed at the end. Only aliases are merged, not the other
+//! file's settings, hooks or packs.
+//!
+//! ```text
+//! cmdlink merge <path/to/config.toml> [--on-conflict skip|overwrite|rename|prompt]
+//! ```
+//!
+//! ### Suggest Aliases From Shell History
+//!
+//! Reads your bash, zsh, fish and PowerShell history, finds long commands you
+//! type often that don't have an alias yet, and offers to add an alias for each
+//! under a short suggested name. When not run from a terminal, the suggestions
+//! are printed as `[aliases]` entries instead, ready to paste into the config.
+//!
+//! ```text
+//! cmdlink suggest [--history <file>]... [--min-count <n>] [--min-length <n>] [-n <limit>]
+//! ```
+//!
+//! By default commands typed at least 3 times and at least 15 characters long
+//! are considered, and at most 10 are suggested. `--history` reads the given
+//! files instead of the detected ones.
+//!
+//! ### Suggest Alias Names
+//!
+//! Proposes short alias names for a command, built from the initials and
+//! prefixes of its words, e.g. `kgp` for `kubectl get pods`. Names that are
+//! already aliases or executables on PATH are skipped, so none of the
+//! suggestions shadow an existing command. `-n` limits how many are printed, 5
+//! by default.
+//!
+//! ```text
+//! cmdlink suggest-name "kubectl get pods --all-namespaces" [-n <count>]
+//! ```
+//!
+//! ### Inspect the Config
+//!
+//! Prints the configuration exactly as cmdlink loaded it, as TOML or, with
+//! `--json`, as JSON.
+//!
+//! ```text
+//! cmdlink config cat [--json]
+//! ```
+//!
+//! `config edit` opens the config in `$VISUAL` or `$EDITOR` and validates it
+//! once the editor exits, offering to edit it again if it's invalid. Missing or
+//! outdated binaries are then regenerated, so hand edits take effect right
+//! away.
+//!
+//! ```text
+//! cmdlink config edit
+//! ```
+//!
+//! `config fmt` rewrites the config in the canonical style cmdlink saves it
+//! in, with aliases sorted by name, keys in a fixed order, and strings longer
+//! than 100 characters wrapped over several lines without changing their value.
+//! This keeps dotfile diffs minimal after hand edits. `--check` fails instead
+//! of rewriting the config if it isn't formatted, e.g. in a pre-commit hook.
+//!
+//! ```text
+//! cmdlink config fmt [--check]
+//! ```
+//!
+//! `config get`, `config set` and `config unset` read and change the global
+//! settings of the `[settings]` table (see [Settings](#settings)) without
+//! hand-editing TOML, e.g. from scripts. Keys and values are checked against
+//! the known settings; `config get` without a key lists them all. `bins_dir`
+//! can only be changed through `cmdlink relocate`, which also moves the
+//! binaries.
+//!
+//! ```text
+//! cmdlink config get [key]
+//! cmdlink config set table_style ascii
+//! cmdlink config unset table_style
+//! ```
+//!
+//! ### Plugins
+//!
+//! Unknown subcommands are forwarded to an executable named `cmdlink-<name>`
+//! on your PATH, in the same way as `cargo` and `git`. For example, `cmdlink
+//! sync --dry-run` runs `cmdlink-sync --dry-run`. Plugins receive the location
+//! of the project directory, config file and bins directory through the
+//! `CMDLINK_HOME`, `CMDLINK_CONFIG` and `CMDLINK_BINS` environment variables.
+//!
+//! ### Watch for Changes
+//!
+//! Watches config.toml and the bins directory, automatically regenerating
+//! missing or outdated binaries whenever the config is edited by hand or
+//! updated by a sync tool.
+//!
+//! ```text
+//! cmdlink watch [--debounce-ms <ms>]
+//! ```
+//!
+//! To keep the watcher running in the background, register it as a service
+//! that starts on login (a systemd user unit on Linux, a launchd agent on
+//! macOS, or a Task Scheduler job on Windows):
+//!
+//! ```text
+//! cmdlink service install
+//! cmdlink service uninstall
+//! ```
+//!
+//! ### Completion for Aliases
+//!
+//! cmdlink keeps a `completion-loader.sh` script in the project directory up to
+//! date with your aliases. Sourcing it from `~/.bashrc` or `~/.zshrc` makes tab
+//! completion after an alias complete files and directories, and flags listed
+//! in the `--help` output of the aliased program. It also defines the functions
+//! of `dir` aliases that jump to a directory:
+//!
+//! ```bash
+//! source ~/.cmdlink/completion-loader.sh
+//! ```
+//!
+//! Aliases can also declare the words to complete for each argument position,
+//! starting at `1`, such as the environments of a deploy script. Words can't
+//! contain whitespace:
+//!
+//! ```toml
+//! [aliases]
+//! deploy = { cmd = "./deploy.sh", completions = { 1 = ["dev", "staging", "prod"], 2 = ["--dry-run"] } }
+//! ```
+//!
+//! ### Completion for cmdlink
+//!
+//! `cmdlink completions print` prints a script completing cmdlink's own
+//! subcommands and flags in bash, zsh, fish or PowerShell.
+//! `cmdlink completions install` writes it to where the shell picks it up:
+//!
+//! - bash: `~/.local/share/bash-completion/completions/cmdlink`, which
+//!   bash-completion loads on demand
+//! - zsh: `~/.zsh/completions/_cmdlink`; add `fpath=(~/.zsh/completions
+//!   $fpath)` to `~/.zshrc` before `compinit` if it isn't there yet
+//! - fish: `~/.config/fish/completions/cmdlink.fish`
+//! - PowerShell: `cmdlink.completion.ps1` next to your profile, along with a
+//!   line in the profile loading it
+//!
+//! The shell is detected from `$SHELL`, and is PowerShell on Windows. Pass
+//! `--shell` to pick another one. `--uninstall` removes the script again:
+//!
+//! ```text
+//! cmdlink completions install [--shell bash|zsh|fish|powershell] [--uninstall]
+//! ```
+//!
+//! ### cmd.exe Macros
+//!
+//! On Windows, the aliases can also be defined as doskey macros in every
+//! cmd.exe session, so that they behave like true shell aliases. `autorun
+//! install` writes the macros to `autorun.cmd` in the project directory and
+//! registers it under the AutoRun value of
+//! `HKCU\Software\Microsoft\Command Processor`, next to any commands already
+//! there. The script is rewritten whenever the aliases change, and `autorun
+//! uninstall` removes it again.
+//!
+//! ```text
+//! cmdlink autorun install [--no-register]
+//! cmdlink autorun uninstall
+//! ```
+//!
+//! Aliases with environment variables, secrets, an env file, a capture file, a
+//! priority or `gui = true`, and commands using `|`, `<`, `>` or `&`, run their
+//! binary from the macro instead, as do all aliases while `pre_exec` or
+//! `post_exec` hooks or `run_history` are set.
+//!
+//! ### Logging
+//!
+//! Only warnings and errors are logged by default. Pass `-v` for INFO, `-vv`
+//! for DEBUG or `-vvv` for TRACE output, or `--quiet` to only log errors. For
+//! finer control, `RUST_LOG` accepts per-module directives that take
+//! precedence over the flags:
+//!
+//! ```text
+//! RUST_LOG=cmdlink::platform_binary=trace cmdlink refresh --force
+//! ```
+//!
+//! Logs are written to stderr, together with status messages such as "No
+//! aliases available" or the summary below `display`, so that stdout only
+//! carries the data a command produces: tables, JSON, paths and scripts.
+//! Output can be piped into other tools without filtering. To also keep the
+//! logs in a file, e.g. during automated provisioning, pass `--log-file <path>`
+//! or set `log_file` in the `[settings]` table of the config:
+//!
+//! ```toml
+//! [settings]
+//! log_file = "/var/log/cmdlink.log"
+//! ```
+//!
+//! Fatal errors are reported with a stable error code, the alias and file
+//! involved, and a suggested fix where one is known. Pass `--output json` to
+//! get them as JSON objects instead, for scripts that wrap cmdlink:
+//!
+//! ```text
+//! $ cmdlink --output json path nope
+//! {"alias":"nope","code":"unknown_alias","help":"run `cmdlink display` to list the aliases","message":"Alias 'nope' does not exist","path":null}
+//! ```
+//!
+//! ### Localization
+//!
+//! Messages, prompts and errors are looked up in a message catalog, so they
+//! can be translated without changing cmdlink. The locale is taken from the
+//! `locale` setting, or the `LC_ALL`, `LC_MESSAGES` and `LANG` environment
+//! variables. Catalogs are TOML files in the `locales` directory of the project
+//! directory, named after their locale:
+//!
+//! ```text
+//! ~/.cmdlink/locales/de.toml
+//! ~/.cmdlink/locales/pt_BR.toml
+//! ```
+//!
+//! Start from `locales/en.toml` in the cmdlink sources, which lists every
+//! message in English. A catalog only needs to contain the messages it
+//! translates. The rest fall back to the language catalog, e.g. `pt.toml` for
+//! `pt_BR`, and then to English. Error messages are translated through
+//! `[error]` and `[help]` tables keyed by their error code. JSON output always
+//! stays in English, so scripts can rely on it. See [i18n] for details.
+//!
+//! ```toml
+//! [display]
+//! summary = "{total} Aliase, {broken} defekt, {disabled} deaktiviert"
+//!
+//! [error]
+//! unknown_alias = "Alias '{alias}' existiert nicht"
+//! ```
+//!
+//! View the --help function to see additional information for each subcommand.
+//!
+//! ## Configuration
+//!
+//! Aliases are saved in a configuration file (config.toml) located in the
+//! project directory. The structure of this file is as follows:
+//!
+//! ```toml
+//! [aliases]
+//! myalias = { cmd = "echo Hello, World!", description = "Prints a greeting" }
+//! another_alias = { cmd = "ls -la", description = "Lists all files in long format" }
+//! ```
+//!
+//! Aliases can use a different command per platform through the `platform`
+//! table, falling back to `cmd` on other platforms:
+//!
+//! ```toml
+//! [aliases]
+//! open = { cmd = "xdg-open", platform = { windows = "start \"\"", macos = "open" } }
+//! ```
+//!
+//! Aliases can open a web page instead of running a command, as one-word
+//! launchers for dashboards and internal tools. With `url`, the shim opens the
+//! page in the default browser (through `xdg-open`, `open` or the Windows URL
+//! handler) via `cmdlink open-url`. The arguments the alias is invoked with are
+//! appended as query parameters: `key=value` arguments become parameters of
+//! their own, and the others are joined into a `q` parameter, so
+//! `dash env=prod latency` opens `...?env=prod&q=latency`. Set
+//! `forward_args = false` to always open the page as is:
+//!
+//! ```toml
+//! [aliases]
+//! dash = { url = "https://grafana.internal/d/api", description = "API dashboard" }
+//! ```
+//!
+//! The same can be set with `cmdlink add <alias> --url <url>`.
+//!
+//! Aliases can also jump to a project directory, zoxide style, optionally
+//! running a command such as a virtualenv activation afterwards. A link runs in
+//! a process of its own and can't change the directory of your shell, so on
+//! bash and zsh the jump is done by a function of the same name defined in the
+//! completion loader (see Completion for Aliases), which takes precedence over
+//! the link once the loader is sourced. The link itself prints the directory,
+//! for `cd "$(proj)"` in other shells. On Windows, the link changes the
+//! directory of the cmd.exe session running it:
+//!
+//! ```toml
+//! [aliases]
+//! proj = { dir = "~/src/project", activate = "source .venv/bin/activate" }
+//! ```
+//!
+//! The same can be set with
+//! `cmdlink add <alias> --dir <path> [--activate <command>]`.
+//!
+//! Aliases can run a specific version of a tool from its GitHub releases,
+//! without installing it. With `release`, the asset for the current platform
+//! is downloaded with `curl` when the link is written, verified against its
+//! SHA-256 checksum, unpacked if it's an archive, and cached under
+//! `~/.cmdlink/releases/<owner>/<repo>/<tag>`; the link then runs the cached
+//! binary. The checksum is the one given in `sha256`, or else the one GitHub
+//! publishes for the asset or a checksums file attached to the release; assets
+//! without any are refused. `asset` picks the asset by name, with `*`
+//! wildcards, when it can't be told from the platform, and `bin` names the
+//! binary inside the archive, the repository name by default. Set
+//! `GITHUB_TOKEN` for private repositories or to avoid rate limits, and run
+//! `cmdlink refresh --force` to download a cache that was removed again:
+//!
+//! ```toml
+//! [aliases]
+//! gh = { release = "cli/cli@v2.63.0", asset = "gh_*_linux_amd64.tar.gz", bin = "gh" }
+//! ```
+//!
+//! The same can be set with `cmdlink add <alias> --release <owner/repo@tag>
+//! [--asset <pattern>] [--bin <name>] [--sha256 <hex>]`.
+//!
+//! Aliases relying on a recent version of a tool can check it before running,
+//! so that an outdated tool fails with a clear error such as
+//! `node 18.19.0 found, alias 'serve' requires >=20` rather than deep inside
+//! the command. `min_version` runs `cmd` and compares the first version number
+//! it prints against `require`, a list of comparisons separated by commas, e.g.
+//! `>=20` or `>=1.2, <2`. A comparison only looks at as many components as it
+//! names, so `<=2` allows `2.9`. The check runs `cmdlink` and the tool on every
+//! invocation, so it adds a little startup time:
+//!
+//! ```toml
+//! [aliases.serve]
+//! cmd = "node server.js"
+//! min_version = { cmd = "node --version", require = ">=20" }
+//! ```
+//!
+//! The same can be set with `cmdlink add <alias> --cmd <command>
+//! --min-version <requirement> [--version-cmd <command>]`.
+//!
+//! Commands and environment values can reference `{home}`, `{config_dir}`,
+//! `{cmdlink_dir}` and `${ENV_VAR}`, which are expanded when the shims are
+//! generated so that configs stay portable across machines. Double the opening
+//! character to keep a placeholder literal, e.g. `$${HOME}` is written to the
+//! shim as `${HOME}` and expanded by the shell at run time instead, which
+//! variables like `${PWD}` usually need. Other `${...}` forms, such as
+//! `${1:-default}` or `${@}`, are always left to the shell.
+//!
+//! Aliases can build on each other by referencing another alias as `@name`.
+//! The shim runs the referenced alias' own shim, so its environment, hooks and
+//! platform variant apply as well, and shims referencing an alias are
+//! regenerated whenever it changes. Only words naming an alias are references,
+//! so arguments like curl's `@data.json` are left alone, and `@@` keeps a
+//! literal `@` at the start of a word. Aliases referencing each other in a
+//! cycle are rejected:
+//!
+//! ```toml
+//! [aliases]
+//! gs = { cmd = "git status -s" }
+//! gl = { cmd = "git log --oneline -5" }
+//! overview = { cmd = "@gs && @gl" }
+//! ```
+//!
+//! Secrets such as API tokens can be kept out of the config and shims by
+//! storing them in the OS keychain (macOS Keychain, libsecret on Linux, the
+//! Windows Credential Locker) and referencing them through `secrets`. The shim
+//! retrieves them through `cmdlink secret` each time the alias is invoked:
+//!
+//! ```toml
+//! [aliases]
+//! deploy = { cmd = "./deploy.sh", secrets = { API_TOKEN = "keyring:myservice/token" } }
+//! ```
+//!
+//! Machine specific values can also be kept in a `.env` file next to the app
+//! they configure. With `env_file`, the shim loads the file's `KEY=VALUE` lines
+//! each time the alias is invoked, and fails if the file can't be read. Blank
+//! lines, `#` comments and a leading `export` are skipped, and quotes around
+//! values are stripped. A leading `~` and template variables in the path are
+//! expanded. Variables from `env` and `secrets` take precedence over the file:
+//!
+//! ```toml
+//! [aliases]
+//! myapp = { cmd = "myapp serve", env_file = "~/.config/myapp/.env" }
+//! ```
+//!
+//! The same can be set with `cmdlink add --env-file <path>`.
+//!
+//! `cwd` sets the directory the command runs in, with a leading `~` and
+//! template variables expanded, and the shim fails if it doesn't exist. On
+//! Unix, `shell` runs the command through another shell, such as `bash` or
+//! `zsh`, for commands relying on its syntax. Windows shims always run the
+//! command in cmd.exe.
+//!
+//! Aliases sharing settings can join a group defined in a `[groups.<name>]`
+//! table, instead of repeating the settings on each alias. Members inherit the
+//! group's `env`, `cwd`, `shell` and `tags`. Values set on the alias itself
+//! take precedence, and environment variables are merged key by key. Shims are
+//! regenerated from the group when it changes, e.g. by `cmdlink refresh`:
+//!
+//! ```toml
+//! [groups.k8s]
+//! env = { KUBECONFIG = "{home}/.kube/dev" }
+//! tags = ["k8s"]
+//!
+//! [aliases]
+//! kgp = { cmd = "kubectl get pods", group = "k8s" }
+//! kprod = { cmd = "kubectl get pods", group = "k8s", env = { KUBECONFIG = "{home}/.kube/prod" } }
+//! ```
+//!
+//! The group can also be set with `cmdlink add --group <name>`.
+//!
+//! Aliases run from launchers or schedulers often have nowhere to show their
+//! output. With `capture`, the shim copies the command's stdout and stderr to a
+//! file, creating its directory if needed, while still passing the output
+//! through and exiting with the command's exit code. On Windows, where cmd.exe
+//! has no `tee`, the output only goes to the file. `capture_mode` picks how the
+//! file is written: `append` (the default), `truncate` to keep only the last
+//! run, or `rotate` to move the previous files to `<file>.1` through
+//! `<file>.5` first:
+//!
+//! ```toml
+//! [aliases]
+//! db-backup = { cmd = "pg_dump mydb -f /backups/mydb.sql", capture = "~/.cmdlink/logs/db-backup.log", capture_mode = "rotate" }
+//! ```
+//!
+//! The same can be set with `cmdlink add --capture <path>`, which appends to
+//! the file.
+//!
+//! Heavy batch jobs can be run at a lower priority by setting `nice = <value>`
+//! or `low_priority = true` on an alias. On Unix the command is wrapped with
+//! `nice` (and `ionice` on Linux), on Windows it is started through `cmd /C`
+//! with the matching `start` priority class, so builtins and command lists
+//! work as well.
+//!
+//! ```toml
+//! [aliases]
+//! backup = { cmd = "restic backup ~", low_priority = true }
+//! ```
+//!
+//! On Windows, GUI apps such as editors and browsers can be aliased with
+//! `gui = true`, which starts the app with `start ""` and returns right away
+//! instead of blocking the console window. It has no effect on other
+//! platforms.
+//!
+//! ```toml
+//! [aliases]
+//! code = { cmd = "\"C:\\Program Files\\Microsoft VS Code\\Code.exe\"", gui = true }
+//! ```
+//!
+//! Windows shims switch the console to the UTF-8 code page (65001) while the
+//! command runs, and restore the previous code page afterwards, so that
+//! commands and arguments with non-ASCII characters aren't garbled.
+//!
+//! Aliases meant to be launched from the Run dialog (Win+R) or by other
+//! programs can set `app_path = true`, which registers the binary under
+//! `HKCU\Software\Microsoft\Windows\CurrentVersion\App Paths\<alias>.exe`.
+//! The entry is created and removed along with the binary, and entries of
+//! installed programs with the same name are never overwritten.
+//!
+//! Aliases that only rename a program, such as `g = "git"`, can set
+//! `symlink = true` on Unix to make their link a symlink to the program rather
+//! than a script, which saves starting a shell on every call (see
+//! `cmdlink bench`). This applies when the command is a lone program name,
+//! looked up on PATH when the link is written, or an absolute path, and to
+//! `release` aliases. Aliases using anything a script wraps the command with,
+//! such as `env`, `cwd`, `shell`, `capture`, `nice`, `min_version`, hooks, run
+//! recording or `forward_args = false`, keep a script, as do all aliases on
+//! Windows. The program sees the alias name as its `argv[0]`, which changes the
+//! behavior of multi-call binaries such as busybox, hence the opt-in. Run
+//! `cmdlink refresh` after installing the program somewhere else so the
+//! symlink follows it.
+//!
+//! Aliases can carry a `long_description` and usage `examples` next to the
+//! short `description`, so that a shared team config doubles as documentation.
+//! They're shown by `cmdlink show` and included in exports and packs:
+//!
+//! ```toml
+//! [aliases.gs]
+//! cmd = "git status"
+//! description = "Short git status"
+//! long_description = """
+//! Shows the working tree status, pass -s for the short format."""
+//! examples = ["gs -s", "gs --staged"]
+//! ```
+//!
+//! Setting `disabled = true` on an alias keeps it in the config without
+//! generating a binary for it.
+//!
+//! ### Settings
+//!
+//! Global preferences live in the `[settings]` table, and can also be changed
+//! with `cmdlink config set`. Every setting is optional:
+//!
+//! ```toml
+//! [settings]
+//! bins_dir = "/home/me/.local/bin" # set by `cmdlink relocate`
+//! log_file = "/var/log/cmdlink.log" # also log to this file
+//! color = "auto"                    # color log output: auto, always or never
+//! table_style = "rounded"           # rounded, sharp, modern, ascii, markdown, psql or blank
+//! confirm = "ask"                   # answer yes/no questions: ask, yes or no
+//! path_scope = "user"               # PATH updated by init and relocate: user or machine
+//! windows_extension = "bat"         # extension of links on Windows: bat or cmd
+//! auto_refresh = false              # repair missing or outdated links automatically
+//! locale = "de"                     # language of messages, defaults to LANG
+//! run_history = false               # record exit codes and durations of alias runs
+//! gc_keep = 10                      # runs and unused releases `cmdlink gc` always keeps
+//! gc_max_age = "90d"                # age beyond which `cmdlink gc` removes the rest
+//! ```
+//!
+//! `color = "auto"` colors log output only on a terminal, and not when
+//! `NO_COLOR` is set. `confirm = "yes"` or `"no"` answers questions such as
+//! whether to add the bins directory to PATH without asking, which also works
+//! when cmdlink runs non-interactively.
+//!
+//! With `auto_refresh = true`, commands that change the config repair missing
+//! or outdated links on their own instead of warning about them and asking for
+//! `cmdlink refresh`. Commands that only display information, such as
+//! `display`, never touch the links.
+//!
+//! Some environments, such as certain task runners and AppLocker policies,
+//! treat `.bat` and `.cmd` files differently. `windows_extension` picks the
+//! extension of the links on Windows, and changing it with `cmdlink config set`
+//! renames the existing links. A single alias can override it with its own
+//! `windows_extension`:
+//!
+//! ```toml
+//! [aliases]
+//! build = { cmd = "msbuild /m", windows_extension = "cmd" }
+//! ```
+//!
+//! The cmdlink application reads from this file and creates binary links in the
+//! bins directory based on the commands specified for each alias.
+//!
+//! You **should not** manually edit this file, but if you do, run cmdlink
+//! refresh to save your changes to the bin files, or edit it through `cmdlink
+//! config edit`, which does so automatically.
+//!
+//! To keep startup fast, cmdlink remembers which links are missing in a
+//! `cache.toml` file next to the config, and only scans the bins directory
+//! again once the config or the bins directory changes. The cache can safely be
+//! deleted at any time.
+//!
+//! ### Defaults
+//!
+//! Team conventions for new aliases can be set in the `[defaults]` table.
+//! `cmdlink add`, and the other commands creating aliases such as `adopt`,
+//! fill in the fields an alias is created without from it. Aliases that
+//! already exist are left as they are:
+//!
+//! ```toml
+//! [defaults]
+//! description = "Runs {cmd}"  # {alias} and {cmd} are replaced with the alias name and command
+//! tags = ["team"]             # tags of aliases created without --tag
+//! forward_args = false        # don't pass the alias' arguments on to the command
+//! shell = "bash"              # shell commands run through on Unix
+//! ```
+//!
+//! Shims pass the arguments an alias is invoked with on to its command, unless
+//! the alias sets `forward_args = false`, e.g. for commands ending in a fixed
+//! argument. `cmdlink add --no-forward-args` sets it for a single alias.
+//!
+//! ### Hooks
+//!
+//! Shell commands can be run after successful mutating operations through the
+//! `[hooks]` table. Hooks receive the hook name and affected alias through the
+//! `CMDLINK_HOOK` and `CMDLINK_ALIAS` environment variables.
+//!
+//! ```toml
+//! [hooks]
+//! post_add = "git -C ~/dotfiles commit -am 'Add alias'"
+//! post_remove = "git -C ~/dotfiles commit -am 'Remove alias'"
+//! post_refresh = "zsh -c rehash"
+//! ```
+//!
+//! The `pre_exec` and `post_exec` hooks are shell snippets baked into every
+//! generated binary instead, running right before and after the alias'
+//! command, e.g. to source a company wrapper or record audit info. `post_exec`
+//! sees the command's exit code in `CMDLINK_STATUS`, which the binary still
+//! exits with. Run `cmdlink refresh` after changing them by hand to regenerate
+//! all binaries; `cmdlink config edit` does so automatically.
+//!
+//! ```toml
+//! [hooks]
+//! pre_exec = ". /opt/company/env.sh"
+//! post_exec = "echo \"$(date) $0 $CMDLINK_STATUS\" >> ~/.cmdlink/audit.log"
+//! ```
+//!
+//! Directory Structure
+//!
+//! ```text
+//! config.toml: Stores alias names, commands, and descriptions.
+//! bins/: Contains the binary files linked to each alias. These binaries
+//! are generated by cmdlink when an alias is added or refreshed.
+//! ```
+//!
+//! ## Library Usage
+//!
+//! The alias management engine is also available as a library, so other
+//! tools can manage cmdlink aliases without shelling out to the CLI:
+//!
+//! ```no_run
+//! use cmdlink::{AliasBuilder, Config, UpsertOutcome};
+//!
+//! fn main() -> cmdlink::Result<()> {
+//!     let mut cfg = Config::new()?;
+//!     let spec = AliasBuilder::new("gs", "git status").description("Git status").tag("git").build()?;
+//!     if cfg.upsert(spec)? == UpsertOutcome::Created {
+//!         println!("created gs");
+//!     }
+//!     // Changes are written to disk when the config is dropped.
+//!     Ok(())
+//! }
+//! ```
+//!
+//! The engine works in `~/.cmdlink` unless told otherwise. To keep a config
+//! and its links somewhere else, e.g. in a temporary directory for tests, load
+//! it with [Config::new_in]. Configs in different directories can be used side
+//! by side:
+//!
+//! ```no_run
+//! use cmdlink::{paths::CmdlinkPaths, Config};
+//!
+//! # fn main() -> cmdlink::Result<()> {
+//! let cfg = Config::new_in(&CmdlinkPaths::new("/tmp/cmdlink-test"))?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## License
+//! This project is licensed under either of
+//! * Apache License, Version 2.0, ([LICENSE-APACHE](LICENSE-APACHE) or http://www.apache.org/licenses/LICENSE-2.0)
+//! * MIT license ([LICENSE-MIT](LICENSE-MIT) or http://opensource.org/licenses/MIT)
+//!
+//! at your option.
+
+pub mod adopt;
+pub mod alias;
+pub mod archive;
+pub mod autorun;
+pub mod bench;
+mod cache;
+pub mod chain;
+pub mod cli_completion;
+pub mod clipboard;
+pub mod completion;
+pub mod config;
+pub mod defaults;
+pub mod describe;
+pub mod diff;
+pub mod dotfiles;
+pub mod error;
+pub mod gc;
+pub mod graph;
+pub mod history;
+pub mod hooks;
+pub mod i18n;
+pub mod import;
+pub mod omz;
+pub mod open;
+pub mod pack;
+pub mod paths;
+pub mod platform_binary;
+pub mod release;
+pub mod report;
+pub mod runs;
+pub mod sandbox;
+pub mod secret;
+pub mod service;
+pub mod settings;
+pub mod suggest;
+pub mod template;
+pub mod trace;
+pub mod user_path;
+pub mod version;
+pub mod watch;
+pub mod which;
+pub mod width;
+
+use std::path::{Path, PathBuf};
+
+pub use alias::{AliasBuilder, AliasSpec, UpsertOutcome};
+pub use config::{AliasValues, Config};
+pub use error::{Error, Result};
+pub use platform_binary::{Action, Link, LinkStatus, PlatformBinary};
+
+#[macro_use]
+extern crate tracing;
+
+#[macro_use]
+extern crate tabled;
+
+/// The name of the marker file that enables portable mode when placed next to
+/// the cmdlink executable.
+pub const PORTABLE_MARKER: &str = "cmdlink.portable";
+
+/// The directory of the cmdlink executable, if a [PORTABLE_MARKER] file next to
+/// it enables portable mode. In portable mode, the config and bins directory
+/// are kept next to the executable instead of in the home directory.
+pub fn portable_dir() -> Option<PathBuf> {
+	let exe_dir = std::env::current_exe().ok()?.parent()?.to_path_buf();
+	exe_dir.join(PORTABLE_MARKER).is_file().then_some(exe_dir)
+}
+
+/// Enables portable mode by creating the [PORTABLE_MARKER] file next to the
+/// cmdlink executable, returning the new project directory.
+///
+/// This has to happen before [paths::get] is first called to take effect in
+/// the current process.
+pub fn enable_portable() -> Result<PathBuf> {
+	let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+	let exe_dir = exe.parent().map(Path::to_path_buf).unwrap_or_default();
+	let marker = exe_dir.join(PORTABLE_MARKER);
+	std::fs::OpenOptions::new()
+		.create(true)
+		.truncate(false)
+		.write(true)
+		.open(&marker)
+		.map_err(|e| Error::ProjectDirCreation(marker, e))?;
+	Ok(exe_dir)
+}
@@ -0,0 +1,170 @@
+//! Exporting the aliases as an oh-my-zsh plugin, as done by
+//! `cmdlink export --omz-plugin`, for machines that should have the aliases
+//! without cmdlink and its links.
+//!
+//! Aliases that only rename a command become zsh aliases, and those with an
+//! environment, working directory, shell, niceness or declared completions
+//! become functions running the command in a subshell, the way their link runs
+//! it in a process of its own. Aliases jumping to a `dir` become the same
+//! functions the completion loader defines. Anything that calls back into
+//! cmdlink, such as secrets, `url` aliases or `min_version` checks, can't be
+//! done by the plugin, so those aliases are left out. The `pre_exec` and
+//! `post_exec` hooks and run history belong to the machine, and aren't exported
+//! either.
+
+use std::{
+	fmt::Write as _,
+	path::{Path, PathBuf},
+};
+
+use crate::{
+	completion,
+	config::AliasValues,
+	error::Error,
+	platform_binary::{sh_quote, Link, LinkContext},
+	Config, Result,
+};
+
+/// The name of the plugin when the directory it's written to has none.
+const DEFAULT_NAME: &str = "cmdlink";
+
+/// The plugin written by [export].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Plugin {
+	/// The name of the plugin, as listed in `plugins=(...)` in `~/.zshrc`.
+	pub name: String,
+	/// The path of the `<name>.plugin.zsh` file.
+	pub path: PathBuf,
+	/// The number of aliases defined by the plugin.
+	pub aliases: usize,
+	/// The aliases left out of the plugin, as they need cmdlink to run.
+	pub skipped: Vec<String>,
+}
+
+/// Writes the plugin for the enabled aliases of the config into `dir`, naming
+/// it after the directory, e.g. `$ZSH_CUSTOM/plugins/team-aliases`.
+pub fn export(cfg: &Config, dir: &Path) -> Result<Plugin> {
+	let name = dir
+		.file_name()
+		.map_or(DEFAULT_NAME.to_string(), |name| name.to_string_lossy().into_owned());
+	let path = dir.join(format!("{name}.plugin.zsh"));
+	let (script, aliases, skipped) = render(cfg, &name);
+	let write = || {
+		std::fs::create_dir_all(dir)?;
+		std::fs::write(&path, script)
+	};
+	write().map_err(|e| Error::Export(path.clone(), e.to_string()))?;
+	Ok(Plugin {
+		name,
+		path,
+		aliases,
+		skipped,
+	})
+}
+
+/// Renders the plugin script, returning it with the number of aliases it
+/// defines and the aliases left out.
+fn render(cfg: &Config, name: &str) -> (String, usize, Vec<String>) {
+	let mut aliases: Vec<(&str, &AliasValues)> = cfg.aliases().filter(|(_, values)| !values.disabled).collect();
+	aliases.sort_by_key(|(alias, _)| *alias);
+
+	let mut definitions = String::new();
+	let mut functions = Vec::new();
+	let mut skipped = Vec::new();
+	let mut defined = 0;
+	for (alias, values) in &aliases {
+		let definition = match values.dir {
+			Some(_) => completion::jump_function(alias, values, cfg.links()),
+			None => definition(alias, values, cfg.links()).inspect(|definition| {
+				if definition.starts_with("function") {
+					functions.push(sh_quote(alias));
+				}
+			}),
+		};
+		match definition {
+			Some(definition) => {
+				definitions.push('\n');
+				definitions.push_str(&definition);
+				defined += 1;
+			},
+			None => {
+				debug!("Left alias {} out of the plugin", alias);
+				skipped.push(alias.to_string());
+			},
+		}
+	}
+
+	let mut script = format!(
+		"# Generated by cmdlink, changes are overwritten on the next export.\n\
+		 # Add {name} to the plugins in ~/.zshrc to enable it.\n\n\
+		 autoload -U +X bashcompinit && bashcompinit\n\n"
+	);
+	script.push_str(&completion::completer(&aliases, cfg.links()));
+	script.push_str(&definitions);
+	// zsh completes its own aliases as the commands they expand to, so only
+	// the functions need the completer.
+	if !functions.is_empty() {
+		let _ = writeln!(
+			script,
+			"\ncomplete -o default -o bashdefault -F _cmdlink_complete {}",
+			functions.join(" ")
+		);
+	}
+	(script, defined, skipped)
+}
+
+/// The zsh alias or function running the command of an alias, or `None` if
+/// running it needs cmdlink or its name can't be defined in zsh.
+fn definition(alias: &str, values: &AliasValues, links: &LinkContext) -> Option<String> {
+	let link = values.link(alias, links);
+	if link.url().is_some()
+		|| link.release_binary().is_some()
+		|| !link.secrets().is_empty()
+		|| link.min_version().is_some()
+		|| link.env_file().is_some()
+		|| link.capture().is_some()
+		|| !completion::is_function_name(alias)
+	{
+		return None;
+	}
+	let cmd = link.expand(link.cmd()).ok()?;
+
+	let env: Vec<(&String, &String)> = link
+		.inherited_env()
+		.into_iter()
+		.flatten()
+		.filter(|(key, _)| !link.env().contains_key(*key))
+		.chain(link.env())
+		.collect();
+	let cwd = link.cwd().map(|cwd| link.expand_path(cwd)).transpose().ok()?;
+	// Declared completions are only offered for functions, see [render].
+	if env.is_empty()
+		&& cwd.is_none()
+		&& link.shell().is_none()
+		&& link.nice().is_none()
+		&& link.forwards_args()
+		&& values.completions.is_empty()
+	{
+		return Some(format!("alias {alias}={}\n", sh_quote(&cmd)));
+	}
+
+	let mut body = String::new();
+	for (key, value) in env {
+		let _ = writeln!(body, "\t\texport {key}={}", sh_quote(&link.expand(value).ok()?));
+	}
+	if let Some(cwd) = cwd {
+		let _ = writeln!(body, "\t\tcd -- {} || exit", sh_quote(&cwd));
+	}
+	let args = if link.forwards_args() { " \"$@\"" } else { "" };
+	let nice = link.nice().map(|nice| format!("nice -n {nice} ")).unwrap_or_default();
+	let _ = match link.shell() {
+		Some(shell) => writeln!(
+			body,
+			"\t\t{nice}{shell} -c {} {}{args}",
+			sh_quote(&format!("{cmd}{args}")),
+			sh_quote(alias)
+		),
+		None => writeln!(body, "\t\t{nice}{cmd}{args}"),
+	};
+	Some(format!("function {alias} {{\n\t(\n{body}\t)\n}}\n"))
+}
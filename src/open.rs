@@ -0,0 +1,132 @@
+//! Opening files and directories in the user's file manager or editor, and web
+//! pages in their browser.
+
+use std::{path::Path, process::Command};
+
+use crate::{error::Error, hooks, Result};
+
+/// Opens the path in the platform file manager: Explorer on Windows, Finder on
+/// macOS, and the `xdg-open` handler elsewhere.
+pub fn in_file_manager(path: &Path) -> Result<()> {
+	let program = if cfg!(target_os = "windows") {
+		"explorer"
+	} else if cfg!(target_os = "macos") {
+		"open"
+	} else {
+		"xdg-open"
+	};
+	debug!("Opening {} with {}", path.display(), program);
+	let status = Command::new(program)
+		.arg(path)
+		.status()
+		.map_err(|e| Error::Open(path.display().to_string(), format!("failed to run {program}: {e}")))?;
+
+	// Explorer exits with a non-zero code even when it succeeds.
+	if !status.success() && !cfg!(target_os = "windows") {
+		return Err(Error::Open(
+			path.display().to_string(),
+			format!("{program} exited with {status}"),
+		));
+	}
+	Ok(())
+}
+
+/// Opens the path in the user's editor, taken from `VISUAL` or `EDITOR`, and
+/// waits for it to exit. Falls back to Notepad on Windows and `vi` elsewhere.
+///
+/// The editor is run through the platform shell, so it may include arguments
+/// such as `code --wait`.
+pub fn in_editor(path: &Path) -> Result<()> {
+	let editor = std::env::var("VISUAL")
+		.or_else(|_| std::env::var("EDITOR"))
+		.ok()
+		.filter(|editor| !editor.trim().is_empty())
+		.unwrap_or_else(|| if cfg!(target_os = "windows") { "notepad" } else { "vi" }.to_string());
+	debug!("Opening {} with {}", path.display(), editor);
+
+	let status = hooks::shell_command(&format!("{editor} \"{}\"", path.display()))
+		.status()
+		.map_err(|e| Error::Open(path.display().to_string(), format!("failed to run {editor}: {e}")))?;
+	if !status.success() {
+		return Err(Error::Open(
+			path.display().to_string(),
+			format!("{editor} exited with {status}"),
+		));
+	}
+	Ok(())
+}
+
+/// Opens the URL in the default browser: through the URL protocol handler on
+/// Windows, `open` on macOS, and `xdg-open` elsewhere.
+pub fn in_browser(url: &str) -> Result<()> {
+	// `start` would have cmd.exe interpret the `&` between query parameters.
+	let (program, args): (&str, &[&str]) = if cfg!(target_os = "windows") {
+		("rundll32", &["url.dll,FileProtocolHandler"])
+	} else if cfg!(target_os = "macos") {
+		("open", &[])
+	} else {
+		("xdg-open", &[])
+	};
+	debug!("Opening {} with {}", url, program);
+	let status = Command::new(program)
+		.args(args)
+		.arg(url)
+		.status()
+		.map_err(|e| Error::Open(url.to_string(), format!("failed to run {program}: {e}")))?;
+	if !status.success() {
+		return Err(Error::Open(url.to_string(), format!("{program} exited with {status}")));
+	}
+	Ok(())
+}
+
+/// The URL with the arguments appended as query parameters, before any
+/// fragment. `key=value` arguments become parameters of their own, and the
+/// other arguments are joined with spaces into a `q` parameter, as taken by
+/// most search pages.
+///
+/// ```
+/// use cmdlink::open::url_with_args;
+///
+/// let url = url_with_args("https://grafana.internal/d/api#panel", &["env=prod", "p99", "latency"]);
+/// assert_eq!(url, "https://grafana.internal/d/api?env=prod&q=p99%20latency#panel");
+/// ```
+pub fn url_with_args(url: &str, args: &[impl AsRef<str>]) -> String {
+	let (base, fragment) = url
+		.split_once('#')
+		.map_or((url, None), |(base, fragment)| (base, Some(fragment)));
+	let mut params = Vec::new();
+	let mut words = Vec::new();
+	for arg in args.iter().map(AsRef::as_ref) {
+		match arg.split_once('=') {
+			Some((key, value)) if !key.is_empty() => {
+				params.push(format!("{}={}", percent_encode(key), percent_encode(value)));
+			},
+			_ => words.push(arg),
+		}
+	}
+	if !words.is_empty() {
+		params.push(format!("q={}", percent_encode(&words.join(" "))));
+	}
+
+	let mut url = base.to_string();
+	if !params.is_empty() {
+		url.push(if base.contains('?') { '&' } else { '?' });
+		url.push_str(&params.join("&"));
+	}
+	if let Some(fragment) = fragment {
+		url.push('#');
+		url.push_str(fragment);
+	}
+	url
+}
+
+/// Encodes all but the unreserved characters of URLs, for use in a query
+/// parameter.
+fn percent_encode(text: &str) -> String {
+	text.bytes()
+		.map(|byte| match byte {
+			b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' => char::from(byte).to_string(),
+			_ => format!("%{byte:02X}"),
+		})
+		.collect()
+}
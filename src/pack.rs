@@ -0,0 +1,180 @@
+use std::{
+	collections::BTreeMap,
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{alias::AliasSpec, config::AliasValues, error::Error, paths, version, Result};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Metadata describing a pack.
+pub struct PackManifest {
+	/// The name of the pack.
+	pub name: String,
+	/// The version of the pack.
+	pub version: String,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// An optional description of the pack.
+	pub description: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The prefix aliases are installed under, defaulting to the pack name.
+	pub prefix: Option<String>,
+	#[serde(default, skip_serializing_if = "Compatibility::is_empty")]
+	/// The environments the pack is compatible with.
+	pub compatibility: Compatibility,
+}
+
+#[derive(Default, Debug, Clone, Serialize, Deserialize)]
+/// Compatibility requirements of a pack, checked on install.
+pub struct Compatibility {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The minimum cmdlink version required by the pack, e.g. `0.2.0`.
+	pub min_version: Option<String>,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// The platforms the pack supports (`windows`, `macos`, `linux`). All
+	/// platforms are supported when empty.
+	pub platforms: Vec<String>,
+}
+
+impl Compatibility {
+	/// Whether or not the pack has no compatibility requirements.
+	pub fn is_empty(&self) -> bool { self.min_version.is_none() && self.platforms.is_empty() }
+
+	/// Checks that the running cmdlink and platform satisfy the requirements.
+	pub fn check(&self, pack: &str) -> Result<()> {
+		if let Some(min_version) = &self.min_version {
+			if version::parse(env!("CARGO_PKG_VERSION")) < version::parse(min_version) {
+				return Err(Error::PackIncompatible(
+					pack.to_string(),
+					format!("requires cmdlink {min_version} or newer"),
+				));
+			}
+		}
+		if !self.platforms.is_empty() && !self.platforms.iter().any(|p| p == std::env::consts::OS) {
+			return Err(Error::PackIncompatible(
+				pack.to_string(),
+				format!("only supports {}", self.platforms.join(", ")),
+			));
+		}
+		Ok(())
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+/// A curated bundle of alias definitions, stored as a TOML file with a
+/// `[pack]` manifest and an `[aliases]` table in the same format as
+/// config.toml.
+pub struct Pack {
+	/// The pack manifest.
+	pub pack: PackManifest,
+	#[serde(default)]
+	/// The aliases defined by the pack, without their prefix.
+	pub aliases: BTreeMap<String, AliasValues>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Record of an installed pack, stored in the config file so the pack can be
+/// updated or removed later.
+pub struct InstalledPack {
+	/// Where the pack was installed from.
+	pub source: String,
+	/// The installed version of the pack.
+	pub version: String,
+	/// The prefix the aliases were installed under.
+	pub prefix: String,
+	/// The full names of the aliases installed by the pack.
+	pub aliases: Vec<String>,
+}
+
+impl Pack {
+	/// Loads a pack from a URL, a file path, or the name of a pack in the
+	/// `packs` directory.
+	pub fn load(source: &str) -> Result<Self> {
+		let contents = if is_url(source) {
+			fetch_url(source)?
+		} else {
+			let path = resolve_path(source);
+			std::fs::read_to_string(&path).map_err(|e| Error::PackFetch(source.to_string(), e))?
+		};
+		let pack: Pack = toml::from_str(&contents).map_err(|e| Error::PackParse(source.to_string(), Box::new(e)))?;
+		pack.pack.compatibility.check(&pack.pack.name)?;
+		Ok(pack)
+	}
+
+	/// Writes the pack to the given file path.
+	pub fn save(&self, path: &Path) -> Result<()> {
+		let contents = toml::to_string(self)?;
+		if let Some(parent) = path.parent() {
+			std::fs::create_dir_all(parent).map_err(|e| Error::PackWrite(path.display().to_string(), e))?;
+		}
+		std::fs::write(path, contents).map_err(|e| Error::PackWrite(path.display().to_string(), e))
+	}
+
+	/// The prefix aliases are installed under, unless overridden.
+	pub fn default_prefix(&self) -> &str { self.pack.prefix.as_deref().unwrap_or(&self.pack.name) }
+
+	/// Returns the alias specs of the pack, namespaced under the given prefix.
+	/// An empty prefix installs the aliases under their own names.
+	pub fn specs(&self, prefix: &str) -> Result<Vec<AliasSpec>> {
+		self.aliases
+			.iter()
+			.map(|(name, values)| {
+				let name = if prefix.is_empty() {
+					name.clone()
+				} else {
+					format!("{prefix}-{name}")
+				};
+				let spec = AliasSpec::from_values(name, values);
+				spec.validate()?;
+				Ok(spec)
+			})
+			.collect()
+	}
+}
+
+/// Normalizes a pack source so it can be re-fetched later, regardless of the
+/// working directory. File paths are made absolute, while URLs and pack names
+/// are kept as is.
+pub fn normalize_source(source: &str) -> String {
+	if is_url(source) {
+		return source.to_string();
+	}
+	match std::fs::canonicalize(source) {
+		Ok(path) => path.display().to_string(),
+		Err(_) => source.to_string(),
+	}
+}
+
+/// The directory local packs are stored in.
+pub fn packs_dir() -> PathBuf { paths::get().root().join("packs") }
+
+/// Whether or not the pack source is a URL.
+fn is_url(source: &str) -> bool { source.starts_with("https://") || source.starts_with("http://") }
+
+/// Resolves a pack source to a file path, checking the `packs` directory for
+/// bare pack names.
+fn resolve_path(source: &str) -> PathBuf {
+	let path = PathBuf::from(source);
+	if path.exists() {
+		return path;
+	}
+	packs_dir().join(format!("{source}.toml"))
+}
+
+/// Downloads the pack contents using `curl`, which ships with all supported
+/// platforms.
+fn fetch_url(url: &str) -> Result<String> {
+	debug!("Fetching pack from {}", url);
+	let output = Command::new("curl")
+		.args(["-fsSL", url])
+		.output()
+		.map_err(|e| Error::PackFetch(url.to_string(), e))?;
+	if !output.status.success() {
+		let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+		return Err(Error::PackFetch(url.to_string(), std::io::Error::other(stderr)));
+	}
+	String::from_utf8(output.stdout)
+		.map_err(|e| Error::PackFetch(url.to_string(), std::io::Error::new(std::io::ErrorKind::InvalidData, e)))
+}
@@ -0,0 +1,171 @@
+//! The locations of cmdlink's files. Everything cmdlink reads and writes lives
+//! under a single project directory, `~/.cmdlink` by default. Embedders and
+//! tests can load a config from any other one with
+//! [Config::new_in](crate::Config::new_in). `cmdlink migrate-home` moves it for
+//! good, see [migrate].
+
+use std::{
+	io,
+	path::{Path, PathBuf},
+	sync::OnceLock,
+};
+
+use crate::{error::Error, platform_binary, Result};
+
+/// The paths in use, decided on first use.
+static PATHS: OnceLock<CmdlinkPaths> = OnceLock::new();
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// The project directory cmdlink works in, and the files within it.
+pub struct CmdlinkPaths {
+	/// The project directory.
+	root: PathBuf,
+}
+
+impl CmdlinkPaths {
+	/// Paths rooted at the given project directory.
+	pub fn new(root: impl Into<PathBuf>) -> Self { Self { root: root.into() } }
+
+	/// The default paths: the directory of the cmdlink executable in portable
+	/// mode, see [crate::portable_dir], the directory recorded by [migrate], or
+	/// `~/.cmdlink` otherwise.
+	pub fn detect() -> Self {
+		Self::new(
+			crate::portable_dir()
+				.or_else(recorded_root)
+				.unwrap_or_else(default_root),
+		)
+	}
+
+	/// The project directory.
+	pub fn root(&self) -> &Path { &self.root }
+
+	/// The config.toml file.
+	pub fn config_file(&self) -> PathBuf { self.root.join("config.toml") }
+
+	/// The directory link files are stored in, unless the `bins_dir` setting
+	/// moves them elsewhere.
+	pub fn default_bins_dir(&self) -> PathBuf { self.root.join("bins") }
+
+	/// The directory the binaries of GitHub releases are cached in, see
+	/// [Release](crate::release::Release).
+	pub fn releases_dir(&self) -> PathBuf { self.root.join("releases") }
+}
+
+/// The paths of the cmdlink executable itself, see [CmdlinkPaths::detect],
+/// detected on first use.
+pub fn get() -> &'static CmdlinkPaths { PATHS.get_or_init(CmdlinkPaths::detect) }
+
+/// Moves the project directory to `new_root`, e.g. onto another drive or into a
+/// synced folder, and records it for future runs. Returns the paths of the old
+/// location.
+///
+/// This has to happen before [get] is first called for this process to pick up
+/// the new location. The links still have to be regenerated
+/// afterwards, as they may contain paths into the old location.
+pub fn migrate(new_root: &Path) -> Result<CmdlinkPaths> {
+	let fail = |reason: String| Error::MigrateHome(new_root.to_path_buf(), reason);
+	if crate::portable_dir().is_some() {
+		return Err(fail("portable installs move along with the cmdlink executable".to_string()));
+	}
+	let old = CmdlinkPaths::detect();
+	let new_root = std::path::absolute(new_root).map_err(|e| fail(e.to_string()))?;
+	if new_root.starts_with(&old.root) {
+		return Err(fail("it is inside the current project directory".to_string()));
+	}
+	if new_root.read_dir().is_ok_and(|mut entries| entries.next().is_some()) {
+		return Err(fail("it isn't empty".to_string()));
+	}
+
+	move_tree(&old.root, &new_root).map_err(|e| fail(e.to_string()))?;
+	record_root(&new_root).map_err(|e| fail(e.to_string()))?;
+	Ok(old)
+}
+
+/// `~/.cmdlink`.
+fn default_root() -> PathBuf { dirs::home_dir().expect("home directory not found!").join(".cmdlink") }
+
+/// The file [migrate] records the project directory in, in the user's config
+/// directory since the project directory itself moves.
+fn root_record() -> Option<PathBuf> { Some(dirs::config_dir()?.join("cmdlink").join("home")) }
+
+/// The project directory recorded by [migrate], if any.
+fn recorded_root() -> Option<PathBuf> {
+	let recorded = std::fs::read_to_string(root_record()?).ok()?;
+	let root = recorded.trim();
+	(!root.is_empty()).then(|| PathBuf::from(root))
+}
+
+/// Records the project directory for future runs, or removes the record when
+/// it's back at the default location.
+fn record_root(root: &Path) -> io::Result<()> {
+	let Some(record) = root_record() else {
+		return Err(io::Error::new(io::ErrorKind::NotFound, "no config directory to record it in"));
+	};
+	if root == default_root() {
+		return match std::fs::remove_file(&record) {
+			Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+			_ => Ok(()),
+		};
+	}
+	if let Some(parent) = record.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	std::fs::write(record, root.display().to_string())
+}
+
+/// Moves a directory tree. Renaming fails across file systems, in which case
+/// the tree is copied and the original removed.
+fn move_tree(from: &Path, to: &Path) -> io::Result<()> {
+	if let Some(parent) = to.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	// An empty target directory is replaced, which renaming refuses on
+	// Windows.
+	let _ = std::fs::remove_dir(to);
+	if std::fs::rename(from, to).is_ok() {
+		return Ok(());
+	}
+	copy_tree(from, to)?;
+	std::fs::remove_dir_all(from)
+}
+
+/// Copies a directory tree. Symlinks are recreated rather than followed, so
+/// links that are symlinks keep pointing to their programs instead of becoming
+/// copies of them.
+fn copy_tree(from: &Path, to: &Path) -> io::Result<()> {
+	std::fs::create_dir_all(to)?;
+	for entry in std::fs::read_dir(from)? {
+		let entry = entry?;
+		let target = to.join(entry.file_name());
+		if entry.file_type()?.is_dir() {
+			copy_tree(&entry.path(), &target)?;
+		} else {
+			platform_binary::copy_link(&entry.path(), &target)?;
+		}
+	}
+	Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[cfg(target_family = "unix")]
+	#[test]
+	fn copy_tree_keeps_symlinks() {
+		let dir = std::env::temp_dir().join(format!("cmdlink-copy-tree-{}", std::process::id()));
+		let (from, to) = (dir.join("from"), dir.join("to"));
+		std::fs::create_dir_all(from.join("bins")).unwrap();
+		std::fs::write(dir.join("program"), "#!/bin/sh\n").unwrap();
+		std::fs::write(from.join("config.toml"), "").unwrap();
+		std::os::unix::fs::symlink(dir.join("program"), from.join("bins/program")).unwrap();
+
+		copy_tree(&from, &to).unwrap();
+		let link = to.join("bins/program");
+		assert!(link.is_symlink());
+		assert_eq!(std::fs::read_link(&link).unwrap(), dir.join("program"));
+		assert!(to.join("config.toml").is_file());
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
+}
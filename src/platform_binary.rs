@@ -1,62 +1,575 @@
 use std::{
+	collections::{BTreeMap, HashSet},
+	fmt::Write as _,
 	fs::File,
 	io::{ErrorKind, Write},
-	path::Path,
-	process::Command,
+	path::{Path, PathBuf},
+	sync::OnceLock,
+	time::Duration,
 };
 
-use crate::{error::Error, Result, PROJECT_DIR};
+use crate::{
+	chain,
+	config::{self, AliasValues, CaptureMode, Group, MinVersion},
+	describe,
+	error::Error,
+	hooks::Hooks,
+	paths::{self, CmdlinkPaths},
+	release::Release,
+	settings::{Settings, WindowsExtension},
+	template, tr, which, Result,
+};
+
+/// The extensions link files may have on the current platform. Unix/Linux
+/// links have no extension, so that users don't have to type the extension,
+/// and Windows links are batch files with either extension.
+pub const LINK_EXTENSIONS: &[&str] = if cfg!(target_os = "windows") {
+	&[".bat", ".cmd"]
+} else {
+	&[""]
+};
+
+/// The longest path cmd.exe runs a batch file from, `MAX_PATH` less the
+/// terminating NUL. The file operations of the standard library lift this
+/// limit by prefixing long paths with `\\?\`, but the link would then be
+/// written without ever being runnable.
+#[cfg(target_os = "windows")]
+const MAX_LINK_PATH: usize = 259;
+
+/// How often an update or removal of a locked link file is retried, see
+/// [PlatformBinary::perform_action].
+const LOCK_RETRIES: u32 = 5;
+
+/// The delay before the first retry of an action on a locked link file,
+/// doubled for each further retry.
+const LOCK_BACKOFF: Duration = Duration::from_millis(50);
+
+/// The shell code loading the `KEY=VALUE` lines of `$env_file`, see
+/// [Link::env_file_lines]. Lines are read as is rather than sourced, so values
+/// with spaces or `$` don't need shell quoting.
+#[cfg(not(target_os = "windows"))]
+const ENV_FILE_LOADER: &str = r#"[ -r "$env_file" ] || { echo "cmdlink: cannot read env file $env_file" >&2; exit 1; }
+cr="$(printf '\r')"
+while IFS= read -r line || [ -n "$line" ]; do
+	line="${line%"$cr"}"
+	line="${line#"${line%%[![:space:]]*}"}"
+	case "$line" in ''|'#'*) continue ;; esac
+	line="${line#export }"
+	case "$line" in *=*) ;; *) continue ;; esac
+	value="${line#*=}"
+	case "$value" in
+		\"*\") value="${value#\"}"; value="${value%\"}" ;;
+		\'*\') value="${value#\'}"; value="${value%\'}" ;;
+	esac
+	export "${line%%=*}=$value"
+done < "$env_file"
+"#;
+
+/// The lines switching the console of a Windows link to the UTF-8 code page, so
+/// that non-ASCII commands and arguments aren't garbled, after saving the
+/// current one in `CMDLINK_CP`. cmd.exe reads batch files line by line in the
+/// current code page, so these come before any line that could contain
+/// non-ASCII text. `chcp` ends its output with a `.` in some languages, hence
+/// the delimiter.
+#[cfg(any(target_os = "windows", test))]
+const UTF8_CODE_PAGE: &str =
+	"for /f \"tokens=2 delims=:.\" %%c in ('chcp') do set /a \"CMDLINK_CP=%%c\"\nchcp 65001 >nul\n";
+
+/// The command ending a Windows link with an exit code of 1 when one of its
+/// checks fails, going through the end of [batch_script] so the code page is
+/// restored on the way out.
+#[cfg(any(target_os = "windows", test))]
+const BATCH_FAIL: &str = "(set \"CMDLINK_STATUS=1\" & goto cmdlink_exit)";
+
+/// The extension of the copies a [LinkBackup] keeps of link files, named
+/// `.<link>.bak` next to them.
+const BACKUP_EXTENSION: &str = "bak";
+
+/// The extension of the temporary files links are written to before they
+/// replace the link file, named `.<link>.tmp` next to it.
+const TEMP_EXTENSION: &str = "tmp";
+
+/// The number of previous capture files kept by [CaptureMode::Rotate].
+const ROTATED_CAPTURES: usize = 5;
 
-#[derive(Debug, Clone, Copy)]
-#[allow(dead_code)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// What the links of a config have in common: the project directory they
+/// belong to, the directory they're stored in, and the settings and hooks
+/// baked into every one of them.
+pub struct LinkContext {
+	/// The project directory of the config.
+	paths: CmdlinkPaths,
+	/// The directory link files are stored in: the `bins_dir` setting, or
+	/// `bins` in the project directory.
+	bins_dir: PathBuf,
+	/// The `windows_extension` setting.
+	windows_extension: Option<WindowsExtension>,
+	/// The `run_history` setting.
+	run_history: bool,
+	/// The `pre_exec` hook.
+	pre_exec: Option<String>,
+	/// The `post_exec` hook.
+	post_exec: Option<String>,
+}
+
+impl Default for LinkContext {
+	/// The links of an empty config in the project directory in use, see
+	/// [paths::get].
+	fn default() -> Self { LinkContext::new(paths::get().clone(), &Settings::default(), &Hooks::default()) }
+}
+
+impl LinkContext {
+	/// The links of a config in the given project directory with the given
+	/// settings and hooks.
+	pub fn new(paths: CmdlinkPaths, settings: &Settings, hooks: &Hooks) -> Self {
+		LinkContext {
+			bins_dir: settings.bins_dir.clone().unwrap_or_else(|| paths.default_bins_dir()),
+			paths,
+			windows_extension: settings.windows_extension,
+			run_history: settings.run_history.unwrap_or_default(),
+			pre_exec: hooks.pre_exec.clone(),
+			post_exec: hooks.post_exec.clone(),
+		}
+	}
+
+	/// The links of the config in the project directory in use, reading only
+	/// the `[settings]` and `[hooks]` tables of its config file, for commands
+	/// that need to know where links go without loading the whole config.
+	pub fn detect() -> Self {
+		let paths = paths::get();
+		let (settings, hooks) = config::file_tables(paths);
+		LinkContext::new(paths.clone(), &settings, &hooks)
+	}
+
+	/// The project directory the links belong to.
+	pub fn paths(&self) -> &CmdlinkPaths { &self.paths }
+
+	/// The directory link files are stored in.
+	pub fn bins_dir(&self) -> &Path { &self.bins_dir }
+
+	/// The extension of link files on the current platform. On Windows, this
+	/// is the alias' own extension if given, or else the `windows_extension`
+	/// setting.
+	pub fn extension(&self, windows: Option<WindowsExtension>) -> &'static str {
+		if cfg!(target_os = "windows") {
+			windows.or(self.windows_extension).unwrap_or_default().as_str()
+		} else {
+			""
+		}
+	}
+
+	/// The path of the link file for the given alias, for aliases that don't
+	/// override the extension.
+	pub fn link_path(&self, alias: &str) -> PathBuf { self.bins_dir.join(format!("{alias}{}", self.extension(None))) }
+
+	/// The names of the files in the bins directory, read in a single pass so
+	/// that the links of many aliases can be checked without a stat each.
+	/// Symlinks whose target is gone are left out, as [Path::exists] would
+	/// count them as missing. Returns `None` if the directory exists but can't
+	/// be read.
+	pub fn present_links(&self) -> Option<HashSet<String>> {
+		let entries = match std::fs::read_dir(&self.bins_dir) {
+			Ok(entries) => entries,
+			Err(e) if e.kind() == ErrorKind::NotFound => return Some(HashSet::new()),
+			Err(e) => {
+				debug!("Failed to read the bins directory: {}", e);
+				return None;
+			},
+		};
+		let mut present = HashSet::new();
+		for entry in entries {
+			let entry = entry.ok()?;
+			let dangling = entry.file_type().is_ok_and(|kind| kind.is_symlink()) && !entry.path().exists();
+			if let Some(name) = link_name(&entry.path()).filter(|_| !dangling) {
+				present.insert(name);
+			}
+		}
+		Some(present)
+	}
+}
+
+/// The directory link files are stored in for the project directory in use,
+/// see [LinkContext::detect].
+pub fn bins_dir() -> PathBuf { LinkContext::detect().bins_dir }
+
+/// Returns the alias name a file in the `bins` directory would belong to, or
+/// `None` if the file name doesn't follow the link naming scheme.
+pub fn alias_of(path: &Path) -> Option<&str> {
+	let name = path.file_name()?.to_str()?;
+	LINK_EXTENSIONS.iter().find_map(|extension| {
+		let (alias, ext) = name.split_at_checked(name.len().checked_sub(extension.len())?)?;
+		ext.eq_ignore_ascii_case(extension).then_some(alias)
+	})
+}
+
+/// Whether or not a file in the bins directory is a backup or temporary copy of
+/// a link file, which belongs to cmdlink rather than any alias or script.
+pub fn is_link_copy(path: &Path) -> bool {
+	path.file_name().and_then(|name| name.to_str()).is_some_and(|name| {
+		name.starts_with('.')
+			&& Path::new(name)
+				.extension()
+				.is_some_and(|extension| extension == BACKUP_EXTENSION || extension == TEMP_EXTENSION)
+	})
+}
+
+#[derive(Default, Debug, Clone, Copy)]
+/// The pending action for a [PlatformBinary], applied when the owning config is
+/// saved.
 pub enum Action {
+	/// Create the link file, failing if it already exists.
 	Create,
+	/// Overwrite the link file with fresh contents.
 	Update,
+	/// Remove the link file.
 	Remove,
+	/// Leave the link file untouched.
+	#[default]
 	None,
 }
 
-#[derive(Debug)]
-/// A struct representing a platform-specific binary/link. These are created and
-/// managed by the `Config` struct to create aliases for commands.
-pub struct PlatformBinary {
-	/// Whether or not the platform binary file exists at it's expected path.
-	exists: bool,
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// The health of an alias' link, as shown by `display`.
+pub enum LinkStatus {
+	/// The link file exists.
+	Ok,
+	/// The link file is missing.
+	Broken,
+	/// The link file exists, but the program it runs can't be found, see
+	/// [PlatformBinary::target_found].
+	Unresolved,
+	/// The alias is disabled, so no link file is expected.
+	Disabled,
+}
+
+impl LinkStatus {
+	/// Whether the alias can't run as it is, as its link is missing or the
+	/// program it runs can't be found.
+	pub fn is_broken(self) -> bool { matches!(self, LinkStatus::Broken | LinkStatus::Unresolved) }
+
+	/// The symbol standing for the status in the `display` table.
+	pub fn icon(self) -> &'static str {
+		match self {
+			LinkStatus::Ok => "✔",
+			LinkStatus::Broken => "✘",
+			LinkStatus::Unresolved => "!",
+			LinkStatus::Disabled => "–",
+		}
+	}
+}
+
+impl std::fmt::Display for LinkStatus {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(match self {
+			LinkStatus::Ok => "ok",
+			LinkStatus::Broken => "broken",
+			LinkStatus::Unresolved => "target not found",
+			LinkStatus::Disabled => "disabled",
+		})
+	}
+}
+
+/// Commands built into the shells links run in, which aren't found on PATH.
+const SHELL_BUILTINS: &[&str] = &[
+	".", ":", "[", "alias", "cd", "command", "echo", "eval", "exec", "exit", "export", "false", "printf", "pwd",
+	"read", "set", "source", "test", "time", "trap", "true", "type", "ulimit", "umask", "unset", "wait",
+	// cmd.exe
+	"assoc", "call", "chdir", "cls", "copy", "date", "del", "dir", "erase", "ftype", "md", "mkdir", "mklink", "move",
+	"popd", "pushd", "rd", "ren", "rename", "rmdir", "start", "title", "ver", "vol",
+];
+
+#[derive(Default, Debug)]
+/// The link state of an alias, owned by its [AliasValues].
+pub struct LinkState {
 	/// The action to be taken for the platform binary file, see [Action]
-	action: Action,
+	pub(crate) action: Action,
+	/// Whether or not the platform binary file exists at it's expected path,
+	/// checked on first access.
+	pub(crate) exists: OnceLock<bool>,
+	/// Whether or not the program the link runs can be found, checked on
+	/// first access.
+	pub(crate) target_found: OnceLock<bool>,
+	/// The link files of the aliases referenced by the alias' command, see
+	/// [chain](crate::chain).
+	pub(crate) chained: BTreeMap<String, PathBuf>,
+	/// The defaults the alias inherits from its group, if it has one.
+	pub(crate) group: Option<Group>,
+}
+
+#[derive(Debug, Default)]
+/// Copies of the link files about to be changed by a batch of link actions,
+/// kept next to them in the bins directory, so that the batch can be undone
+/// as a whole if any part of it fails.
+pub struct LinkBackup {
+	/// The link files that existed, with the paths of their copies.
+	saved: Vec<(PathBuf, PathBuf)>,
+	/// The link files that didn't exist yet.
+	created: Vec<PathBuf>,
+}
+
+impl LinkBackup {
+	/// Backs up the link files of the given links.
+	pub fn new(links: &[PlatformBinary]) -> Result<Self> {
+		let mut backup = LinkBackup::default();
+		for link in links {
+			let path = link.file_path();
+			let copy = link
+				.context
+				.bins_dir
+				.join(format!(".{}{}.{BACKUP_EXTENSION}", link.alias, link.extension()));
+			match copy_link(path, &copy) {
+				Ok(()) => backup.saved.push((path.to_path_buf(), copy)),
+				Err(e) if e.kind() == ErrorKind::NotFound => backup.created.push(path.to_path_buf()),
+				Err(e) => {
+					backup.discard();
+					return Err(Error::LinkBackup(path.to_path_buf(), e));
+				},
+			}
+		}
+		Ok(backup)
+	}
+
+	/// Puts the backed up link files back in place, and removes the ones that
+	/// didn't exist before. Links that can't be restored are reported, but
+	/// don't stop the others from being restored.
+	pub fn restore(self) {
+		let restored = self
+			.saved
+			.iter()
+			.map(|(path, copy)| (path, std::fs::rename(copy, path)))
+			.chain(self.created.iter().map(|path| {
+				let removed = std::fs::remove_file(path).or_else(|e| match e.kind() {
+					ErrorKind::NotFound => Ok(()),
+					_ => Err(e),
+				});
+				(path, removed)
+			}));
+		for (path, result) in restored {
+			if let Err(e) = result {
+				warn!("{}", tr!("config.restore_failed", path = path.display(), error = e));
+			}
+		}
+		if !self.saved.is_empty() || !self.created.is_empty() {
+			warn!("{}", tr!("config.restored"));
+		}
+	}
+
+	/// Removes the copies once the batch succeeded.
+	pub fn discard(self) {
+		for (_, copy) in &self.saved {
+			let _ = std::fs::remove_file(copy);
+		}
+	}
+}
+
+/// The name of a link file as compared by [LinkContext::present_links],
+/// lowercased on Windows where file names are case-insensitive.
+fn link_name(path: &Path) -> Option<String> {
+	let name = path.file_name()?.to_string_lossy();
+	Some(match cfg!(target_os = "windows") {
+		true => name.to_lowercase(),
+		false => name.into_owned(),
+	})
+}
+
+#[derive(Debug)]
+/// A struct representing a platform-specific binary/link. These are created on
+/// demand by the `Config` struct as a view over an alias and its values.
+pub struct PlatformBinary<'a> {
 	/// The alias for the platform binary.
-	alias: String,
-	/// The command to run in place of the alias.
-	cmd: String,
+	alias: &'a str,
+	/// The values of the alias, including its link state.
+	values: &'a AliasValues,
+	/// What the link has in common with the other links of its config.
+	context: &'a LinkContext,
+	/// The path of the link file.
+	file_path: PathBuf,
 }
 
-impl PlatformBinary {
-	pub fn new(alias: String, cmd: String, action: Action) -> Self {
-		let mut p = PlatformBinary {
+impl<'a> PlatformBinary<'a> {
+	/// Creates a new platform binary view for the given alias.
+	pub fn new(alias: &'a str, values: &'a AliasValues, context: &'a LinkContext) -> Self {
+		PlatformBinary {
 			alias,
-			cmd,
-			exists: false,
-			action,
-		};
-		p.validate();
-		p
+			values,
+			context,
+			file_path: context
+				.bins_dir
+				.join(format!("{alias}{}", context.extension(values.windows_extension))),
+		}
 	}
 
-	/// Validates the existence of the platform binary file.
-	#[inline]
-	fn validate(&mut self) { self.exists = self.file_path().exists(); }
+	/// The paths the alias' link file would have with the other extensions,
+	/// left behind when the extension changes.
+	fn stale_paths(&self) -> impl Iterator<Item = PathBuf> + '_ {
+		LINK_EXTENSIONS
+			.iter()
+			.filter(|extension| **extension != self.extension())
+			.map(|extension| self.context.bins_dir.join(format!("{}{extension}", self.alias)))
+	}
+
+	/// Removes the link files the alias had with other extensions, if any.
+	fn remove_stale(&self) -> std::io::Result<()> {
+		for path in self.stale_paths() {
+			match std::fs::remove_file(&path) {
+				Ok(()) => debug!("Removed stale link {}", path.display()),
+				Err(e) if e.kind() != ErrorKind::NotFound => return Err(e),
+				Err(_) => {},
+			}
+		}
+		Ok(())
+	}
 
-	/// Determines whether or not the platform binary file exists.
+	/// The defaults the alias inherits from its group, if it has one.
+	fn group(&self) -> Option<&Group> { self.values.link.group.as_ref() }
+
+	/// Determines whether or not the platform binary file exists. The
+	/// filesystem is only checked once per alias.
 	#[inline]
-	pub fn exists(&self) -> bool { self.exists }
+	pub fn exists(&self) -> bool { *self.values.link.exists.get_or_init(|| self.file_path.exists()) }
+
+	/// Whether the program the link runs can be found: the directory of a
+	/// `dir` alias, the cached binary of a `release`, or the program of the
+	/// command, looked up on PATH unless it's a path. Programs given by a
+	/// relative path, shell builtins and commands run through another `shell`
+	/// can't be checked, so they count as found, while commands referencing
+	/// undefined variables don't. The filesystem is only checked once per
+	/// alias.
+	pub fn target_found(&self) -> bool {
+		*self.values.link.target_found.get_or_init(|| {
+			if self.url().is_some() {
+				return true;
+			}
+			if let Some(dir) = self.dir() {
+				return self.expand_path(dir).is_ok_and(|dir| Path::new(&dir).is_dir());
+			}
+			if let Some(binary) = self.release_binary() {
+				return binary.is_file();
+			}
+			if self.shell().is_some() {
+				return true;
+			}
+			let Ok(cmd) = self.expand(self.cmd()).map(|cmd| chain::resolve(&cmd, self.chained())) else {
+				return false;
+			};
+			let Some(program) = describe::program(&cmd) else {
+				return true;
+			};
+			let program = self.expand_path(program).unwrap_or_else(|_| program.to_string());
+			let path = Path::new(&program);
+			if path.is_absolute() {
+				path.is_file()
+			} else {
+				path.components().count() > 1
+					|| program.contains('=')
+					|| SHELL_BUILTINS.contains(&program.to_ascii_lowercase().as_str())
+					|| which::find_executable(&program).is_some()
+			}
+		})
+	}
+
+	/// Records whether the link file exists from the file names listed by
+	/// [LinkContext::present_links], so that [PlatformBinary::exists] needn't
+	/// check the filesystem.
+	pub(crate) fn mark_exists(&self, present: &HashSet<String>) {
+		let exists = link_name(&self.file_path).is_some_and(|name| present.contains(&name));
+		let _ = self.values.link.exists.set(exists);
+	}
+
+	/// The program the link is a symlink to, if the alias asks for one with
+	/// `symlink` and needs nothing a script would wrap the command with: the
+	/// binary of its release, or its command if that's a lone program name,
+	/// looked up on PATH, or absolute path. Always `None` on Windows, where
+	/// links stay batch files.
+	pub fn symlink_target(&self) -> Option<PathBuf> {
+		if !cfg!(target_family = "unix") || !self.values.symlink || self.needs_script() {
+			return None;
+		}
+		if let Some(binary) = self.release_binary() {
+			return Some(binary);
+		}
+		let cmd = self.expand_path(self.cmd().trim()).ok()?;
+		if cmd.is_empty() || !cmd.chars().all(|c| c.is_alphanumeric() || "/._-+".contains(c)) {
+			return None;
+		}
+		match cmd.contains('/') {
+			true => Some(PathBuf::from(cmd)).filter(|path| path.is_absolute() && path.is_file()),
+			false => which::find_executable_outside(&cmd, &self.context.bins_dir),
+		}
+	}
+
+	/// Whether or not the alias uses anything the link script wraps its
+	/// command with, which a symlink can't do.
+	fn needs_script(&self) -> bool {
+		self.url().is_some()
+			|| self.dir().is_some()
+			|| self.min_version().is_some()
+			|| !self.env().is_empty()
+			|| !self.secrets().is_empty()
+			|| self.inherited_env().is_some_and(|env| !env.is_empty())
+			|| self.env_file().is_some()
+			|| self.cwd().is_some()
+			|| self.shell().is_some()
+			|| !self.forwards_args()
+			|| self.capture().is_some()
+			|| self.nice().is_some()
+			|| self.pre_exec().is_some()
+			|| self.post_exec().is_some()
+			|| self.records_runs()
+			|| !self.chained().is_empty()
+	}
+
+	/// Determines whether or not the link file on disk differs from the
+	/// contents that would currently be generated for it, or from the program
+	/// it would be a symlink to.
+	pub fn is_outdated(&self) -> Result<bool> {
+		if let Some(target) = self.symlink_target() {
+			return Ok(std::fs::read_link(self.file_path()).map_or(true, |actual| actual != target));
+		}
+		if self.file_path.is_symlink() {
+			return Ok(true);
+		}
+		let expected = self.contents()?;
+		match std::fs::read_to_string(self.file_path()) {
+			Ok(actual) => Ok(actual != expected),
+			Err(e) if e.kind() == ErrorKind::NotFound => Ok(true),
+			Err(e) => Err(Error::LinkRead(self.alias().to_string(), self.file_path.clone(), e)),
+		}
+	}
 
 	/// Determins the action to take for the binary.
 	#[inline]
-	pub fn action(&self) -> Action { self.action }
+	pub fn action(&self) -> Action { self.values.link.action }
 
 	/// Performs the appropriate action based on the platform binary's action.
+	///
+	/// On Windows, antivirus scanners and running batch files hold link files
+	/// open for a moment, so updates and removals that fail because the file
+	/// is locked are retried a few times, backing off in between.
 	pub fn perform_action(&self) -> Result<()> {
-		match self.action {
+		let mut delay = LOCK_BACKOFF;
+		for _ in 0..LOCK_RETRIES {
+			match self.perform_action_once() {
+				Err(e) if is_locked(&e) => {
+					debug!("Link for alias {} is locked, retrying in {:?}", self.alias, delay);
+					std::thread::sleep(delay);
+					delay *= 2;
+				},
+				result => return result,
+			}
+		}
+		self.perform_action_once().map_err(|e| {
+			if is_locked(&e) {
+				Error::LinkLocked(self.alias().to_string(), self.file_path.clone())
+			} else {
+				e
+			}
+		})
+	}
+
+	/// Performs the action once.
+	fn perform_action_once(&self) -> Result<()> {
+		match self.action() {
 			Action::Create => self.create_link(),
 			Action::Update => self.update_link(),
 			Action::Remove => self.remove_link(),
@@ -64,46 +577,215 @@ impl PlatformBinary {
 		}
 	}
 
-	/// Sets the action for the platform binary.
-	pub fn set_action(&mut self, action: Action) { self.action = action; }
-
 	/// Creates a link, returning an error if the link already exists.
 	fn create_link(&self) -> Result<()> {
+		self.check_path_length()?;
+		self.fetch_release()?;
 		let file_path = self.file_path();
-		let mut file = File::create_new(file_path).map_err(|e| {
+		let error = |e| Error::LinkCreation(self.alias().to_string(), self.file_path.clone(), e);
+		let already_exists = |e: std::io::Error| {
 			if e.kind() == ErrorKind::AlreadyExists {
-				Error::LinkAlreadyExists(self.alias().to_string())
+				Error::LinkAlreadyExists(self.alias().to_string(), self.file_path.clone())
 			} else {
-				Error::LinkCreation(self.alias().to_string(), e)
-			}
-		})?;
-		file.write_all(self.contents().as_bytes())
-			.map_err(|e| Error::LinkCreation(self.alias().to_string(), e))?;
-		
-		#[cfg(target_family = "unix")]
-		Command::new("chmod")
-			.arg("+x")
-			.arg(file_path)
-			.status()
-			.map_err(|e| Error::LinkCreation(self.alias().to_string(), e))?;
-		Ok(())
+				error(e)
+			}
+		};
+		// A symlink whose program is gone counts as a missing link, so it's
+		// replaced.
+		if file_path.is_symlink() && !file_path.exists() {
+			std::fs::remove_file(file_path).map_err(error)?;
+		}
+		if let Some(target) = self.symlink_target() {
+			symlink(&target, file_path).map_err(already_exists)?;
+			return self.remove_stale().and_then(|_| self.sync_app_path()).map_err(error);
+		}
+		let contents = self.contents()?;
+		let mut file = File::create_new(file_path).map_err(already_exists)?;
+		file.write_all(contents.as_bytes()).map_err(error)?;
+
+		make_executable(file_path)
+			.and_then(|_| self.remove_stale())
+			.and_then(|_| self.sync_app_path())
+			.map_err(|e| Error::LinkCreation(self.alias().to_string(), self.file_path.clone(), e))
 	}
 
-	/// Updates the link with the new contents
+	/// Updates the link with the new contents.
+	///
+	/// The link may be running while it's updated, and both cmd.exe and `sh`
+	/// read scripts incrementally, so overwriting it in place could have a
+	/// running invocation read parts of both versions. The contents are
+	/// written to a temporary file next to the link instead, which then
+	/// replaces the link in a single rename. Symlinks are replaced the same
+	/// way.
 	fn update_link(&self) -> Result<()> {
-		std::fs::write(self.file_path(), self.contents()).map_err(|e| Error::LinkUpdate(self.alias().to_string(), e))
+		self.check_path_length()?;
+		self.fetch_release()?;
+		let target = self.symlink_target();
+		let contents = match target {
+			Some(_) => String::new(),
+			None => self.contents()?,
+		};
+		let temp_path = self
+			.context
+			.bins_dir
+			.join(format!(".{}{}.{TEMP_EXTENSION}", self.alias, self.extension()));
+		// A symlink left behind by an interrupted update would otherwise have
+		// the contents written to the program it points to.
+		let _ = std::fs::remove_file(&temp_path);
+		let written = match &target {
+			Some(target) => symlink(target, &temp_path),
+			None => std::fs::write(&temp_path, contents).and_then(|_| make_executable(&temp_path)),
+		};
+		let replaced = written.and_then(|_| std::fs::rename(&temp_path, self.file_path()));
+		if replaced.is_err() {
+			let _ = std::fs::remove_file(&temp_path);
+		}
+		replaced
+			.and_then(|_| self.remove_stale())
+			.and_then(|_| self.sync_app_path())
+			.map_err(|e| Error::LinkUpdate(self.alias().to_string(), self.file_path.clone(), e))
+	}
+
+	/// Downloads the binary of the alias' release into the cache, unless it's
+	/// there already, so that the link doesn't point at a missing file.
+	fn fetch_release(&self) -> Result<()> {
+		let Some(release) = self.values.release.as_deref().and_then(Release::parse) else {
+			return Ok(());
+		};
+		release.fetch(
+			&self.context.paths,
+			self.values.asset.as_deref(),
+			self.values.bin.as_deref(),
+			self.values.sha256.as_deref(),
+		)?;
+		Ok(())
+	}
+
+	/// Returns an error if the link's path is too long to be run on Windows,
+	/// rather than leaving a link that fails with a cryptic IO error. A no-op
+	/// elsewhere.
+	fn check_path_length(&self) -> Result<()> {
+		#[cfg(target_os = "windows")]
+		{
+			use std::os::windows::ffi::OsStrExt;
+
+			if self.file_path.as_os_str().encode_wide().count() > MAX_LINK_PATH {
+				return Err(Error::LinkPathTooLong(self.alias().to_string(), self.file_path.clone()));
+			}
+		}
+		Ok(())
+	}
+
+	/// Registers the link under `App Paths` if the alias asks for it, and
+	/// unregisters it otherwise. Windows only, this is a no-op elsewhere.
+	fn sync_app_path(&self) -> std::io::Result<()> {
+		if !self.values.app_path {
+			return self.unregister_app_path();
+		}
+		#[cfg(target_os = "windows")]
+		{
+			use windows_registry::CURRENT_USER;
+
+			let key = CURRENT_USER.create(app_path_key(self.alias))?;
+			if registered_elsewhere(&key, &self.context.bins_dir) {
+				return Err(std::io::Error::new(
+					ErrorKind::AlreadyExists,
+					format!("App Paths already has an entry for {}.exe", self.alias),
+				));
+			}
+			key.set_string("", &self.file_path.display().to_string())?;
+			key.set_string("Path", &self.context.bins_dir.display().to_string())?;
+		}
+		Ok(())
+	}
+
+	/// Removes the alias' `App Paths` entry, if it points into the bins
+	/// directory. Entries of installed programs with the same name are left
+	/// alone.
+	fn unregister_app_path(&self) -> std::io::Result<()> {
+		#[cfg(target_os = "windows")]
+		{
+			use windows_registry::CURRENT_USER;
+
+			let Ok(key) = CURRENT_USER.open(app_path_key(self.alias)) else {
+				return Ok(());
+			};
+			if key.get_string("").is_ok() && !registered_elsewhere(&key, &self.context.bins_dir) {
+				CURRENT_USER.remove_tree(app_path_key(self.alias))?;
+			}
+		}
+		Ok(())
 	}
 
-	/// Removes the link, returning an error if the link does not exist.
+	/// Removes a link, including any link files left with other extensions.
+	/// Links that are already gone, such as those of disabled aliases, are not
+	/// an error.
 	fn remove_link(&self) -> Result<()> {
-		std::fs::remove_file(self.file_path()).map_err(|e| Error::LinkUpdate(self.alias().to_string(), e))
+		match std::fs::remove_file(self.file_path()) {
+			Err(e) if e.kind() != ErrorKind::NotFound => {
+				Err(Error::LinkRemoval(self.alias().to_string(), self.file_path.clone(), e))
+			},
+			_ => self
+				.remove_stale()
+				.and_then(|_| self.unregister_app_path())
+				.map_err(|e| Error::LinkRemoval(self.alias().to_string(), self.file_path.clone(), e)),
+		}
 	}
 }
 
-impl Link for PlatformBinary {
-	fn alias(&self) -> &str { self.alias.as_str() }
+impl Link for PlatformBinary<'_> {
+	fn alias(&self) -> &str { self.alias }
+
+	fn cmd(&self) -> &str { self.values.effective_cmd() }
+
+	fn url(&self) -> Option<&str> { self.values.url.as_deref() }
+
+	fn dir(&self) -> Option<&str> { self.values.dir.as_deref() }
+
+	fn activate(&self) -> Option<&str> { self.values.activate.as_deref() }
+
+	fn release_binary(&self) -> Option<PathBuf> {
+		let release = Release::parse(self.values.release.as_deref()?)?;
+		Some(release.binary_path(&self.context.paths, self.values.bin.as_deref()))
+	}
+
+	fn nice(&self) -> Option<i8> { self.values.niceness() }
+
+	fn gui(&self) -> bool { self.values.gui }
+
+	fn env(&self) -> &BTreeMap<String, String> { &self.values.env }
+
+	fn secrets(&self) -> &BTreeMap<String, String> { &self.values.secrets }
 
-	fn cmd(&self) -> &str { self.cmd.as_str() }
+	fn chained(&self) -> &BTreeMap<String, PathBuf> { &self.values.link.chained }
+
+	fn inherited_env(&self) -> Option<&BTreeMap<String, String>> { self.group().map(|group| &group.env) }
+
+	fn cwd(&self) -> Option<&str> { self.values.cwd.as_deref().or_else(|| self.group()?.cwd.as_deref()) }
+
+	fn shell(&self) -> Option<&str> { self.values.shell.as_deref().or_else(|| self.group()?.shell.as_deref()) }
+
+	fn min_version(&self) -> Option<&MinVersion> { self.values.min_version.as_ref() }
+
+	fn forwards_args(&self) -> bool { self.values.forward_args.unwrap_or(true) }
+
+	fn env_file(&self) -> Option<&str> { self.values.env_file.as_deref() }
+
+	fn pre_exec(&self) -> Option<&str> { self.context.pre_exec.as_deref() }
+
+	fn post_exec(&self) -> Option<&str> { self.context.post_exec.as_deref() }
+
+	fn records_runs(&self) -> bool { self.context.run_history }
+
+	fn capture(&self) -> Option<&str> { self.values.capture.as_deref() }
+
+	fn capture_mode(&self) -> CaptureMode { self.values.capture_mode.unwrap_or_default() }
+
+	fn extension(&self) -> &str { self.context.extension(self.values.windows_extension) }
+
+	fn file_path(&self) -> &Path { &self.file_path }
+
+	fn project_dir(&self) -> &Path { self.context.paths.root() }
 }
 
 /// Helper trait to abstract platform-specific link functionality.
@@ -112,38 +794,822 @@ pub trait Link {
 	fn alias(&self) -> &str;
 	/// Getter for the command.
 	fn cmd(&self) -> &str;
-	/// The extension of the link file.
+	/// Getter for the web page opened instead of running the command.
 	#[inline]
-	fn extension(&self) -> &str {
-		if cfg!(target_os = "windows") {
-			".bat"
-		} else {
-			// No extension for Unix/Linux, so that users don't have to type
-			// the extension.
-			""
+	fn url(&self) -> Option<&str> { None }
+	/// Getter for the directory the shell changes to instead of running the
+	/// command.
+	#[inline]
+	fn dir(&self) -> Option<&str> { None }
+	/// Getter for the command run in the shell after changing to the
+	/// directory.
+	#[inline]
+	fn activate(&self) -> Option<&str> { None }
+	/// Getter for the cached binary of the release run instead of the
+	/// command.
+	#[inline]
+	fn release_binary(&self) -> Option<PathBuf> { None }
+	/// Getter for the `nice` value the command should be run with.
+	fn nice(&self) -> Option<i8>;
+	/// Whether or not the command is a GUI app, which the Windows link starts
+	/// without waiting for it.
+	fn gui(&self) -> bool;
+	/// Getter for the environment variables exported before the command runs.
+	fn env(&self) -> &BTreeMap<String, String>;
+	/// Getter for the keychain secret references exported before the command
+	/// runs.
+	fn secrets(&self) -> &BTreeMap<String, String>;
+	/// Getter for the link files of the aliases referenced as `@name` by the
+	/// command, see [chain].
+	fn chained(&self) -> &BTreeMap<String, PathBuf>;
+	/// Getter for the environment variables inherited from the alias' group,
+	/// exported unless the alias sets them itself.
+	#[inline]
+	fn inherited_env(&self) -> Option<&BTreeMap<String, String>> { None }
+	/// Getter for the file of `KEY=VALUE` lines loaded before the command runs.
+	#[inline]
+	fn env_file(&self) -> Option<&str> { None }
+	/// Getter for the directory the command runs in.
+	#[inline]
+	fn cwd(&self) -> Option<&str> { None }
+	/// Getter for the shell the command runs through on Unix.
+	#[inline]
+	fn shell(&self) -> Option<&str> { None }
+	/// Getter for the version of the tool the command needs.
+	#[inline]
+	fn min_version(&self) -> Option<&MinVersion> { None }
+	/// Whether or not the link passes its arguments on to the command.
+	#[inline]
+	fn forwards_args(&self) -> bool { true }
+	/// Getter for the shell snippet run before the command, from the
+	/// `pre_exec` hook.
+	#[inline]
+	fn pre_exec(&self) -> Option<&str> { None }
+	/// Getter for the shell snippet run after the command, from the
+	/// `post_exec` hook.
+	#[inline]
+	fn post_exec(&self) -> Option<&str> { None }
+	/// Whether or not the link records the exit code and duration of its runs
+	/// through `cmdlink record`, as enabled by the `run_history` setting.
+	#[inline]
+	fn records_runs(&self) -> bool { false }
+	/// Getter for the file the output of the command is copied to.
+	#[inline]
+	fn capture(&self) -> Option<&str> { None }
+	/// Getter for how the capture file is written.
+	#[inline]
+	fn capture_mode(&self) -> CaptureMode { CaptureMode::default() }
+	/// The extension of the link file, `.bat` or `.cmd` on Windows depending on
+	/// the `windows_extension` setting.
+	fn extension(&self) -> &str;
+	/// The file path of the link file.
+	fn file_path(&self) -> &Path;
+	/// The project directory the link belongs to, which `{cmdlink_dir}`
+	/// expands to.
+	fn project_dir(&self) -> &Path;
+
+	/// The command run with its priority lowered (or raised), if a `nice`
+	/// value is set. On Windows, `start` only runs programs, so the command is
+	/// run through `cmd /C` for builtins and command lists to work as well.
+	#[inline]
+	fn prioritized(&self, command: &str) -> String {
+		let Some(nice) = self.nice() else {
+			return command.to_string();
+		};
+
+		#[cfg(target_os = "windows")]
+		{
+			format!("start \"\" {} /B /WAIT cmd /C \"{command}\"", priority_class(nice))
+		}
+		#[cfg(target_os = "linux")]
+		{
+			// ionice's best-effort class is used so that the command is never
+			// starved of IO entirely.
+			let io_level = if nice > 0 { 7 } else { 4 };
+			format!("nice -n {nice} ionice -c 2 -n {io_level} {command}")
+		}
+		#[cfg(not(any(target_os = "windows", target_os = "linux")))]
+		{
+			format!("nice -n {nice} {command}")
 		}
 	}
-	/// The file path of the link file.
+
+	/// Expands the template variables in the input, see [template].
+	#[inline]
+	fn expand(&self, input: &str) -> Result<String> {
+		template::expand(input, self.project_dir())
+			.map_err(|var| Error::UndefinedVariable(self.alias().to_string(), var))
+	}
+
+	/// Expands a leading `~` to the home directory, and the template variables,
+	/// in a path.
+	#[inline]
+	fn expand_path(&self, path: &str) -> Result<String> {
+		match path.strip_prefix('~') {
+			Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => self.expand(&format!("{{home}}{rest}")),
+			_ => self.expand(path),
+		}
+	}
+
+	/// The lines exporting the alias' environment variables, including those
+	/// inherited from its group, each terminated by a newline. The values are
+	/// taken literally, without expanding any variables they mention.
+	#[inline]
+	fn env_lines(&self) -> Result<String> {
+		self.inherited_env()
+			.into_iter()
+			.flatten()
+			.filter(|(key, _)| !self.env().contains_key(*key))
+			.chain(self.env())
+			.map(|(key, value)| {
+				let value = self.expand(value)?;
+				#[cfg(target_os = "windows")]
+				{
+					Ok(batch_env_line(key, &value))
+				}
+				#[cfg(not(target_os = "windows"))]
+				{
+					Ok(format!("export {key}={}\n", sh_quote(&value)))
+				}
+			})
+			.collect()
+	}
+
+	/// The lines loading the alias' env file at invocation time, failing if it
+	/// can't be read. Blank lines and `#` comments are skipped, and values may
+	/// be wrapped in quotes, which are stripped.
+	#[inline]
+	fn env_file_lines(&self) -> Result<String> {
+		let Some(path) = self.env_file() else {
+			return Ok(String::new());
+		};
+		let path = self.expand_path(path)?;
+		#[cfg(target_os = "windows")]
+		{
+			Ok(batch_env_file_lines(&path))
+		}
+		#[cfg(not(target_os = "windows"))]
+		{
+			Ok(format!("env_file={}\n{ENV_FILE_LOADER}", sh_quote(&path)))
+		}
+	}
+
+	/// The lines retrieving the alias' secrets from the OS keychain through
+	/// `cmdlink secret` at invocation time, so the values are never written to
+	/// disk.
+	#[inline]
+	fn secret_lines(&self) -> Result<String> {
+		if self.secrets().is_empty() {
+			return Ok(String::new());
+		}
+		let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+		let exe = exe.display().to_string();
+
+		Ok(self
+			.secrets()
+			.iter()
+			.map(|(key, reference)| {
+				#[cfg(target_os = "windows")]
+				{
+					batch_secret_lines(&exe, key, reference)
+				}
+				#[cfg(not(target_os = "windows"))]
+				{
+					format!(
+						"{key}=\"$({} secret {})\" || exit 1\nexport {key}\n",
+						sh_quote(&exe),
+						sh_quote(reference)
+					)
+				}
+			})
+			.collect())
+	}
+
+	/// The line changing to the directory the command runs in, failing if it
+	/// doesn't exist. Empty if the alias has no working directory. On Windows,
+	/// the calling session gets its directory back from the `endlocal` of
+	/// [batch_script].
+	#[inline]
+	fn cwd_lines(&self) -> Result<String> {
+		let Some(cwd) = self.cwd() else {
+			return Ok(String::new());
+		};
+		let cwd = self.expand_path(cwd)?;
+		#[cfg(target_os = "windows")]
+		{
+			Ok(batch_cwd_line(&cwd))
+		}
+		#[cfg(not(target_os = "windows"))]
+		{
+			Ok(format!("cd {} || exit 1\n", sh_quote(&cwd)))
+		}
+	}
+
+	/// The line checking the version of the alias' tool through
+	/// `cmdlink check-version`, failing if it doesn't meet the alias'
+	/// `min_version`. Empty if the alias has no version requirement.
+	#[inline]
+	fn version_check_lines(&self) -> Result<String> {
+		let Some(min_version) = self.min_version() else {
+			return Ok(String::new());
+		};
+		let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+		let (exe, alias) = (exe.display(), self.alias());
+		let cmd = self.expand(&min_version.cmd)?;
+		#[cfg(target_os = "windows")]
+		{
+			// Doubled quotes stay inside a quoted argument, for both cmd.exe and
+			// the argument parsing of cmdlink.
+			let escape = |text: &str| text.replace('%', "%%").replace('"', "\"\"");
+			Ok(format!(
+				"\"{exe}\" check-version \"{alias}\" \"{}\" \"{}\" || {BATCH_FAIL}\n",
+				escape(&min_version.require),
+				escape(&cmd)
+			))
+		}
+		#[cfg(not(target_os = "windows"))]
+		{
+			Ok(format!(
+				"{} check-version {} {} {} || exit 1\n",
+				sh_quote(&exe.to_string()),
+				sh_quote(alias),
+				sh_quote(&min_version.require),
+				sh_quote(&cmd)
+			))
+		}
+	}
+
+	/// The arguments appended to the command, passing the link's arguments on
+	/// unless the alias opted out with `forward_args = false`.
+	#[inline]
+	fn args(&self) -> &'static str {
+		match self.forwards_args() {
+			false => "",
+			#[cfg(target_os = "windows")]
+			true => " %*",
+			#[cfg(not(target_os = "windows"))]
+			true => " \"$@\"",
+		}
+	}
+
+	/// The command run through the alias' shell, if it has one, with the
+	/// link's arguments passed on. Windows links always run the command in
+	/// cmd.exe, so the shell is ignored there.
+	#[inline]
+	fn through_shell(&self, cmd: String) -> String {
+		#[cfg(not(target_os = "windows"))]
+		if let Some(shell) = self.shell() {
+			return format!(
+				"{shell} -c {} {}",
+				sh_quote(&format!("{cmd}{}", self.args())),
+				sh_quote(self.alias())
+			);
+		}
+		cmd
+	}
+
+	/// The lines run right before the command: the `pre_exec` snippet, and
+	/// the start time of the command if runs are recorded, each terminated by a
+	/// newline.
+	#[inline]
+	fn pre_exec_lines(&self) -> Result<String> {
+		let mut lines = String::new();
+		if let Some(snippet) = self.pre_exec() {
+			lines.push_str(&self.expand(snippet)?);
+			lines.push('\n');
+		}
+		if self.records_runs() {
+			#[cfg(target_os = "windows")]
+			lines.push_str(&set_time_of_day("CMDLINK_START", ""));
+			#[cfg(not(target_os = "windows"))]
+			lines.push_str("CMDLINK_START=$(date +%s)\n");
+		}
+		Ok(lines)
+	}
+
+	/// Whether or not anything runs after the command: restoring the code page
+	/// on Windows, recording the run, or the `post_exec` snippet.
+	#[inline]
+	fn runs_after(&self) -> bool { cfg!(target_os = "windows") || self.post_exec().is_some() || self.records_runs() }
+
+	/// The lines running the command, already given the link's
+	/// [arguments](Link::args), copying its output to the capture file if there
+	/// is one. If anything [runs after](Link::runs_after) it, its exit code is
+	/// kept in `CMDLINK_STATUS`.
+	///
+	/// `sh` has no portable way to get the exit code of the first command of a
+	/// pipeline, so it's passed around `tee` through file descriptor 3. cmd.exe
+	/// has no `tee` at all, so the output only goes to the file on Windows.
+	#[inline]
+	fn command_lines(&self, command: &str) -> Result<String> {
+		let Some(path) = self.capture() else {
+			#[cfg(target_os = "windows")]
+			return Ok(match self.runs_after() {
+				true => format!("{command}\nset \"CMDLINK_STATUS=%errorlevel%\""),
+				false => command.to_string(),
+			});
+			#[cfg(not(target_os = "windows"))]
+			return Ok(match self.runs_after() {
+				true => format!("{command}\nCMDLINK_STATUS=$?"),
+				// `exec` would keep the rest of a command list from running.
+				false if command.contains([';', '&', '|', '\n']) => command.to_string(),
+				false => format!("exec {command}"),
+			});
+		};
+		let path = self.expand_path(path)?;
+		let mode = self.capture_mode();
+		let dir = Path::new(&path).parent().filter(|dir| !dir.as_os_str().is_empty());
+		let mut lines = String::new();
+		#[cfg(target_os = "windows")]
+		{
+			if let Some(dir) = dir {
+				let _ = writeln!(lines, "if not exist \"{0}\" mkdir \"{0}\"", dir.display());
+			}
+			if mode == CaptureMode::Rotate {
+				for i in (1..ROTATED_CAPTURES).rev() {
+					let _ = writeln!(
+						lines,
+						"if exist \"{path}.{i}\" move /y \"{path}.{i}\" \"{path}.{}\" >nul",
+						i + 1
+					);
+				}
+				let _ = writeln!(lines, "if exist \"{path}\" move /y \"{path}\" \"{path}.1\" >nul");
+			}
+			let redirect = if mode == CaptureMode::Truncate { ">" } else { ">>" };
+			lines.push_str(&format!("{command} {redirect} \"{path}\" 2>&1"));
+			if self.runs_after() {
+				lines.push_str("\nset \"CMDLINK_STATUS=%errorlevel%\"");
+			}
+		}
+		#[cfg(not(target_os = "windows"))]
+		{
+			if let Some(dir) = dir {
+				let _ = writeln!(lines, "mkdir -p {}", sh_quote(&dir.display().to_string()));
+			}
+			if mode == CaptureMode::Rotate {
+				for i in (1..ROTATED_CAPTURES).rev() {
+					let (from, to) = (sh_quote(&format!("{path}.{i}")), sh_quote(&format!("{path}.{}", i + 1)));
+					let _ = writeln!(lines, "[ -e {from} ] && mv -f {from} {to}");
+				}
+				let (from, to) = (sh_quote(&path), sh_quote(&format!("{path}.1")));
+				let _ = writeln!(lines, "[ -e {from} ] && mv -f {from} {to}");
+			}
+			let append = if mode == CaptureMode::Truncate { "" } else { " -a" };
+			lines.push_str(&format!(
+				"exec 4>&1\nCMDLINK_STATUS=$( {{ {{ {command} 2>&1; echo $? >&3; }} | tee{append} {} >&4; }} 3>&1 )",
+				sh_quote(&path)
+			));
+			if !self.runs_after() {
+				lines.push_str("\nexit $CMDLINK_STATUS");
+			}
+		}
+		Ok(lines)
+	}
+
+	/// The lines run after the command, each preceded by a newline: recording
+	/// the run if runs are recorded, and the `post_exec` snippet, exiting with
+	/// the command's exit code afterwards on Unix. Empty if nothing
+	/// [runs after](Link::runs_after) the command.
 	#[inline]
-	fn file_path(&self) -> &'static Path {
-		Box::leak(
-			PROJECT_DIR
-				.join("bins")
-				.join(format!("{}{}", self.alias(), self.extension()))
-				.into_boxed_path(),
-		)
+	fn post_exec_lines(&self) -> Result<String> {
+		if !self.runs_after() {
+			return Ok(String::new());
+		}
+		let mut lines = String::new();
+		if self.records_runs() {
+			let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+			let (exe, alias) = (exe.display(), self.alias());
+			#[cfg(target_os = "windows")]
+			lines.push_str(&format!(
+				"\n{}if %CMDLINK_ELAPSED% lss 0 set /a \"CMDLINK_ELAPSED+=8640000\"\n\
+				 \"{exe}\" record \"{alias}\" %CMDLINK_STATUS% %CMDLINK_ELAPSED%0ms -- %*",
+				set_time_of_day("CMDLINK_ELAPSED", "-CMDLINK_START")
+			));
+			#[cfg(not(target_os = "windows"))]
+			lines.push_str(&format!(
+				"\n{} record {} \"$CMDLINK_STATUS\" \"$(($(date +%s) - CMDLINK_START))s\" -- \"$@\"",
+				sh_quote(&exe.to_string()),
+				sh_quote(alias)
+			));
+		}
+		if let Some(snippet) = self.post_exec() {
+			lines.push('\n');
+			lines.push_str(&self.expand(snippet)?);
+		}
+
+		// Windows links exit through [batch_script].
+		#[cfg(not(target_os = "windows"))]
+		lines.push_str("\nexit $CMDLINK_STATUS");
+		Ok(lines)
+	}
+
+	/// The command opening the alias' URL through `cmdlink open-url`, which
+	/// appends the arguments as query parameters.
+	#[inline]
+	fn open_url_cmd(&self, url: &str) -> Result<String> {
+		let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+		let url = self.expand(url)?;
+		#[cfg(target_os = "windows")]
+		{
+			// Percent encoded characters would be taken for batch parameters.
+			Ok(format!("\"{}\" open-url \"{}\"", exe.display(), url.replace('%', "%%")))
+		}
+		#[cfg(not(target_os = "windows"))]
+		{
+			Ok(format!("{} open-url {}", sh_quote(&exe.display().to_string()), sh_quote(&url)))
+		}
+	}
+
+	/// The contents of the link file of an alias jumping to a directory. A
+	/// process can't change the directory of the shell it's run from, so on
+	/// Unix the link prints the directory instead, e.g. for `cd "$(proj)"`,
+	/// while the shell function defined by the completion loader does the
+	/// actual jump. Batch files run in the cmd.exe session calling them, so on
+	/// Windows the link changes to it directly.
+	#[inline]
+	fn jump_contents(&self, dir: &str) -> Result<String> {
+		let dir = self.expand_path(dir)?;
+		#[cfg(target_os = "windows")]
+		{
+			let activate = match self.activate() {
+				Some(activate) => format!("{}\n", self.expand(activate)?),
+				None => String::new(),
+			};
+			Ok(format!("@echo off\ncd /d \"{dir}\" || exit /b 1\n{activate}"))
+		}
+		#[cfg(not(target_os = "windows"))]
+		{
+			Ok(format!("#!/bin/sh\nprintf '%s\\n' {}\n", sh_quote(&dir)))
+		}
 	}
 
 	/// The contents of the link file
 	#[inline]
-	fn contents(&self) -> String {
+	fn contents(&self) -> Result<String> {
+		if let Some(dir) = self.dir() {
+			return self.jump_contents(dir);
+		}
+		let cmd = match (self.url(), self.release_binary()) {
+			(Some(url), _) => self.open_url_cmd(url)?,
+			#[cfg(target_os = "windows")]
+			(None, Some(binary)) => format!("\"{}\"", binary.display()),
+			#[cfg(not(target_os = "windows"))]
+			(None, Some(binary)) => sh_quote(&binary.display().to_string()),
+			(None, None) => self.through_shell(chain::resolve(&self.expand(self.cmd())?, self.chained())),
+		};
 		#[cfg(target_os = "windows")]
 		{
-			format!("@echo off\necho.\n{} %*", self.cmd())
+			// GUI apps are started in the background so the console isn't
+			// blocked, so `start` is used on its own rather than
+			// through the priority prefix.
+			if self.gui() {
+				let class = self.nice().map(|nice| format!("{} ", priority_class(nice)));
+				return Ok(batch_script(&format!(
+					"{}{}{}{}{}{}{}{}",
+					self.env_file_lines()?,
+					self.env_lines()?,
+					self.secret_lines()?,
+					self.cwd_lines()?,
+					self.version_check_lines()?,
+					self.pre_exec_lines()?,
+					self.command_lines(&format!("start \"\" {}{}{}", class.unwrap_or_default(), cmd, self.args()))?,
+					self.post_exec_lines()?
+				)));
+			}
+			Ok(batch_script(&format!(
+				"echo.\n{}{}{}{}{}{}{}{}",
+				self.env_file_lines()?,
+				self.env_lines()?,
+				self.secret_lines()?,
+				self.cwd_lines()?,
+				self.version_check_lines()?,
+				self.pre_exec_lines()?,
+				self.command_lines(&self.prioritized(&format!("{cmd}{}", self.args())))?,
+				self.post_exec_lines()?
+			)))
 		}
 		#[cfg(any(target_os = "linux", target_os = "macos"))]
 		{
-			format!("#!/bin/sh\nexec {} \"$@\"", self.cmd())
+			Ok(format!(
+				"#!/bin/sh\n{}{}{}{}{}{}{}{}",
+				self.env_file_lines()?,
+				self.env_lines()?,
+				self.secret_lines()?,
+				self.cwd_lines()?,
+				self.version_check_lines()?,
+				self.pre_exec_lines()?,
+				self.command_lines(&self.prioritized(&format!("{cmd}{}", self.args())))?,
+				self.post_exec_lines()?
+			))
+		}
+	}
+}
+
+/// Whether the error is an update or removal of a link file that another
+/// process holds open, failing with ERROR_SHARING_VIOLATION or
+/// ERROR_LOCK_VIOLATION on Windows.
+fn is_locked(error: &Error) -> bool {
+	match error {
+		Error::LinkUpdate(.., e) | Error::LinkRemoval(.., e) => {
+			cfg!(target_os = "windows") && matches!(e.raw_os_error(), Some(32 | 33))
+		},
+		_ => false,
+	}
+}
+
+/// Creates a symlink at `path` pointing to the program at `target`. Links are
+/// only ever symlinks on Unix, so this fails elsewhere.
+fn symlink(target: &Path, path: &Path) -> std::io::Result<()> {
+	#[cfg(target_family = "unix")]
+	{
+		std::os::unix::fs::symlink(target, path)
+	}
+	#[cfg(not(target_family = "unix"))]
+	{
+		let _ = (target, path);
+		Err(std::io::Error::new(
+			ErrorKind::Unsupported,
+			"links can't be symlinks on this platform",
+		))
+	}
+}
+
+/// Copies a link file, or the symlink itself rather than the program it points
+/// to, replacing whatever is at `copy`.
+pub(crate) fn copy_link(path: &Path, copy: &Path) -> std::io::Result<()> {
+	// Writing through a symlink left at `copy` would change the program it
+	// points to.
+	let _ = std::fs::remove_file(copy);
+	if path.is_symlink() {
+		return symlink(&std::fs::read_link(path)?, copy);
+	}
+	std::fs::copy(path, copy).map(|_| ())
+}
+
+/// Marks a link file as executable on Unix. Windows relies on the file
+/// extension instead, so this is a no-op there.
+fn make_executable(path: &Path) -> std::io::Result<()> {
+	#[cfg(target_family = "unix")]
+	{
+		use std::os::unix::fs::PermissionsExt;
+
+		let mut permissions = std::fs::metadata(path)?.permissions();
+		permissions.set_mode(permissions.mode() | 0o111);
+		std::fs::set_permissions(path, permissions)?;
+	}
+	#[cfg(not(target_family = "unix"))]
+	let _ = path;
+	Ok(())
+}
+
+/// Quotes a word for a POSIX shell, in single quotes with any of its own
+/// closed, escaped and reopened.
+pub(crate) fn sh_quote(word: &str) -> String { format!("'{}'", word.replace('\'', r"'\''")) }
+
+/// The `start` priority class closest to the given `nice` value.
+#[cfg(target_os = "windows")]
+fn priority_class(nice: i8) -> &'static str {
+	match nice {
+		i8::MIN..=-10 => "/HIGH",
+		-9..=-1 => "/ABOVENORMAL",
+		0 => "/NORMAL",
+		1..=9 => "/BELOWNORMAL",
+		10.. => "/LOW",
+	}
+}
+
+/// The line setting a variable to the time of day in centiseconds plus the
+/// given `set /a` expression, used to time runs on Windows. `%time%` has a
+/// leading space before 10 o'clock and leading zeros in its fields, which
+/// `set /a` would read as octal, hence the `1xx-100` dance.
+#[cfg(target_os = "windows")]
+fn set_time_of_day(var: &str, offset: &str) -> String {
+	format!(
+		"for /f \"tokens=1-4 delims=:.,\" %%a in (\"%time: =0%\") do \
+		 set /a \"{var}=(((1%%a-100)*60+1%%b-100)*60+1%%c-100)*100+1%%d-100{offset}\"\n"
+	)
+}
+
+/// The Windows link running the given lines, which must set `CMDLINK_STATUS`.
+/// Batch files run in the cmd.exe session calling them, so the lines are
+/// wrapped in `setlocal` and `endlocal` to keep the variables they set, such as
+/// resolved secrets, from outliving the link. The code page is restored before
+/// exiting with the status, including when a check [fails](BATCH_FAIL).
+#[cfg(any(target_os = "windows", test))]
+fn batch_script(lines: &str) -> String {
+	format!(
+		"@echo off\nsetlocal\n{UTF8_CODE_PAGE}{lines}\n\
+		 :cmdlink_exit\nchcp %CMDLINK_CP% >nul\nendlocal & exit /b %CMDLINK_STATUS%"
+	)
+}
+
+/// The lines loading the env file at `path`, failing if it doesn't exist.
+#[cfg(any(target_os = "windows", test))]
+fn batch_env_file_lines(path: &str) -> String {
+	format!(
+		"if not exist \"{path}\" (echo cmdlink: cannot read env file {path} 1>&2& {BATCH_FAIL})\n\
+		 for /f \"usebackq eol=# tokens=1,* delims==\" %%a in (\"{path}\") do set \"%%a=%%~b\"\n"
+	)
+}
+
+/// The line setting the environment variable `key` to `value`, with `%`
+/// doubled so that cmd.exe doesn't expand variables in it.
+#[cfg(any(target_os = "windows", test))]
+fn batch_env_line(key: &str, value: &str) -> String { format!("set \"{key}={}\"\n", value.replace('%', "%%")) }
+
+/// The line changing to the directory `cwd`, failing if it doesn't exist.
+#[cfg(any(target_os = "windows", test))]
+fn batch_cwd_line(cwd: &str) -> String { format!("cd /d \"{cwd}\" || {BATCH_FAIL}\n") }
+
+/// The lines setting `key` to the secret `reference` resolves to through
+/// `cmdlink secret`, failing if it resolves to nothing. The variable is cleared
+/// first, so one set in the calling session doesn't pass for the secret.
+#[cfg(any(target_os = "windows", test))]
+fn batch_secret_lines(exe: &str, key: &str, reference: &str) -> String {
+	format!(
+		"set \"{key}=\"\n\
+		 for /f \"usebackq delims=\" %%s in (`\"{exe}\" secret \"{reference}\"`) do set \"{key}=%%s\"\n\
+		 if not defined {key} {BATCH_FAIL}\n"
+	)
+}
+
+/// The registry key of the `App Paths` entry for the alias.
+#[cfg(target_os = "windows")]
+fn app_path_key(alias: &str) -> String { format!(r"Software\Microsoft\Windows\CurrentVersion\App Paths\{alias}.exe") }
+
+/// Whether or not an `App Paths` entry points to a program outside the given
+/// bins directory, meaning it belongs to an installed program rather than
+/// cmdlink.
+#[cfg(target_os = "windows")]
+fn registered_elsewhere(key: &windows_registry::Key, bins_dir: &Path) -> bool {
+	key.get_string("")
+		.is_ok_and(|target| Path::new(&target).parent() != Some(bins_dir))
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn batch_script_keeps_secrets_local() {
+		let script = batch_script(&format!(
+			"{}cmd %*\nset \"CMDLINK_STATUS=%errorlevel%\"",
+			batch_secret_lines(r"C:\cmdlink.exe", "TOKEN", "api/token")
+		));
+		assert!(script.starts_with("@echo off\nsetlocal\n"));
+		assert!(script.ends_with("\nendlocal & exit /b %CMDLINK_STATUS%"));
+		let secret = script.find("set \"TOKEN=%%s\"").unwrap();
+		assert!(script.find("setlocal").unwrap() < secret && secret < script.find("endlocal").unwrap());
+	}
+
+	#[test]
+	fn batch_script_restores_the_directory() {
+		let script = batch_script(&format!(
+			"{}cmd\nset \"CMDLINK_STATUS=%errorlevel%\"",
+			batch_cwd_line(r"C:\work")
+		));
+		let cd = script.find(r#"cd /d "C:\work""#).unwrap();
+		assert!(script.find("\nsetlocal\n").unwrap() < cd && cd < script.find("\nendlocal & ").unwrap());
+	}
+
+	#[test]
+	fn batch_failures_restore_the_code_page() {
+		let lines = [
+			batch_env_file_lines(r"C:\.env"),
+			batch_secret_lines(r"C:\cmdlink.exe", "TOKEN", "api/token"),
+			batch_cwd_line(r"C:\work"),
+		];
+		let script = batch_script(&format!("{}cmd\nset \"CMDLINK_STATUS=%errorlevel%\"", lines.concat()));
+		assert!(!script.contains("exit /b 1"));
+		assert_eq!(script.matches("goto cmdlink_exit").count(), 3);
+		assert!(script.ends_with("\n:cmdlink_exit\nchcp %CMDLINK_CP% >nul\nendlocal & exit /b %CMDLINK_STATUS%"));
+	}
+
+	#[test]
+	fn batch_script_switches_to_utf8_before_non_ascii_commands() {
+		let cmd = "echo 打招呼 héllo";
+		let script = batch_script(&format!(
+			"{}{cmd} %*\nset \"CMDLINK_STATUS=%errorlevel%\"",
+			batch_env_line("GREETING", "¡hola!")
+		));
+		let chcp = script.find("\nchcp 65001 >nul\n").unwrap();
+		assert!(chcp < script.find("set \"GREETING=¡hola!\"\n").unwrap());
+		assert!(chcp < script.find(&format!("\n{cmd} %*\n")).unwrap());
+		let bytes = script.as_bytes();
+		assert!(bytes.windows(cmd.len()).any(|window| window == cmd.as_bytes()));
+	}
+
+	#[test]
+	fn batch_env_line_keeps_percent_signs() {
+		assert_eq!(batch_env_line("GREETING", "hello"), "set \"GREETING=hello\"\n");
+		assert_eq!(batch_env_line("LITERAL", "%PATH%;50%"), "set \"LITERAL=%%PATH%%;50%%\"\n");
+	}
+
+	#[test]
+	fn link_copies_are_recognized() {
+		for name in [".gs.bak", ".gs.tmp", ".gs.bat.bak", ".gs.cmd.tmp"] {
+			assert!(is_link_copy(Path::new(name)), "{name}");
+		}
+		for name in ["gs", "gs.bat", "notes.bak", ".hidden", ".gs.bat"] {
+			assert!(!is_link_copy(Path::new(name)), "{name}");
 		}
 	}
+
+	#[test]
+	fn sh_quote_escapes_single_quotes() {
+		assert_eq!(sh_quote("plain"), "'plain'");
+		assert_eq!(sh_quote("it's"), r"'it'\''s'");
+	}
+
+	#[cfg(target_family = "unix")]
+	#[test]
+	fn sh_quote_round_trips_through_sh() {
+		let word = "/opt/o'brien/$HOME `id` \"x\" \\";
+		let output = std::process::Command::new("sh")
+			.arg("-c")
+			.arg(format!("printf %s {}", sh_quote(word)))
+			.output()
+			.unwrap();
+		assert_eq!(String::from_utf8(output.stdout).unwrap(), word);
+	}
+
+	#[test]
+	fn batch_secret_lines_clear_the_variable_first() {
+		let lines = batch_secret_lines(r"C:\cmdlink.exe", "TOKEN", "api/token");
+		assert!(lines.starts_with("set \"TOKEN=\"\n"));
+		assert!(lines.contains(r#"(`"C:\cmdlink.exe" secret "api/token"`)"#));
+	}
+
+	/// The links of a config in the given project directory, with the given
+	/// hooks.
+	fn context(root: &Path, hooks: Hooks) -> LinkContext {
+		LinkContext::new(CmdlinkPaths::new(root), &Settings::default(), &hooks)
+	}
+
+	#[cfg(target_os = "windows")]
+	#[test]
+	fn windows_priority_runs_the_command_through_cmd() {
+		let values = AliasValues {
+			cmd: r"cd /d C:\logs && del *.old".to_string(),
+			low_priority: true,
+			..AliasValues::default()
+		};
+		let context = context(Path::new(r"C:\cmdlink"), Hooks::default());
+		let contents = values.link("prune", &context).contents().unwrap();
+		assert!(contents.contains("\nstart \"\" /LOW /B /WAIT cmd /C \"cd /d C:\\logs && del *.old %*\"\n"));
+	}
+
+	#[cfg(target_family = "unix")]
+	#[test]
+	fn unix_links_exec_the_command() {
+		let values = AliasValues {
+			cmd: "git status".to_string(),
+			..AliasValues::default()
+		};
+		let context = context(Path::new("/opt/cmdlink"), Hooks::default());
+		let link = values.link("gs", &context);
+		assert_eq!(link.file_path(), Path::new("/opt/cmdlink/bins/gs"));
+		assert_eq!(link.contents().unwrap(), "#!/bin/sh\nexec git status \"$@\"");
+
+		let values = AliasValues {
+			forward_args: Some(false),
+			..values
+		};
+		assert_eq!(values.link("gs", &context).contents().unwrap(), "#!/bin/sh\nexec git status");
+	}
+
+	#[cfg(target_family = "unix")]
+	#[test]
+	fn unix_links_set_up_and_run_the_command() {
+		let dir = std::env::temp_dir().join(format!("cmdlink-unix-link-{}", std::process::id()));
+		std::fs::create_dir_all(&dir).unwrap();
+		let values = AliasValues {
+			cmd: "sh -c 'printf \"%s|%s|%s|%s\" \"$GREETING\" \"$PWD\" \"$0\" \"$1\"; exit 3' {cmdlink_dir}"
+				.to_string(),
+			env: BTreeMap::from([("GREETING".to_string(), "it's $me".to_string())]),
+			cwd: Some(dir.display().to_string()),
+			..AliasValues::default()
+		};
+		let hooks = Hooks {
+			pre_exec: Some("echo before >&2".to_string()),
+			post_exec: Some("echo after >&2".to_string()),
+			..Hooks::default()
+		};
+		let context = context(Path::new("/opt/cmdlink"), hooks);
+		let script = dir.join("link");
+		std::fs::write(&script, values.link("greet", &context).contents().unwrap()).unwrap();
+
+		let output = std::process::Command::new("sh")
+			.arg(&script)
+			.arg("an arg")
+			.output()
+			.unwrap();
+		assert_eq!(output.status.code(), Some(3));
+		assert_eq!(
+			String::from_utf8_lossy(&output.stdout),
+			format!("it's $me|{}|/opt/cmdlink|an arg", dir.display())
+		);
+		assert_eq!(String::from_utf8_lossy(&output.stderr), "before\nafter\n");
+		std::fs::remove_dir_all(&dir).unwrap();
+	}
 }
@@ -0,0 +1,549 @@
+//! Pinned GitHub release binaries, run by aliases setting `release`.
+//!
+//! The asset of the release matching the current platform is downloaded with
+//! `curl` when the alias' link is written, verified against its SHA-256
+//! checksum, unpacked if it's an archive, and cached under
+//! `~/.cmdlink/releases/<owner>/<repo>/<tag>`. Links then run the cached
+//! binary, so the version only changes when the alias does.
+
+use std::{
+	path::{Path, PathBuf},
+	process::Command,
+};
+
+use serde::Deserialize;
+
+use crate::{error::Error, paths::CmdlinkPaths, Result};
+
+/// The asset names, lowercased, that identify the current operating system.
+const OS_NAMES: &[&str] = if cfg!(target_os = "windows") {
+	&["windows", "win64", "win32"]
+} else if cfg!(target_os = "macos") {
+	&["darwin", "macos", "apple", "osx"]
+} else {
+	&["linux"]
+};
+
+/// The asset names, lowercased, that identify the current architecture.
+/// macOS releases often ship a single universal binary instead.
+const ARCH_NAMES: &[&str] = if cfg!(target_arch = "aarch64") {
+	&["aarch64", "arm64", "universal"]
+} else {
+	&["x86_64", "amd64", "x64", "universal"]
+};
+
+/// The extensions of assets that accompany a binary rather than contain one,
+/// or install it through a package manager.
+const SKIPPED_EXTENSIONS: &[&str] = &[
+	".sha256", ".sha512", ".sig", ".asc", ".pem", ".sbom", ".txt", ".json", ".deb", ".rpm", ".apk", ".msi", ".pkg",
+	".dmg",
+];
+
+/// The archive extensions, in the order they're preferred in when a release
+/// has several assets for the platform. Anything else is taken to be the
+/// binary itself.
+const ARCHIVE_EXTENSIONS: &[&str] = &[".tar.gz", ".tgz", ".tar.xz", ".tar.bz2", ".tar", ".zip"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A release of a GitHub repository, written as `owner/repo@tag`.
+pub struct Release {
+	/// The owner of the repository.
+	pub owner: String,
+	/// The name of the repository.
+	pub repo: String,
+	/// The tag of the release.
+	pub tag: String,
+}
+
+#[derive(Debug, Deserialize)]
+/// The parts of a release returned by the GitHub API that are used.
+struct ReleaseInfo {
+	assets: Vec<Asset>,
+}
+
+#[derive(Debug, Deserialize)]
+/// A file attached to a release.
+struct Asset {
+	name: String,
+	browser_download_url: String,
+	#[serde(default)]
+	/// The checksum of the asset, as `sha256:<hex>`.
+	digest: Option<String>,
+}
+
+impl Release {
+	/// Parses an `owner/repo@tag` reference, returning `None` if any part is
+	/// missing.
+	pub fn parse(reference: &str) -> Option<Self> {
+		let (repository, tag) = reference.split_once('@')?;
+		let (owner, repo) = repository.split_once('/')?;
+		let valid = |part: &str| !part.is_empty() && !part.contains(['/', '\\', '@']) && part != "." && part != "..";
+		(valid(owner) && valid(repo) && valid(tag)).then(|| Release {
+			owner: owner.to_string(),
+			repo: repo.to_string(),
+			tag: tag.to_string(),
+		})
+	}
+
+	/// The directory the release's binary is cached in, within the given
+	/// project directory.
+	pub fn cache_dir(&self, paths: &CmdlinkPaths) -> PathBuf {
+		paths.releases_dir().join(&self.owner).join(&self.repo).join(&self.tag)
+	}
+
+	/// The path of the cached binary, named `bin` or after the repository.
+	pub fn binary_path(&self, paths: &CmdlinkPaths, bin: Option<&str>) -> PathBuf {
+		let name = bin.unwrap_or(&self.repo);
+		let extension = if cfg!(target_os = "windows") && !name.contains('.') {
+			".exe"
+		} else {
+			""
+		};
+		self.cache_dir(paths).join(format!("{name}{extension}"))
+	}
+
+	/// Downloads the release's binary into the cache unless it's already
+	/// there, returning its path.
+	///
+	/// The asset is the one matching `asset`, a name with `*` wildcards, or
+	/// else the one naming the current operating system and architecture. It
+	/// is verified against `sha256`, or else the checksum GitHub records for
+	/// it, or else a checksums file published with the release. Archives are
+	/// unpacked and the file named `bin`, the repository name by default, is
+	/// kept.
+	pub fn fetch(
+		&self, paths: &CmdlinkPaths, asset: Option<&str>, bin: Option<&str>, sha256: Option<&str>,
+	) -> Result<PathBuf> {
+		let binary = self.binary_path(paths, bin);
+		if binary.is_file() {
+			return Ok(binary);
+		}
+		let fail = |reason: String| Error::Release(self.to_string(), reason);
+		info!("Downloading release {}", self);
+
+		let api = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| "https://api.github.com".to_string());
+		let url = format!(
+			"{}/repos/{}/{}/releases/tags/{}",
+			api.trim_end_matches('/'),
+			self.owner,
+			self.repo,
+			self.tag
+		);
+		let info: ReleaseInfo = serde_json::from_slice(&curl(&url, None).map_err(&fail)?)
+			.map_err(|e| fail(format!("unexpected response from {url}: {e}")))?;
+		let chosen = choose_asset(&info.assets, asset).ok_or_else(|| {
+			let names: Vec<&str> = info.assets.iter().map(|asset| asset.name.as_str()).collect();
+			fail(format!(
+				"no asset for this platform among {}, pick one with `asset`",
+				names.join(", ")
+			))
+		})?;
+		// The name comes from the API and is joined onto the cache directory.
+		if !is_file_name(&chosen.name) {
+			return Err(fail(format!(
+				"refusing to download asset {:?}, it isn't a plain file name",
+				chosen.name
+			)));
+		}
+		let expected = match sha256 {
+			Some(sha256) => sha256.to_ascii_lowercase(),
+			None => published_checksum(chosen, &info.assets)
+				.map_err(&fail)?
+				.ok_or_else(|| fail(format!("no checksum is published for {}, set `sha256`", chosen.name)))?,
+		};
+
+		let dir = self.cache_dir(paths);
+		std::fs::create_dir_all(&dir).map_err(|e| fail(e.to_string()))?;
+		let download = dir.join(&chosen.name);
+		curl(&chosen.browser_download_url, Some(&download)).map_err(&fail)?;
+		let actual = std::fs::read(&download)
+			.map(|contents| hex(&sha256_digest(&contents)))
+			.map_err(|e| fail(e.to_string()))?;
+		if actual != expected {
+			let _ = std::fs::remove_file(&download);
+			return Err(fail(format!(
+				"checksum mismatch for {}: expected {expected}, got {actual}",
+				chosen.name
+			)));
+		}
+
+		let unpacked = match is_archive(&chosen.name) {
+			true => unpack(&download, &dir, &binary).map_err(&fail),
+			false => std::fs::rename(&download, &binary).map_err(|e| fail(e.to_string())),
+		};
+		let _ = std::fs::remove_file(&download);
+		unpacked?;
+		make_executable(&binary).map_err(|e| fail(e.to_string()))?;
+		debug!("Cached release {} at {}", self, binary.display());
+		Ok(binary)
+	}
+}
+
+impl std::fmt::Display for Release {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{}/{}@{}", self.owner, self.repo, self.tag)
+	}
+}
+
+/// Runs `curl`, returning the response body, or writing it to `out` instead.
+/// `GITHUB_TOKEN` is passed along when set, for private repositories and
+/// higher rate limits.
+fn curl(url: &str, out: Option<&Path>) -> std::result::Result<Vec<u8>, String> {
+	let mut command = Command::new("curl");
+	command.args(["--fail", "--silent", "--show-error", "--location", "--retry", "2"]);
+	if let Ok(token) = std::env::var("GITHUB_TOKEN") {
+		command.arg("--header").arg(format!("Authorization: Bearer {token}"));
+	}
+	if let Some(out) = out {
+		command.arg("--output").arg(out);
+	}
+	let output = command
+		.arg(url)
+		.output()
+		.map_err(|e| format!("failed to run curl: {e}"))?;
+	if !output.status.success() {
+		return Err(format!(
+			"failed to download {url}: {}",
+			String::from_utf8_lossy(&output.stderr).trim()
+		));
+	}
+	Ok(output.stdout)
+}
+
+/// The asset matching the pattern, or else the preferred asset naming the
+/// current operating system and architecture.
+fn choose_asset<'a>(assets: &'a [Asset], pattern: Option<&str>) -> Option<&'a Asset> {
+	if let Some(pattern) = pattern {
+		return assets.iter().find(|asset| wildcard_match(pattern, &asset.name));
+	}
+	assets
+		.iter()
+		.filter(|asset| {
+			let name = asset.name.to_ascii_lowercase();
+			OS_NAMES.iter().any(|os| name.contains(os))
+				&& ARCH_NAMES.iter().any(|arch| name.contains(arch))
+				&& !SKIPPED_EXTENSIONS.iter().any(|extension| name.ends_with(extension))
+		})
+		.min_by_key(|asset| {
+			let name = asset.name.to_ascii_lowercase();
+			ARCHIVE_EXTENSIONS
+				.iter()
+				.position(|extension| name.ends_with(extension))
+				.unwrap_or(ARCHIVE_EXTENSIONS.len())
+		})
+}
+
+/// The SHA-256 checksum published for the asset, from GitHub's record of it
+/// or a checksums file attached to the release, such as `SHA256SUMS` or
+/// `gh_2.63.0_checksums.txt`.
+fn published_checksum(asset: &Asset, assets: &[Asset]) -> std::result::Result<Option<String>, String> {
+	if let Some(digest) = asset
+		.digest
+		.as_deref()
+		.and_then(|digest| digest.strip_prefix("sha256:"))
+	{
+		return Ok(Some(digest.to_ascii_lowercase()));
+	}
+	let Some(checksums) = assets.iter().find(|candidate| {
+		let name = candidate.name.to_ascii_lowercase();
+		name.contains("checksums") || name.contains("sha256sums")
+	}) else {
+		return Ok(None);
+	};
+	let checksums = curl(&checksums.browser_download_url, None)?;
+	Ok(find_checksum(&String::from_utf8_lossy(&checksums), &asset.name))
+}
+
+/// The checksum of the named file in the `<checksum>  <name>` lines of a
+/// checksums file, as written by `sha256sum`.
+fn find_checksum(checksums: &str, file_name: &str) -> Option<String> {
+	checksums.lines().find_map(|line| {
+		let (checksum, name) = line.split_once(char::is_whitespace)?;
+		// `sha256sum` marks files hashed in binary mode with a `*`.
+		(name.trim().trim_start_matches('*') == file_name).then(|| checksum.to_ascii_lowercase())
+	})
+}
+
+/// Whether the name is a plain file name, without path separators or `..`
+/// that could take a path joined with it out of its directory.
+fn is_file_name(name: &str) -> bool {
+	!name.is_empty() && !name.contains(['/', '\\', ':', '\0']) && !name.contains("..")
+}
+
+/// Whether the asset is an archive to unpack rather than the binary itself.
+fn is_archive(name: &str) -> bool {
+	let name = name.to_ascii_lowercase();
+	ARCHIVE_EXTENSIONS.iter().any(|extension| name.ends_with(extension))
+}
+
+/// Unpacks the archive into a scratch directory in `dir` and moves the file
+/// named like `binary` out of it, wherever it is in the archive.
+fn unpack(archive: &Path, dir: &Path, binary: &Path) -> std::result::Result<(), String> {
+	let scratch = dir.join(".unpack");
+	let _ = std::fs::remove_dir_all(&scratch);
+	std::fs::create_dir_all(&scratch).map_err(|e| e.to_string())?;
+	// The tar of Windows unpacks zip archives as well, GNU tar doesn't.
+	let zip = archive.to_string_lossy().to_ascii_lowercase().ends_with(".zip");
+	let mut command = if zip && !cfg!(target_os = "windows") {
+		let mut command = Command::new("unzip");
+		command.arg("-q").arg(archive).arg("-d").arg(&scratch);
+		command
+	} else {
+		let mut command = Command::new("tar");
+		command.arg("-xf").arg(archive).arg("-C").arg(&scratch);
+		command
+	};
+	let program = command.get_program().to_string_lossy().into_owned();
+	let output = command.output().map_err(|e| format!("failed to run {program}: {e}"))?;
+	let result = if !output.status.success() {
+		Err(format!(
+			"failed to unpack {}: {}",
+			archive.display(),
+			String::from_utf8_lossy(&output.stderr).trim()
+		))
+	} else {
+		let name = binary.file_name().unwrap_or_default();
+		match find_file(&scratch, name) {
+			Some(found) => std::fs::rename(found, binary).map_err(|e| e.to_string()),
+			None => Err(format!(
+				"{} has no file named {}, pick it with `bin`",
+				archive.display(),
+				name.to_string_lossy()
+			)),
+		}
+	};
+	let _ = std::fs::remove_dir_all(&scratch);
+	result
+}
+
+/// The first file with the given name in the directory tree.
+fn find_file(dir: &Path, name: &std::ffi::OsStr) -> Option<PathBuf> {
+	let mut entries: Vec<_> = std::fs::read_dir(dir).ok()?.filter_map(|entry| entry.ok()).collect();
+	entries.sort_by_key(|entry| entry.path());
+	entries.iter().find_map(|entry| {
+		let path = entry.path();
+		match entry.file_type().ok()?.is_dir() {
+			true => find_file(&path, name),
+			false => (path.file_name() == Some(name)).then_some(path),
+		}
+	})
+}
+
+/// Marks the binary as executable on Unix. A no-op on Windows.
+fn make_executable(path: &Path) -> std::io::Result<()> {
+	#[cfg(target_family = "unix")]
+	{
+		use std::os::unix::fs::PermissionsExt;
+
+		let mut permissions = std::fs::metadata(path)?.permissions();
+		permissions.set_mode(permissions.mode() | 0o111);
+		std::fs::set_permissions(path, permissions)?;
+	}
+	#[cfg(not(target_family = "unix"))]
+	let _ = path;
+	Ok(())
+}
+
+/// Whether the name matches the pattern, in which `*` stands for any run of
+/// characters.
+fn wildcard_match(pattern: &str, name: &str) -> bool {
+	match pattern.split_once('*') {
+		None => pattern == name,
+		Some((prefix, rest)) => {
+			let Some(name) = name.strip_prefix(prefix) else {
+				return false;
+			};
+			(0..=name.len())
+				.filter(|i| name.is_char_boundary(*i))
+				.any(|i| wildcard_match(rest, &name[i..]))
+		},
+	}
+}
+
+/// Lowercase hexadecimal digits of the bytes.
+fn hex(bytes: &[u8]) -> String { bytes.iter().map(|byte| format!("{byte:02x}")).collect() }
+
+/// The SHA-256 round constants.
+const SHA256_K: [u32; 64] = [
+	0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5, 0xd807aa98,
+	0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174, 0xe49b69c1, 0xefbe4786,
+	0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da, 0x983e5152, 0xa831c66d, 0xb00327c8,
+	0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967, 0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13,
+	0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85, 0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819,
+	0xd6990624, 0xf40e3585, 0x106aa070, 0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a,
+	0x5b9cca4f, 0x682e6ff3, 0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7,
+	0xc67178f2,
+];
+
+/// The SHA-256 digest of the data, which release checksums are published as.
+fn sha256_digest(data: &[u8]) -> [u8; 32] {
+	let mut state: [u32; 8] = [
+		0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+	];
+	// The data is padded with a 1 bit, zeroes and its length in bits to a
+	// multiple of 64 bytes.
+	let mut message = data.to_vec();
+	message.push(0x80);
+	while message.len() % 64 != 56 {
+		message.push(0);
+	}
+	message.extend((data.len() as u64 * 8).to_be_bytes());
+
+	for block in message.chunks_exact(64) {
+		let mut w = [0u32; 64];
+		for (i, word) in block.chunks_exact(4).enumerate() {
+			w[i] = u32::from_be_bytes([word[0], word[1], word[2], word[3]]);
+		}
+		for i in 16..64 {
+			let s0 = w[i - 15].rotate_right(7) ^ w[i - 15].rotate_right(18) ^ (w[i - 15] >> 3);
+			let s1 = w[i - 2].rotate_right(17) ^ w[i - 2].rotate_right(19) ^ (w[i - 2] >> 10);
+			w[i] = w[i - 16].wrapping_add(s0).wrapping_add(w[i - 7]).wrapping_add(s1);
+		}
+
+		let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+		for i in 0..64 {
+			let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+			let choice = (e & f) ^ (!e & g);
+			let t1 = h
+				.wrapping_add(s1)
+				.wrapping_add(choice)
+				.wrapping_add(SHA256_K[i])
+				.wrapping_add(w[i]);
+			let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+			let majority = (a & b) ^ (a & c) ^ (b & c);
+			let t2 = s0.wrapping_add(majority);
+			h = g;
+			g = f;
+			f = e;
+			e = d.wrapping_add(t1);
+			d = c;
+			c = b;
+			b = a;
+			a = t1.wrapping_add(t2);
+		}
+		for (word, value) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+			*word = word.wrapping_add(value);
+		}
+	}
+
+	let mut digest = [0u8; 32];
+	for (bytes, word) in digest.chunks_exact_mut(4).zip(state) {
+		bytes.copy_from_slice(&word.to_be_bytes());
+	}
+	digest
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn sha256_matches_nist_vectors() {
+		let vectors: [(&[u8], &str); 4] = [
+			(b"", "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"),
+			(b"abc", "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"),
+			(
+				b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq",
+				"248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1",
+			),
+			(
+				b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu",
+				"cf5b16a778af8380036ce59e7b0492370b249b11e8f07a51afac45037afee9d1",
+			),
+		];
+		for (data, digest) in vectors {
+			assert_eq!(hex(&sha256_digest(data)), digest);
+		}
+		assert_eq!(
+			hex(&sha256_digest(&vec![b'a'; 1_000_000])),
+			"cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0"
+		);
+	}
+
+	#[test]
+	fn sha256_pads_across_block_boundaries() {
+		// 55 bytes is the longest message whose padding fits in one block.
+		assert_eq!(
+			hex(&sha256_digest(&[b'a'; 55])),
+			"9f4390f8d30c2dd92ec9f095b65e2b9ae9b0a925a5258e241c9f1e910f734318"
+		);
+		assert_eq!(
+			hex(&sha256_digest(&[b'a'; 56])),
+			"b35439a4ac6f0948b6d6f9e3c6af0f5f590ce20f1bde7090ef7970686ec6738a"
+		);
+		assert_eq!(
+			hex(&sha256_digest(&[b'a'; 64])),
+			"ffe054fe7ae0cb6dc65c3af9b61d5209f439851db43d0ba5997337df154668eb"
+		);
+	}
+
+	/// Assets with the given names, downloaded from nowhere.
+	fn assets(names: &[&str]) -> Vec<Asset> {
+		names
+			.iter()
+			.map(|name| Asset {
+				name: name.to_string(),
+				browser_download_url: String::new(),
+				digest: None,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn asset_is_chosen_by_pattern() {
+		let assets = assets(&["tool-1.0-linux-x86_64.tar.gz", "tool-1.0-linux-arm64.zip", "checksums.txt"]);
+		let chosen = |pattern| choose_asset(&assets, Some(pattern)).map(|asset| asset.name.as_str());
+		assert_eq!(chosen("*arm64*"), Some("tool-1.0-linux-arm64.zip"));
+		assert_eq!(chosen("tool-*-linux-*.tar.gz"), Some("tool-1.0-linux-x86_64.tar.gz"));
+		assert_eq!(chosen("checksums.txt"), Some("checksums.txt"));
+		assert_eq!(chosen("*.deb"), None);
+	}
+
+	#[test]
+	fn asset_is_chosen_for_the_platform() {
+		let (os, arch) = (OS_NAMES[0], ARCH_NAMES[0]);
+		let names = [
+			"tool-other-platform.tar.gz".to_string(),
+			format!("tool-{os}-{arch}.zip"),
+			format!("tool-{os}-{arch}.tar.gz.sha256"),
+			format!("tool-{os}-{arch}.deb"),
+			format!("tool-{os}-{arch}.tar.gz"),
+		];
+		let assets = assets(&names.iter().map(String::as_str).collect::<Vec<_>>());
+		assert_eq!(
+			choose_asset(&assets, None).map(|asset| asset.name.clone()),
+			Some(format!("tool-{os}-{arch}.tar.gz"))
+		);
+		assert!(choose_asset(&assets[..1], None).is_none());
+	}
+
+	#[test]
+	fn wildcards_match_any_run_of_characters() {
+		assert!(wildcard_match("tool", "tool"));
+		assert!(!wildcard_match("tool", "tool.exe"));
+		assert!(wildcard_match("tool*", "tool.exe"));
+		assert!(wildcard_match("*", ""));
+		assert!(wildcard_match("*-linux-*", "rg-14-linux-musl"));
+		assert!(wildcard_match("a*b*c", "abc"));
+		assert!(wildcard_match("a*b*c", "a-b-b-c"));
+		assert!(!wildcard_match("a*b*c", "a-c-b"));
+		assert!(wildcard_match("*é*", "café-x"));
+	}
+
+	#[test]
+	fn checksums_are_found_by_file_name() {
+		let checksums = "AB12  tool-linux.tar.gz\r\ncd34 *tool-windows.zip\nef56  tool-linux.tar.gz.sbom\n";
+		assert_eq!(find_checksum(checksums, "tool-linux.tar.gz").as_deref(), Some("ab12"));
+		assert_eq!(find_checksum(checksums, "tool-windows.zip").as_deref(), Some("cd34"));
+		assert_eq!(find_checksum(checksums, "tool-macos.zip"), None);
+		assert_eq!(find_checksum("", "tool"), None);
+	}
+
+	#[test]
+	fn asset_names_must_be_plain() {
+		assert!(is_file_name("ripgrep-14.1.0-x86_64-unknown-linux-musl.tar.gz"));
+		for name in ["", "../../bin/sh", "..", "dir/tool", r"dir\tool", "C:tool"] {
+			assert!(!is_file_name(name), "{name}");
+		}
+	}
+}
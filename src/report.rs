@@ -0,0 +1,203 @@
+//! Rendering the aliases as a document for a team wiki, as done by `cmdlink
+//! report`.
+
+use std::{collections::BTreeMap, fmt::Write};
+
+use crate::{config::AliasValues, tr, Config};
+
+/// The formats a report can be rendered in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+	/// A standalone HTML page.
+	Html,
+	/// A Markdown document.
+	Markdown,
+}
+
+/// The aliases of a report grouped by tag, with untagged aliases under `None`.
+/// Aliases with several tags are listed under each of them.
+type Sections<'a> = BTreeMap<Option<&'a str>, Vec<(&'a str, &'a AliasValues)>>;
+
+/// Renders every alias of the config, grouped by tag, with its descriptions,
+/// command, platform variants and examples. Environment variables and secrets
+/// are left out, as their values may be sensitive.
+pub fn render(cfg: &Config, format: Format) -> String {
+	let mut sections = Sections::new();
+	for (alias, values) in cfg.aliases() {
+		let tags = values.effective_tags();
+		if tags.is_empty() {
+			sections.entry(None).or_default().push((alias, values));
+		}
+		for tag in tags {
+			sections.entry(Some(tag)).or_default().push((alias, values));
+		}
+	}
+	sections
+		.values_mut()
+		.for_each(|aliases| aliases.sort_unstable_by_key(|(alias, _)| *alias));
+
+	// Untagged aliases sort first, but read better at the end.
+	let untagged = tr!("display.untagged");
+	let sections: Vec<(&str, &Vec<(&str, &AliasValues)>)> = sections
+		.iter()
+		.filter_map(|(tag, aliases)| Some(((*tag)?, aliases)))
+		.chain(sections.get(&None).map(|aliases| (untagged.as_str(), aliases)))
+		.collect();
+
+	match format {
+		Format::Html => html(&sections),
+		Format::Markdown => markdown(&sections),
+	}
+}
+
+/// The platform variants of an alias command, labeled by platform.
+fn variants(values: &AliasValues) -> Vec<(&'static str, &str)> {
+	[
+		("Windows", &values.platform.windows),
+		("macOS", &values.platform.macos),
+		("Linux", &values.platform.linux),
+	]
+	.into_iter()
+	.filter_map(|(platform, cmd)| Some((platform, cmd.as_deref()?)))
+	.collect()
+}
+
+/// Renders the report as Markdown.
+fn markdown(sections: &[(&str, &Vec<(&str, &AliasValues)>)]) -> String {
+	let mut doc = format!("# {}\n", tr!("report.title"));
+	for (tag, aliases) in sections {
+		let _ = write!(doc, "\n## {tag}\n");
+		for (alias, values) in aliases.iter() {
+			let _ = write!(doc, "\n### {}\n\n", code_span(alias));
+			if values.disabled {
+				let _ = writeln!(doc, "_{}_\n", tr!("report.disabled"));
+			}
+			if let Some(description) = &values.description {
+				let _ = writeln!(doc, "{}\n", markdown_text(description));
+			}
+			if let Some(long_description) = &values.long_description {
+				let _ = writeln!(doc, "{}\n", markdown_text(long_description.trim()));
+			}
+			let _ = writeln!(doc, "{}", code_block(values.target()));
+
+			let variants = variants(values);
+			if !variants.is_empty() {
+				let _ = write!(
+					doc,
+					"\n| {} | {} |\n| --- | --- |\n",
+					tr!("report.platform"),
+					tr!("report.command")
+				);
+				for (platform, cmd) in variants {
+					let _ = writeln!(doc, "| {platform} | {} |", code_span(cmd).replace('|', "\\|"));
+				}
+			}
+			if !values.examples.is_empty() {
+				let _ = write!(doc, "\n{}\n\n", tr!("report.examples"));
+				for example in &values.examples {
+					let _ = writeln!(doc, "- {}", code_span(example));
+				}
+			}
+		}
+	}
+	doc
+}
+
+/// Escapes the characters of plain text that Markdown would read as HTML or
+/// emphasis. Backticks are kept, as descriptions often quote commands in them.
+fn markdown_text(text: &str) -> String {
+	text.chars().fold(String::with_capacity(text.len()), |mut escaped, c| {
+		if matches!(c, '\\' | '*' | '<' | '>') {
+			escaped.push('\\');
+		}
+		escaped.push(c);
+		escaped
+	})
+}
+
+/// Wraps text in a Markdown code span, using more backticks than any run of
+/// backticks in the text.
+fn code_span(text: &str) -> String {
+	let ticks = "`".repeat(longest_backtick_run(text) + 1);
+	let pad = if text.starts_with('`') || text.ends_with('`') {
+		" "
+	} else {
+		""
+	};
+	format!("{ticks}{pad}{text}{pad}{ticks}")
+}
+
+/// Wraps text in a fenced Markdown code block for shell code, using a longer
+/// fence than any run of backticks in the text.
+fn code_block(text: &str) -> String {
+	let fence = "`".repeat((longest_backtick_run(text) + 1).max(3));
+	format!("{fence}sh\n{}\n{fence}", text.trim_end())
+}
+
+/// The length of the longest run of backticks in the text.
+fn longest_backtick_run(text: &str) -> usize { text.split(|c| c != '`').map(str::len).max().unwrap_or_default() }
+
+/// The styles of the HTML report, kept minimal so that it blends into wikis.
+const STYLE: &str = "body { font-family: system-ui, sans-serif; max-width: 60rem; margin: 2rem auto; padding: 0 1rem; }
+pre, code { font-family: ui-monospace, monospace; }
+pre { background: #f5f5f5; padding: 0.75rem; overflow-x: auto; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #ddd; padding: 0.25rem 0.5rem; text-align: left; }
+.disabled { color: #888; }";
+
+/// Renders the report as a standalone HTML page.
+fn html(sections: &[(&str, &Vec<(&str, &AliasValues)>)]) -> String {
+	let title = escape(&tr!("report.title"));
+	let mut doc = format!(
+		"<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>\n{STYLE}\n</style>\n</head>\n<body>\n<h1>{title}</h1>\n"
+	);
+	for (tag, aliases) in sections {
+		let _ = writeln!(doc, "<h2>{}</h2>", escape(tag));
+		for (alias, values) in aliases.iter() {
+			let _ = writeln!(doc, "<h3><code>{}</code></h3>", escape(alias));
+			if values.disabled {
+				let _ = writeln!(doc, "<p class=\"disabled\"><em>{}</em></p>", escape(&tr!("report.disabled")));
+			}
+			if let Some(description) = &values.description {
+				let _ = writeln!(doc, "<p>{}</p>", escape(description));
+			}
+			if let Some(long_description) = &values.long_description {
+				for paragraph in long_description.trim().split("\n\n") {
+					let _ = writeln!(doc, "<p>{}</p>", escape(paragraph).replace('\n', "<br>\n"));
+				}
+			}
+			let _ = writeln!(doc, "<pre><code>{}</code></pre>", escape(values.target()));
+
+			let variants = variants(values);
+			if !variants.is_empty() {
+				let _ = writeln!(
+					doc,
+					"<table>\n<tr><th>{}</th><th>{}</th></tr>",
+					escape(&tr!("report.platform")),
+					escape(&tr!("report.command"))
+				);
+				for (platform, cmd) in variants {
+					let _ = writeln!(doc, "<tr><td>{platform}</td><td><code>{}</code></td></tr>", escape(cmd));
+				}
+				doc.push_str("</table>\n");
+			}
+			if !values.examples.is_empty() {
+				let _ = writeln!(doc, "<p>{}</p>\n<ul>", escape(&tr!("report.examples")));
+				for example in &values.examples {
+					let _ = writeln!(doc, "<li><code>{}</code></li>", escape(example));
+				}
+				doc.push_str("</ul>\n");
+			}
+		}
+	}
+	doc.push_str("</body>\n</html>\n");
+	doc
+}
+
+/// Escapes the characters with a special meaning in HTML.
+fn escape(text: &str) -> String {
+	text.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+}
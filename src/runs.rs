@@ -0,0 +1,212 @@
+//! The run history of aliases, recorded by their links when the `run_history`
+//! setting is enabled and shown by `cmdlink history`.
+//!
+//! Links time the alias' command themselves and report its arguments, exit
+//! code and duration through the hidden `cmdlink record` subcommand, which
+//! appends a JSON line to the history file in the project directory.
+
+use std::{
+	fs::OpenOptions,
+	io::{ErrorKind, Write},
+	path::PathBuf,
+	time::{Duration, SystemTime},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, paths::CmdlinkPaths, Result};
+
+/// The name of the history file, in the project directory.
+pub const RUNS_FILE: &str = "runs.jsonl";
+
+/// The size the history file may grow to before its oldest half is dropped.
+const MAX_SIZE: u64 = 512 * 1024;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// A single invocation of an alias.
+pub struct Run {
+	/// The alias that was run.
+	pub alias: String,
+	/// When the command started, in RFC 3339 format.
+	pub started: String,
+	/// The exit code of the command.
+	pub status: i32,
+	/// How long the command ran, in milliseconds.
+	pub duration_ms: u64,
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	/// The arguments the alias was run with.
+	pub args: Vec<String>,
+}
+
+impl Run {
+	/// Whether or not the command failed.
+	pub fn failed(&self) -> bool { self.status != 0 }
+
+	/// How long the command ran.
+	pub fn duration(&self) -> Duration { Duration::from_millis(self.duration_ms) }
+
+	/// The alias and its arguments as they would be typed, quoting arguments
+	/// that are empty or contain whitespace or quotes.
+	pub fn command_line(&self) -> String {
+		std::iter::once(self.alias.clone())
+			.chain(self.args.iter().map(|arg| {
+				if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == '"' || c == '\'') {
+					format!("'{}'", arg.replace('\'', "'\\''"))
+				} else {
+					arg.clone()
+				}
+			}))
+			.collect::<Vec<_>>()
+			.join(" ")
+	}
+}
+
+/// The path of the history file in the given project directory.
+pub fn runs_path(paths: &CmdlinkPaths) -> PathBuf { paths.root().join(RUNS_FILE) }
+
+/// Appends a run of the alias with the given arguments that just finished
+/// after the given time to the history, dropping the oldest half of the
+/// history once it grows too large.
+pub fn record(paths: &CmdlinkPaths, alias: &str, status: i32, elapsed: Duration, args: Vec<String>) -> Result<()> {
+	let path = runs_path(paths);
+	let error = |e| Error::RunHistory(path.clone(), e);
+	let run = Run {
+		alias: alias.to_string(),
+		started: humantime::format_rfc3339_seconds(SystemTime::now() - elapsed).to_string(),
+		status,
+		duration_ms: elapsed.as_millis().try_into().unwrap_or(u64::MAX),
+		args,
+	};
+
+	let mut file = OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(&path)
+		.map_err(error)?;
+	writeln!(file, "{}", serde_json::to_string(&run)?).map_err(error)?;
+	if file.metadata().map_err(error)?.len() > MAX_SIZE {
+		let contents = std::fs::read_to_string(&path).map_err(error)?;
+		let lines: Vec<&str> = contents.lines().collect();
+		let kept = lines[lines.len() / 2..].join("\n");
+		std::fs::write(&path, format!("{kept}\n")).map_err(error)?;
+	}
+	Ok(())
+}
+
+/// Reads the runs recorded in the given project directory, oldest first. Lines
+/// that can't be parsed, such as those cut off by a full disk, are skipped.
+pub fn read(paths: &CmdlinkPaths) -> Result<Vec<Run>> {
+	let path = runs_path(paths);
+	let contents = match std::fs::read_to_string(&path) {
+		Ok(contents) => contents,
+		Err(e) if e.kind() == ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(e) => return Err(Error::RunHistory(path, e)),
+	};
+	Ok(contents
+		.lines()
+		.filter_map(|line| match serde_json::from_str(line) {
+			Ok(run) => Some(run),
+			Err(why) => {
+				debug!("Skipping history line {:?}: {}", line, why);
+				None
+			},
+		})
+		.collect())
+}
+
+/// Drops the runs that started before `cutoff` from the history, except for
+/// the `keep` most recent ones, along with lines that can't be parsed. Returns
+/// the number of lines dropped, without touching the history if `dry_run` is
+/// set.
+pub fn trim(paths: &CmdlinkPaths, keep: usize, cutoff: SystemTime, dry_run: bool) -> Result<usize> {
+	let path = runs_path(paths);
+	let contents = match std::fs::read_to_string(&path) {
+		Ok(contents) => contents,
+		Err(e) if e.kind() == ErrorKind::NotFound => return Ok(0),
+		Err(e) => return Err(Error::RunHistory(path, e)),
+	};
+	let (kept, dropped) = trimmed(&contents, keep, cutoff);
+	if dropped > 0 && !dry_run {
+		std::fs::write(&path, kept).map_err(|e| Error::RunHistory(path, e))?;
+	}
+	Ok(dropped)
+}
+
+/// The history with the lines [trim] drops removed, and their number.
+fn trimmed(contents: &str, keep: usize, cutoff: SystemTime) -> (String, usize) {
+	let lines: Vec<(&str, Option<Run>)> = contents
+		.lines()
+		.map(|line| (line, serde_json::from_str(line).ok()))
+		.collect();
+	let older = lines
+		.iter()
+		.filter(|(_, run)| run.is_some())
+		.count()
+		.saturating_sub(keep);
+	let (mut kept, mut dropped, mut index) = (String::new(), 0, 0);
+	for (line, run) in lines {
+		let keep = run.is_some_and(|run| {
+			index += 1;
+			index > older || humantime::parse_rfc3339_weak(&run.started).map_or(true, |started| started >= cutoff)
+		});
+		match keep {
+			true => {
+				kept.push_str(line);
+				kept.push('\n');
+			},
+			false => dropped += 1,
+		}
+	}
+	(kept, dropped)
+}
+
+/// Reads the most recent runs, newest first, failing with [Error::NoRuns] if
+/// none were recorded.
+pub fn latest(paths: &CmdlinkPaths, limit: usize) -> Result<Vec<Run>> {
+	let runs: Vec<Run> = read(paths)?.into_iter().rev().take(limit).collect();
+	if runs.is_empty() {
+		return Err(Error::NoRuns);
+	}
+	Ok(runs)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn line(alias: &str, started: &str) -> String {
+		serde_json::to_string(&Run {
+			alias: alias.to_string(),
+			started: started.to_string(),
+			status: 0,
+			duration_ms: 10,
+			args: Vec::new(),
+		})
+		.unwrap()
+	}
+
+	#[test]
+	fn trim_drops_old_runs_beyond_the_kept_ones() {
+		let contents = [
+			line("a", "2026-01-01T00:00:00Z"),
+			line("b", "2026-02-01T00:00:00Z"),
+			"{truncated".to_string(),
+			line("c", "2026-03-01T00:00:00Z"),
+			line("d", "2026-09-01T00:00:00Z"),
+		]
+		.join("\n");
+		let cutoff = humantime::parse_rfc3339("2026-06-01T00:00:00Z").unwrap();
+
+		let (kept, dropped) = trimmed(&contents, 2, cutoff);
+		assert_eq!(dropped, 3);
+		assert_eq!(
+			kept,
+			format!("{}\n{}\n", line("c", "2026-03-01T00:00:00Z"), line("d", "2026-09-01T00:00:00Z"))
+		);
+
+		let (_, dropped) = trimmed(&contents, 0, cutoff);
+		assert_eq!(dropped, 4);
+		let (_, dropped) = trimmed(&contents, 10, cutoff);
+		assert_eq!(dropped, 1);
+	}
+}
@@ -0,0 +1,72 @@
+//! Running an alias with a scrubbed environment, as done by
+//! `cmdlink run --sandbox`, to safely try out aliases that come from packs or
+//! remote configs.
+
+use std::{ffi::OsString, process::ExitStatus};
+
+use crate::{error::Error, platform_binary::Link, trace, Result};
+
+/// The environment variables kept in the sandbox, besides those allowed
+/// explicitly.
+#[cfg(target_os = "windows")]
+pub const DEFAULT_ALLOWED_ENV: [&str; 7] = ["COMSPEC", "PATHEXT", "SYSTEMROOT", "TEMP", "TMP", "USERPROFILE", "WINDIR"];
+/// The environment variables kept in the sandbox, besides those allowed
+/// explicitly.
+#[cfg(not(target_os = "windows"))]
+pub const DEFAULT_ALLOWED_ENV: [&str; 6] = ["HOME", "LANG", "LC_ALL", "TERM", "TMPDIR", "USER"];
+
+/// How an alias is isolated when run in a sandbox.
+#[derive(Debug, Default, Clone)]
+pub struct Sandbox {
+	/// Environment variables kept in addition to [DEFAULT_ALLOWED_ENV].
+	/// Allowing `PATH` keeps the full PATH.
+	pub allow_env: Vec<String>,
+	/// Whether or not the alias runs in a new temporary directory, removed
+	/// afterwards, instead of the current one.
+	pub temp_dir: bool,
+}
+
+impl Sandbox {
+	/// Runs the link of the alias with the given arguments, with a minimal PATH
+	/// and only the allowed environment variables, returning its exit status.
+	/// Variables set by the alias itself are still exported by the link.
+	pub fn run(&self, link: &impl Link, args: &[String]) -> Result<ExitStatus> {
+		let dir = std::env::temp_dir().join(format!("cmdlink-sandbox-{}", std::process::id()));
+		if self.temp_dir {
+			std::fs::create_dir_all(&dir).map_err(|e| Error::AliasRun(link.alias().to_string(), e))?;
+		}
+
+		let status = trace::run_script(link, &link.contents()?, args, |command| {
+			command.env_clear().env("PATH", minimal_path());
+			for key in DEFAULT_ALLOWED_ENV
+				.into_iter()
+				.chain(self.allow_env.iter().map(String::as_str))
+			{
+				if let Some(value) = std::env::var_os(key) {
+					command.env(key, value);
+				}
+			}
+			if self.temp_dir {
+				command.current_dir(&dir);
+			}
+		});
+
+		if self.temp_dir {
+			let _ = std::fs::remove_dir_all(&dir);
+		}
+		status
+	}
+}
+
+/// The PATH inside the sandbox, containing only the system directories.
+fn minimal_path() -> OsString {
+	#[cfg(target_os = "windows")]
+	{
+		let root = std::env::var("SYSTEMROOT").unwrap_or_else(|_| r"C:\Windows".to_string());
+		format!(r"{root}\System32;{root};{root}\System32\Wbem").into()
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		"/usr/local/bin:/usr/bin:/bin".into()
+	}
+}
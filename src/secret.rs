@@ -0,0 +1,114 @@
+use std::process::Command;
+
+use crate::{error::Error, Result};
+
+/// The scheme prefix of OS keychain secret references.
+const KEYRING_SCHEME: &str = "keyring:";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A reference to a secret stored in the OS keychain, written as
+/// `keyring:<service>/<account>`.
+pub struct SecretRef<'a> {
+	/// The keychain service the secret is stored under.
+	pub service: &'a str,
+	/// The keychain account the secret is stored under.
+	pub account: &'a str,
+}
+
+impl<'a> SecretRef<'a> {
+	/// Parses a `keyring:<service>/<account>` secret reference.
+	pub fn parse(reference: &'a str) -> Result<Self> {
+		reference
+			.strip_prefix(KEYRING_SCHEME)
+			.and_then(|rest| rest.split_once('/'))
+			.filter(|(service, account)| !service.is_empty() && !account.is_empty())
+			.map(|(service, account)| SecretRef { service, account })
+			.ok_or_else(|| Error::InvalidSecretRef(reference.to_string()))
+	}
+
+	/// Retrieves the secret from the OS keychain using the platform tooling:
+	/// `security` on macOS, `secret-tool` (libsecret) on Linux, and the
+	/// Windows Credential Locker through PowerShell on Windows.
+	pub fn resolve(&self) -> Result<String> {
+		let output = self
+			.lookup_command()
+			.output()
+			.map_err(|e| Error::SecretLookup(self.to_string(), e.to_string()))?;
+		if !output.status.success() {
+			let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+			return Err(Error::SecretLookup(self.to_string(), stderr));
+		}
+
+		let secret = String::from_utf8_lossy(&output.stdout);
+		Ok(secret.trim_end_matches(['\r', '\n']).to_string())
+	}
+
+	/// The platform command used to look up the secret.
+	fn lookup_command(&self) -> Command {
+		#[cfg(target_os = "macos")]
+		{
+			let mut command = Command::new("security");
+			command.args(["find-generic-password", "-s", self.service, "-a", self.account, "-w"]);
+			command
+		}
+		#[cfg(target_os = "windows")]
+		{
+			let script = format!(
+				"[void][Windows.Security.Credentials.PasswordVault,Windows.Security.Credentials,ContentType=WindowsRuntime];\
+				 $c = (New-Object Windows.Security.Credentials.PasswordVault).Retrieve('{}', '{}');\
+				 $c.RetrievePassword(); $c.Password",
+				self.service.replace('\'', "''"),
+				self.account.replace('\'', "''")
+			);
+			let mut command = Command::new("powershell.exe");
+			command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+			command
+		}
+		#[cfg(not(any(target_os = "macos", target_os = "windows")))]
+		{
+			let mut command = Command::new("secret-tool");
+			command.args(["lookup", "service", self.service, "account", self.account]);
+			command
+		}
+	}
+}
+
+impl std::fmt::Display for SecretRef<'_> {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		write!(f, "{KEYRING_SCHEME}{}/{}", self.service, self.account)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn references_parse_and_display_alike() {
+		for (reference, service, account) in [
+			("keyring:github/token", "github", "token"),
+			("keyring:aws/prod/access-key", "aws", "prod/access-key"),
+		] {
+			let secret = SecretRef::parse(reference).unwrap();
+			assert_eq!(secret, SecretRef { service, account });
+			assert_eq!(secret.to_string(), reference);
+		}
+	}
+
+	#[test]
+	fn malformed_references_are_rejected() {
+		for reference in [
+			"github/token",
+			"keyring:",
+			"keyring:github",
+			"keyring:/token",
+			"keyring:github/",
+			"vault:a/b",
+		] {
+			assert!(
+				matches!(SecretRef::parse(reference), Err(Error::InvalidSecretRef(r)) if r == reference),
+				"{reference}"
+			);
+		}
+	}
+}
@@ -0,0 +1,134 @@
+#[cfg(not(target_os = "windows"))]
+use std::path::PathBuf;
+use std::process::Command;
+
+use crate::{error::Error, Result};
+
+/// The name the background service is registered under.
+pub const SERVICE_NAME: &str = "cmdlink-watch";
+
+/// Registers `cmdlink watch` as a background service that starts on login: a
+/// systemd user unit on Linux, a launchd agent on macOS, and a Task Scheduler
+/// job on Windows. Returns the location of the service definition.
+pub fn install() -> Result<String> {
+	let exe = std::env::current_exe().map_err(Error::CurrentExe)?;
+
+	#[cfg(target_os = "linux")]
+	{
+		let unit_path = systemd_unit_path()?;
+		let unit = format!(
+			"[Unit]\nDescription=cmdlink watch: regenerates alias shims on config changes\n\n\
+			 [Service]\nExecStart=\"{}\" watch\nRestart=on-failure\n\n\
+			 [Install]\nWantedBy=default.target\n",
+			exe.display()
+		);
+		write_definition(&unit_path, &unit)?;
+		run(Command::new("systemctl").args(["--user", "daemon-reload"]))?;
+		run(Command::new("systemctl").args(["--user", "enable", "--now", &format!("{SERVICE_NAME}.service")]))?;
+		Ok(unit_path.display().to_string())
+	}
+	#[cfg(target_os = "macos")]
+	{
+		let plist_path = launchd_plist_path()?;
+		let plist = format!(
+			"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+			 <!DOCTYPE plist PUBLIC \"-//Apple//DTD PLIST 1.0//EN\" \"http://www.apple.com/DTDs/PropertyList-1.0.dtd\">\n\
+			 <plist version=\"1.0\">\n<dict>\n\
+			 \t<key>Label</key>\n\t<string>{}</string>\n\
+			 \t<key>ProgramArguments</key>\n\t<array>\n\t\t<string>{}</string>\n\t\t<string>watch</string>\n\t</array>\n\
+			 \t<key>RunAtLoad</key>\n\t<true/>\n\
+			 \t<key>KeepAlive</key>\n\t<true/>\n\
+			 </dict>\n</plist>\n",
+			launchd_label(),
+			exe.display()
+		);
+		write_definition(&plist_path, &plist)?;
+		run(Command::new("launchctl").arg("load").arg("-w").arg(&plist_path))?;
+		Ok(plist_path.display().to_string())
+	}
+	#[cfg(target_os = "windows")]
+	{
+		let task = format!("\"{}\" watch --quiet", exe.display());
+		run(Command::new("schtasks").args(["/Create", "/TN", SERVICE_NAME, "/TR", &task, "/SC", "ONLOGON", "/F"]))?;
+		run(Command::new("schtasks").args(["/Run", "/TN", SERVICE_NAME]))?;
+		Ok(format!("Task Scheduler job '{SERVICE_NAME}'"))
+	}
+}
+
+/// Stops and removes the background service registered by [install].
+pub fn uninstall() -> Result<()> {
+	#[cfg(target_os = "linux")]
+	{
+		let unit_path = systemd_unit_path()?;
+		run(Command::new("systemctl").args(["--user", "disable", "--now", &format!("{SERVICE_NAME}.service")]))?;
+		remove_definition(&unit_path)?;
+		run(Command::new("systemctl").args(["--user", "daemon-reload"]))
+	}
+	#[cfg(target_os = "macos")]
+	{
+		let plist_path = launchd_plist_path()?;
+		run(Command::new("launchctl").arg("unload").arg("-w").arg(&plist_path))?;
+		remove_definition(&plist_path)
+	}
+	#[cfg(target_os = "windows")]
+	{
+		// Ending the task fails if it isn't running, which is fine.
+		let _ = Command::new("schtasks").args(["/End", "/TN", SERVICE_NAME]).status();
+		run(Command::new("schtasks").args(["/Delete", "/TN", SERVICE_NAME, "/F"]))
+	}
+}
+
+#[cfg(target_os = "linux")]
+/// The path of the systemd user unit.
+fn systemd_unit_path() -> Result<PathBuf> {
+	let config_dir = dirs::config_dir().ok_or_else(|| Error::Service("config directory not found".to_string()))?;
+	Ok(config_dir
+		.join("systemd")
+		.join("user")
+		.join(format!("{SERVICE_NAME}.service")))
+}
+
+#[cfg(target_os = "macos")]
+/// The launchd label of the agent.
+fn launchd_label() -> String { format!("com.cmdlink.{}", SERVICE_NAME.trim_start_matches("cmdlink-")) }
+
+#[cfg(target_os = "macos")]
+/// The path of the launchd agent plist.
+fn launchd_plist_path() -> Result<PathBuf> {
+	let home = dirs::home_dir().ok_or_else(|| Error::Service("home directory not found".to_string()))?;
+	Ok(home
+		.join("Library")
+		.join("LaunchAgents")
+		.join(format!("{}.plist", launchd_label())))
+}
+
+#[cfg(not(target_os = "windows"))]
+/// Writes a service definition file, creating its parent directories.
+fn write_definition(path: &PathBuf, contents: &str) -> Result<()> {
+	if let Some(parent) = path.parent() {
+		std::fs::create_dir_all(parent).map_err(|e| Error::Service(format!("{}: {e}", parent.display())))?;
+	}
+	std::fs::write(path, contents).map_err(|e| Error::Service(format!("{}: {e}", path.display())))
+}
+
+#[cfg(not(target_os = "windows"))]
+/// Removes a service definition file, ignoring files that don't exist.
+fn remove_definition(path: &PathBuf) -> Result<()> {
+	match std::fs::remove_file(path) {
+		Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(Error::Service(format!("{}: {e}", path.display()))),
+		_ => Ok(()),
+	}
+}
+
+/// Runs a service manager command, failing if it exits unsuccessfully.
+fn run(command: &mut Command) -> Result<()> {
+	let program = command.get_program().to_string_lossy().into_owned();
+	debug!("Running {:?}", command);
+	let status = command
+		.status()
+		.map_err(|e| Error::Service(format!("failed to run {program}: {e}")))?;
+	if !status.success() {
+		return Err(Error::Service(format!("{program} exited with {status}")));
+	}
+	Ok(())
+}
@@ -0,0 +1,275 @@
+//! Global preferences, configured through the `[settings]` table of the
+//! config.
+
+use std::{io::IsTerminal, path::PathBuf};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tabled::{settings::Style, Table};
+
+use crate::{error::Error, Result};
+
+/// The settings accessible through `config get` and `config set`.
+pub const SETTINGS: [&str; 12] = [
+	"auto_refresh",
+	"bins_dir",
+	"color",
+	"confirm",
+	"gc_keep",
+	"gc_max_age",
+	"locale",
+	"log_file",
+	"path_scope",
+	"run_history",
+	"table_style",
+	"windows_extension",
+];
+
+#[derive(Default, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+/// The `[settings]` table of the config. Unset settings use their defaults.
+pub struct Settings {
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Repairs missing and outdated links whenever the config is loaded,
+	/// instead of warning about them.
+	pub auto_refresh: Option<bool>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The directory link files are stored in, set by `cmdlink relocate`.
+	/// Defaults to `bins` in the project directory.
+	pub bins_dir: Option<PathBuf>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Whether log output on the terminal is colored.
+	pub color: Option<ColorMode>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// How yes/no questions are answered.
+	pub confirm: Option<ConfirmMode>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The number of the most recent runs and unused cached releases that
+	/// `cmdlink gc` keeps regardless of their age. See [gc](crate::gc).
+	pub gc_keep: Option<usize>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// How old runs and unused cached releases have to be for `cmdlink gc` to
+	/// remove them, e.g. `90d`.
+	pub gc_max_age: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The locale messages are shown in, e.g. `de` or `pt_BR`, overriding the
+	/// `LANG` environment variable. See [i18n](crate::i18n).
+	pub locale: Option<String>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// A file log output is appended to, in addition to the terminal.
+	pub log_file: Option<PathBuf>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Whose PATH `init` and `relocate` add the bins directory to.
+	pub path_scope: Option<PathScope>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// Makes links record the exit code and duration of every run, shown by
+	/// `cmdlink history`. See [runs](crate::runs).
+	pub run_history: Option<bool>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The border style of the tables printed by commands such as `display`.
+	pub table_style: Option<TableStyle>,
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	/// The extension of link files on Windows, unless overridden per alias.
+	pub windows_extension: Option<WindowsExtension>,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// When log output on the terminal is colored.
+pub enum ColorMode {
+	/// Colors output if stderr is a terminal and `NO_COLOR` isn't set.
+	#[default]
+	Auto,
+	/// Always colors output.
+	Always,
+	/// Never colors output.
+	Never,
+}
+
+impl ColorMode {
+	/// Whether or not output to stderr should be colored.
+	pub fn enabled(self) -> bool {
+		match self {
+			ColorMode::Auto => std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none(),
+			ColorMode::Always => true,
+			ColorMode::Never => false,
+		}
+	}
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// How yes/no questions, such as whether to add the bins directory to PATH,
+/// are answered.
+pub enum ConfirmMode {
+	/// Asks the user, failing if cmdlink runs non-interactively.
+	#[default]
+	Ask,
+	/// Answers yes without asking.
+	Yes,
+	/// Answers no without asking.
+	No,
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// Whose PATH the bins directory is added to.
+pub enum PathScope {
+	/// The PATH of the current user.
+	#[default]
+	User,
+	/// The PATH of every user of the machine, e.g. on shared lab machines.
+	/// Changing it requires an elevated prompt on Windows, or root elsewhere.
+	Machine,
+}
+
+impl std::fmt::Display for PathScope {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		f.write_str(&match self {
+			PathScope::User => crate::tr!("path.scope_user"),
+			PathScope::Machine => crate::tr!("path.scope_machine"),
+		})
+	}
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The border style of printed tables.
+pub enum TableStyle {
+	/// Box drawing characters with rounded corners.
+	#[default]
+	Rounded,
+	/// Box drawing characters with square corners.
+	Sharp,
+	/// Box drawing characters without lines between rows.
+	Modern,
+	/// Plain ASCII characters, for terminals without Unicode support.
+	Ascii,
+	/// A Markdown table.
+	Markdown,
+	/// PostgreSQL style, with only inner lines.
+	Psql,
+	/// No borders at all.
+	Blank,
+}
+
+impl TableStyle {
+	/// Applies the style to the table.
+	pub fn apply(self, table: &mut Table) {
+		match self {
+			TableStyle::Rounded => table.with(Style::rounded()),
+			TableStyle::Sharp => table.with(Style::sharp()),
+			TableStyle::Modern => table.with(Style::modern()),
+			TableStyle::Ascii => table.with(Style::ascii()),
+			TableStyle::Markdown => table.with(Style::markdown()),
+			TableStyle::Psql => table.with(Style::psql()),
+			TableStyle::Blank => table.with(Style::blank()),
+		};
+	}
+}
+
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+/// The extension of batch file links on Windows. Some environments, such as
+/// certain task runners and AppLocker policies, treat the two differently.
+pub enum WindowsExtension {
+	/// `.bat` files.
+	#[default]
+	Bat,
+	/// `.cmd` files.
+	Cmd,
+}
+
+impl WindowsExtension {
+	/// All the extensions, in the order link files are looked up.
+	pub const ALL: [WindowsExtension; 2] = [WindowsExtension::Bat, WindowsExtension::Cmd];
+
+	/// The extension including the leading dot, e.g. `.bat`.
+	pub fn as_str(self) -> &'static str {
+		match self {
+			WindowsExtension::Bat => ".bat",
+			WindowsExtension::Cmd => ".cmd",
+		}
+	}
+}
+
+impl Settings {
+	/// Whether or not no settings are set.
+	pub fn is_empty(&self) -> bool { *self == Settings::default() }
+
+	/// The value of a setting as it would be written with `config set`, or
+	/// `None` if it isn't set.
+	pub fn get(&self, key: &str) -> Result<Option<String>> {
+		Ok(match key {
+			"auto_refresh" => self.auto_refresh.map(to_value),
+			"bins_dir" => self.bins_dir.as_ref().map(|path| path.display().to_string()),
+			"color" => self.color.map(to_value),
+			"confirm" => self.confirm.map(to_value),
+			"gc_keep" => self.gc_keep.map(to_value),
+			"gc_max_age" => self.gc_max_age.clone(),
+			"locale" => self.locale.clone(),
+			"log_file" => self.log_file.as_ref().map(|path| path.display().to_string()),
+			"path_scope" => self.path_scope.map(to_value),
+			"run_history" => self.run_history.map(to_value),
+			"table_style" => self.table_style.map(to_value),
+			"windows_extension" => self.windows_extension.map(to_value),
+			_ => return Err(Error::UnknownSetting(key.to_string())),
+		})
+	}
+
+	/// Changes a setting, or resets it to its default if `value` is `None`.
+	/// The bins directory can only be changed through `cmdlink relocate`,
+	/// which also moves the link files.
+	pub fn set(&mut self, key: &str, value: Option<&str>) -> Result<()> {
+		let invalid = |reason: &str| Error::InvalidSetting(key.to_string(), reason.to_string());
+		match key {
+			"auto_refresh" => {
+				self.auto_refresh = value
+					.map(|value| value.parse().map_err(|_| invalid("expected `true` or `false`")))
+					.transpose()?
+			},
+			"bins_dir" => return Err(invalid("run `cmdlink relocate` to move the bins directory")),
+			"color" => self.color = value.map(|value| from_value(key, value)).transpose()?,
+			"confirm" => self.confirm = value.map(|value| from_value(key, value)).transpose()?,
+			"gc_keep" => {
+				self.gc_keep = value
+					.map(|value| value.parse().map_err(|_| invalid("expected a number of entries")))
+					.transpose()?
+			},
+			"gc_max_age" => {
+				if let Some(value) = value {
+					humantime::parse_duration(value).map_err(|e| invalid(&e.to_string()))?;
+				}
+				self.gc_max_age = value.map(str::to_string)
+			},
+			"locale" if value == Some("") => return Err(invalid("the locale cannot be empty")),
+			"locale" => self.locale = value.map(str::to_string),
+			"log_file" if value == Some("") => return Err(invalid("the path cannot be empty")),
+			"log_file" => self.log_file = value.map(PathBuf::from),
+			"path_scope" => self.path_scope = value.map(|value| from_value(key, value)).transpose()?,
+			"run_history" => {
+				self.run_history = value
+					.map(|value| value.parse().map_err(|_| invalid("expected `true` or `false`")))
+					.transpose()?
+			},
+			"table_style" => self.table_style = value.map(|value| from_value(key, value)).transpose()?,
+			"windows_extension" => self.windows_extension = value.map(|value| from_value(key, value)).transpose()?,
+			_ => return Err(Error::UnknownSetting(key.to_string())),
+		}
+		Ok(())
+	}
+}
+
+/// Renders a setting value the way it's written in the config.
+fn to_value<T: Serialize>(value: T) -> String {
+	match toml::Value::try_from(value) {
+		Ok(toml::Value::String(value)) => value,
+		Ok(value) => value.to_string(),
+		Err(_) => String::new(),
+	}
+}
+
+/// Parses a setting value the way it's written in the config.
+fn from_value<T: DeserializeOwned>(key: &str, value: &str) -> Result<T> {
+	toml::Value::String(value.to_string())
+		.try_into()
+		.map_err(|e: toml::de::Error| Error::InvalidSetting(key.to_string(), e.message().to_string()))
+}
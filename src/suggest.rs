@@ -0,0 +1,88 @@
+//! Suggesting short alias names for commands.
+
+use std::path::Path;
+
+use crate::{alias, which};
+
+/// Proposes up to `count` short alias names for the command, derived from the
+/// initials and prefixes of its words and flags, e.g. `kgp` and `kgpa` for
+/// `kubectl get pods --all-namespaces`.
+///
+/// Names for which `taken` returns true are skipped, as are names of
+/// executables on PATH, so that the alias doesn't shadow a real command. When
+/// every candidate is taken, numbered variants of the shortest one are
+/// proposed instead.
+pub fn names(cmd: &str, count: usize, taken: impl Fn(&str) -> bool) -> Vec<String> {
+	let words = words(cmd);
+	let Some(first) = words.first() else {
+		return Vec::new();
+	};
+	let commands: Vec<&str> = words.iter().filter(|w| !w.is_flag).map(|w| w.text.as_str()).collect();
+	let initials = |words: &mut dyn Iterator<Item = &str>| words.filter_map(|w| w.chars().next()).collect::<String>();
+
+	let mut candidates = vec![
+		initials(&mut commands.iter().copied()),
+		initials(&mut words.iter().map(|w| w.text.as_str())),
+		initials(&mut commands.iter().copied().take(2)),
+	];
+	for len in 2..=4 {
+		let prefix: String = first.text.chars().take(len).collect();
+		candidates.push(prefix.clone() + &initials(&mut commands.iter().copied().skip(1)));
+		candidates.push(prefix);
+	}
+	candidates.push(commands.join("-"));
+
+	let available = |name: &str| {
+		name.chars().count() >= 2
+			&& alias::validate_name(name).is_ok()
+			&& !taken(name)
+			&& which::find_executable(name).is_none()
+	};
+	let mut names: Vec<String> = Vec::new();
+	for candidate in candidates {
+		if names.len() < count && !names.contains(&candidate) && available(&candidate) {
+			names.push(candidate);
+		}
+	}
+
+	// Fall back to numbered variants of the shortest candidate.
+	let base = initials(&mut commands.iter().copied());
+	let numbered = (2..).map(|n| format!("{base}{n}")).filter(|name| available(name));
+	names.extend(numbered.take(count - names.len()));
+	names
+}
+
+/// A word of a command, normalized for use in alias names.
+struct Word {
+	/// The lowercase alphanumeric text of the word.
+	text: String,
+	/// Whether or not the word is a flag, such as `--all-namespaces`.
+	is_flag: bool,
+}
+
+/// Splits a command into normalized words. Programs are reduced to their file
+/// stem, so `./scripts/deploy.sh` becomes `deploy`, and words without any
+/// alphanumeric characters, such as `|`, are dropped.
+fn words(cmd: &str) -> Vec<Word> {
+	cmd.split_whitespace()
+		.enumerate()
+		.filter_map(|(idx, word)| {
+			let word = word.trim_matches(['"', '\'']);
+			let is_flag = word.starts_with('-');
+			let word = if idx == 0 {
+				Path::new(word)
+					.file_stem()
+					.and_then(|stem| stem.to_str())
+					.unwrap_or(word)
+			} else {
+				word.trim_start_matches('-')
+			};
+			let text: String = word
+				.chars()
+				.filter(char::is_ascii_alphanumeric)
+				.map(|c| c.to_ascii_lowercase())
+				.collect();
+			(!text.is_empty()).then_some(Word { text, is_flag })
+		})
+		.collect()
+}
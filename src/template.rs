@@ -0,0 +1,110 @@
+//! Interpolation of template variables in alias commands, applied when link
+//! files are generated.
+//!
+//! The following placeholders are supported:
+//!
+//! - `{home}`: the user's home directory.
+//! - `{config_dir}`: the platform configuration directory (e.g. `~/.config`).
+//! - `{cmdlink_dir}`: the cmdlink project directory.
+//! - `${VAR}`: the value of the environment variable `VAR` when the link is
+//!   generated, so e.g. `${PWD}` is fixed to the directory cmdlink ran in.
+//!
+//! Placeholders can be escaped by doubling their opening character, so
+//! `{{home}` produces a literal `{home}` and `$${VAR}` a literal `${VAR}` (for
+//! variables that should be expanded by the shell at run time instead). Any
+//! other text, including unknown `{...}` sequences and `${...}` ones that
+//! aren't a variable name such as `${1:-default}` or `${@}`, is left
+//! untouched.
+
+use std::path::Path;
+
+use crate::alias::is_valid_env_key;
+
+/// Expands all template variables in the input, with `cmdlink_dir` as the
+/// project directory, returning the name of the first undefined variable on
+/// failure.
+pub fn expand(input: &str, cmdlink_dir: &Path) -> Result<String, String> {
+	let mut output = String::with_capacity(input.len());
+	let mut rest = input;
+
+	while let Some(idx) = rest.find(['{', '$']) {
+		output.push_str(&rest[..idx]);
+		rest = &rest[idx..];
+
+		if let Some(after) = rest.strip_prefix("$${") {
+			output.push_str("${");
+			rest = after;
+		} else if let Some((name, after)) = rest
+			.strip_prefix("${")
+			.and_then(|after| after.split_once('}'))
+			.filter(|(name, _)| is_valid_env_key(name))
+		{
+			let value = std::env::var(name).map_err(|_| name.to_string())?;
+			output.push_str(&value);
+			rest = after;
+		} else if let Some((placeholder, after)) = rest.strip_prefix("{{").and_then(split_placeholder) {
+			output.push('{');
+			output.push_str(placeholder);
+			output.push('}');
+			rest = after;
+		} else if let Some((placeholder, after)) = rest.strip_prefix('{').and_then(split_placeholder) {
+			output.push_str(&placeholder_value(placeholder, cmdlink_dir));
+			rest = after;
+		} else {
+			// A lone `{` or `$` that doesn't start a placeholder or variable.
+			output.push_str(&rest[..1]);
+			rest = &rest[1..];
+		}
+	}
+	output.push_str(rest);
+	Ok(output)
+}
+
+/// Splits a known placeholder name (without its opening brace) from the rest
+/// of the input.
+fn split_placeholder(input: &str) -> Option<(&str, &str)> {
+	["home", "config_dir", "cmdlink_dir"]
+		.into_iter()
+		.find_map(|name| input.strip_prefix(name)?.strip_prefix('}').map(|after| (name, after)))
+}
+
+/// The value of a known placeholder.
+fn placeholder_value(placeholder: &str, cmdlink_dir: &Path) -> String {
+	let path = match placeholder {
+		"home" => dirs::home_dir(),
+		"config_dir" => dirs::config_dir(),
+		_ => Some(cmdlink_dir.to_path_buf()),
+	};
+	path.map(|p| p.display().to_string()).unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn expand(input: &str) -> Result<String, String> { super::expand(input, Path::new("/project")) }
+
+	#[test]
+	fn expands_placeholders_and_variables() {
+		std::env::set_var("CMDLINK_TEMPLATE_TEST", "value");
+		assert_eq!(expand("ls {cmdlink_dir}/bins").unwrap(), "ls /project/bins");
+		assert_eq!(expand("echo ${CMDLINK_TEMPLATE_TEST}!").unwrap(), "echo value!");
+		assert_eq!(
+			expand("echo ${CMDLINK_TEMPLATE_UNDEFINED}"),
+			Err("CMDLINK_TEMPLATE_UNDEFINED".to_string())
+		);
+	}
+
+	#[test]
+	fn escapes_are_kept_literally() {
+		assert_eq!(expand("echo {{home} $${HOME}").unwrap(), "echo {home} ${HOME}");
+		assert_eq!(expand("echo {{other} $$HOME").unwrap(), "echo {{other} $$HOME");
+	}
+
+	#[test]
+	fn other_braces_are_left_untouched() {
+		for input in ["echo {unknown} {home", "echo ${1:-default} ${@} ${#} ${", "awk '{print $1}'"] {
+			assert_eq!(expand(input).unwrap(), input);
+		}
+	}
+}
@@ -0,0 +1,59 @@
+//! Running the link of an alias with tracing turned on, as done by
+//! `cmdlink test`, to see exactly which command and arguments its target
+//! receives after expansion and quoting.
+
+use std::process::{Command, ExitStatus};
+
+use crate::{error::Error, platform_binary::Link, Result};
+
+/// The contents of the link with tracing turned on, so that the shell echoes
+/// every command after expansion: `set -x` for `sh`, `@echo on` for `cmd`.
+pub fn script(link: &impl Link) -> Result<String> {
+	let contents = link.contents()?;
+	#[cfg(target_os = "windows")]
+	{
+		Ok(contents.replacen("@echo off", "@echo on", 1))
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		Ok(contents.replacen("#!/bin/sh\n", "#!/bin/sh\nset -x\n", 1))
+	}
+}
+
+/// Writes the traced [script] of the link to a temporary file and runs it with
+/// the given arguments, returning its exit status. The link in the bins
+/// directory is left untouched, so outdated links are tested as configured.
+pub fn run(link: &impl Link, args: &[String]) -> Result<ExitStatus> { run_script(link, &script(link)?, args, |_| {}) }
+
+/// Writes a script for the link to a temporary file and runs it with the given
+/// arguments, after `configure` had a chance to adjust the command.
+pub fn run_script(
+	link: &impl Link, script: &str, args: &[String], configure: impl FnOnce(&mut Command),
+) -> Result<ExitStatus> {
+	let error = |e| Error::AliasRun(link.alias().to_string(), e);
+	let path = std::env::temp_dir().join(format!(
+		"cmdlink-run-{}-{}{}",
+		link.alias(),
+		std::process::id(),
+		link.extension()
+	));
+	std::fs::write(&path, script).map_err(error)?;
+
+	#[cfg(target_os = "windows")]
+	let mut command = {
+		let mut command = Command::new("cmd");
+		command.arg("/d").arg("/c").arg(&path);
+		command
+	};
+	#[cfg(not(target_os = "windows"))]
+	let mut command = {
+		let mut command = Command::new("sh");
+		command.arg(&path);
+		command
+	};
+	configure(&mut command);
+	let status = command.args(args).status();
+
+	let _ = std::fs::remove_file(&path);
+	status.map_err(error)
+}
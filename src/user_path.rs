@@ -0,0 +1,463 @@
+//! Adding the bins directory to the user's `PATH`, as done by `cmdlink init`,
+//! removing it again when the bins directory is relocated, and diagnosing why
+//! it isn't picked up, as done by `cmdlink env`.
+
+use std::{
+	fmt,
+	path::{Path, PathBuf},
+};
+
+use crate::{error::Error, settings::PathScope, Result};
+
+/// The file the machine-wide `export` line is written to on platforms other
+/// than Windows, which login shells source for every user.
+#[cfg(not(target_os = "windows"))]
+const MACHINE_PROFILE: &str = "/etc/profile.d/cmdlink.sh";
+
+/// The registry key holding the machine-wide environment on Windows.
+#[cfg(target_os = "windows")]
+const MACHINE_ENVIRONMENT: &str = r"SYSTEM\CurrentControlSet\Control\Session Manager\Environment";
+
+/// Adds the directory to the user's or machine's `PATH`, returning whether or
+/// not it had to be added.
+///
+/// On Windows the `Path` value of `HKCU\Environment` is edited directly, which
+/// doesn't require elevation, or that of the machine environment under `HKLM`,
+/// which does. Running programs are notified of the change, but open terminals
+/// have to be restarted to pick it up. On other platforms an `export` line is
+/// appended to `~/.profile`, or to `/etc/profile.d/cmdlink.sh` for the machine.
+pub fn add(dir: &Path, scope: PathScope) -> Result<bool> {
+	#[cfg(target_os = "windows")]
+	{
+		add_windows(dir, scope)
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		add_profile(dir, scope)
+	}
+}
+
+/// Removes the directory from the user's or machine's `PATH`, returning
+/// whether or not it was listed. Only entries added by [add] are recognized; on
+/// platforms other than Windows, that's the `export` line it appended.
+pub fn remove(dir: &Path, scope: PathScope) -> Result<bool> {
+	#[cfg(target_os = "windows")]
+	{
+		remove_windows(dir, scope)
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		remove_profile(dir, scope)
+	}
+}
+
+/// Directories holding the programs of the operating system. Aliases named like
+/// one of their programs are shadowed by it if the directory comes first on
+/// PATH.
+#[cfg(not(target_os = "windows"))]
+const SYSTEM_DIRS: [&str; 6] = ["/usr/local/sbin", "/usr/local/bin", "/usr/sbin", "/usr/bin", "/sbin", "/bin"];
+
+/// The shell startup files checked for the directory, relative to the home
+/// directory unless absolute.
+#[cfg(not(target_os = "windows"))]
+const RC_FILES: [&str; 7] = [
+	MACHINE_PROFILE,
+	".profile",
+	".bash_profile",
+	".bashrc",
+	".zprofile",
+	".zshrc",
+	".config/fish/config.fish",
+];
+
+/// How a directory is set up on the user's PATH, as printed by `cmdlink env`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostics {
+	/// The directory that was checked.
+	pub dir: PathBuf,
+	/// Whether or not the directory exists.
+	pub exists: bool,
+	/// The number of entries on the PATH of the current process.
+	pub path_len: usize,
+	/// The index of the directory on the PATH of the current process, if it's
+	/// listed.
+	pub position: Option<usize>,
+	/// The system directories listed before the directory, whose programs take
+	/// precedence over aliases with the same name.
+	pub system_dirs_before: Vec<PathBuf>,
+	/// Whether or not the directory is on the PATH new terminals get: the user
+	/// or machine `Path` value in the registry on Windows, or a shell startup
+	/// file elsewhere.
+	pub persisted: bool,
+	/// The shell startup files mentioning the directory. Always empty on
+	/// Windows.
+	pub rc_files: Vec<PathBuf>,
+}
+
+/// Checks how the directory is set up on the user's PATH.
+pub fn diagnose(dir: &Path) -> Diagnostics {
+	let path: Vec<PathBuf> = std::env::var_os("PATH")
+		.map(|path| std::env::split_paths(&path).collect())
+		.unwrap_or_default();
+	let position = path.iter().position(|entry| same_dir(entry, dir));
+	let system_dirs_before = path[..position.unwrap_or(0)]
+		.iter()
+		.filter(|entry| is_system_dir(entry))
+		.cloned()
+		.collect();
+
+	#[cfg(target_os = "windows")]
+	let (persisted, rc_files) = (persisted_windows(dir), Vec::new());
+	#[cfg(not(target_os = "windows"))]
+	let (persisted, rc_files) = {
+		let rc_files = rc_files_mentioning(dir);
+		(!rc_files.is_empty(), rc_files)
+	};
+
+	Diagnostics {
+		dir: dir.to_path_buf(),
+		exists: dir.is_dir(),
+		path_len: path.len(),
+		position,
+		system_dirs_before,
+		persisted,
+		rc_files,
+	}
+}
+
+impl fmt::Display for Diagnostics {
+	/// Renders the diagnostics followed by hints for fixing any problems.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let list = |paths: &[PathBuf]| {
+			paths
+				.iter()
+				.map(|path| path.display().to_string())
+				.collect::<Vec<_>>()
+				.join(", ")
+		};
+		let exists = if self.exists { "exists" } else { "missing" };
+		writeln!(f, "Bins directory:  {} ({exists})", self.dir.display())?;
+		match self.position {
+			Some(idx) => writeln!(f, "Current PATH:    entry {} of {}", idx + 1, self.path_len)?,
+			None => writeln!(f, "Current PATH:    not listed")?,
+		}
+		if !self.system_dirs_before.is_empty() {
+			writeln!(f, "Preceded by:     {}", list(&self.system_dirs_before))?;
+		}
+		writeln!(f, "Persisted PATH:  {}", if self.persisted { "yes" } else { "no" })?;
+		if cfg!(not(target_os = "windows")) {
+			let rc_files = if self.rc_files.is_empty() {
+				"none".to_string()
+			} else {
+				list(&self.rc_files)
+			};
+			writeln!(f, "Shell rc files:  {rc_files}")?;
+		}
+
+		if !self.exists {
+			writeln!(f, "\nhelp: run `cmdlink init` to create the bins directory")?;
+		}
+		if !self.persisted && self.position.is_none() {
+			writeln!(f, "\nhelp: run `cmdlink init --path` to add the bins directory to your PATH")?;
+		} else if self.position.is_none() {
+			writeln!(
+				f,
+				"\nhelp: the bins directory is only added to new terminals, restart yours or log in again"
+			)?;
+		}
+		if !self.system_dirs_before.is_empty() {
+			writeln!(
+				f,
+				"\nhelp: aliases named like programs in the directories above are shadowed by them, move the bins \
+				 directory to the front of your PATH to prefer the aliases"
+			)?;
+		}
+		Ok(())
+	}
+}
+
+/// Whether or not two PATH entries name the same directory, ignoring trailing
+/// separators, and case on Windows.
+fn same_dir(entry: &Path, dir: &Path) -> bool {
+	let normalize = |path: &Path| {
+		let path = path.display().to_string();
+		let path = path.trim_end_matches(['/', '\\']);
+		if cfg!(target_os = "windows") {
+			path.to_lowercase()
+		} else {
+			path.to_string()
+		}
+	};
+	normalize(entry) == normalize(dir)
+}
+
+/// Whether or not a PATH entry is a directory of the operating system.
+fn is_system_dir(entry: &Path) -> bool {
+	#[cfg(target_os = "windows")]
+	{
+		let system_root = std::env::var_os("SystemRoot").unwrap_or_else(|| r"C:\Windows".into());
+		entry
+			.display()
+			.to_string()
+			.to_lowercase()
+			.starts_with(&Path::new(&system_root).display().to_string().to_lowercase())
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		SYSTEM_DIRS
+			.iter()
+			.any(|system_dir| same_dir(entry, Path::new(system_dir)))
+	}
+}
+
+#[cfg(target_os = "windows")]
+/// Whether or not the directory is listed in the user or machine `Path` value
+/// in the registry.
+fn persisted_windows(dir: &Path) -> bool {
+	use windows_registry::{CURRENT_USER, LOCAL_MACHINE};
+
+	[CURRENT_USER.open("Environment"), LOCAL_MACHINE.open(MACHINE_ENVIRONMENT)]
+		.into_iter()
+		.flatten()
+		.any(|environment| {
+			let current = environment.get_string("Path").unwrap_or_default();
+			current.split(';').any(|entry| same_dir(Path::new(entry), dir))
+		})
+}
+
+#[cfg(target_os = "windows")]
+/// Opens the registry key holding the environment of the given scope for
+/// writing. Opening the machine environment without elevation is reported as
+/// [Error::PathAccessDenied].
+fn environment_key(scope: PathScope) -> Result<windows_registry::Key> {
+	use windows_registry::{CURRENT_USER, LOCAL_MACHINE};
+
+	match scope {
+		PathScope::User => CURRENT_USER.create("Environment"),
+		PathScope::Machine => LOCAL_MACHINE.create(MACHINE_ENVIRONMENT),
+	}
+	.map_err(|e| {
+		// E_ACCESSDENIED, as returned without elevation.
+		if e.code().0 as u32 == 0x8007_0005 {
+			Error::PathAccessDenied(scope, e.message())
+		} else {
+			Error::UserPath(e.message())
+		}
+	})
+}
+
+#[cfg(not(target_os = "windows"))]
+/// The shell startup files in the home directory that mention the directory.
+fn rc_files_mentioning(dir: &Path) -> Vec<PathBuf> {
+	let Some(home) = dirs::home_dir() else {
+		return Vec::new();
+	};
+	let dir = dir.display().to_string();
+	RC_FILES
+		.iter()
+		.map(|file| home.join(file))
+		.filter(|file| std::fs::read_to_string(file).is_ok_and(|contents| contents.contains(&dir)))
+		.collect()
+}
+
+#[cfg(target_os = "windows")]
+/// Appends the directory to the user or machine `Path` value in the registry.
+fn add_windows(dir: &Path, scope: PathScope) -> Result<bool> {
+	use windows_registry::Type;
+
+	let to_error = |e: windows_result::Error| Error::UserPath(e.message());
+	let environment = environment_key(scope)?;
+
+	// A missing value is an empty PATH. The value type is kept so that entries
+	// such as `%USERPROFILE%\bin` in a REG_EXPAND_SZ value keep expanding.
+	let (current, ty) = match environment.get_type("Path") {
+		Ok(ty) => (environment.get_string("Path").map_err(to_error)?, ty),
+		Err(_) => (String::new(), Type::ExpandString),
+	};
+	let Some(updated) = append_entry(&current, dir, ';') else {
+		return Ok(false);
+	};
+
+	match ty {
+		Type::String => environment.set_string("Path", &updated),
+		_ => environment.set_expand_string("Path", &updated),
+	}
+	.map_err(to_error)?;
+	broadcast_environment_change();
+	Ok(true)
+}
+
+#[cfg(target_os = "windows")]
+/// Removes the directory from the user or machine `Path` value in the
+/// registry.
+fn remove_windows(dir: &Path, scope: PathScope) -> Result<bool> {
+	use windows_registry::Type;
+
+	let to_error = |e: windows_result::Error| Error::UserPath(e.message());
+	let environment = environment_key(scope)?;
+	let Ok(ty) = environment.get_type("Path") else {
+		return Ok(false);
+	};
+	let current = environment.get_string("Path").map_err(to_error)?;
+	let Some(updated) = remove_entry(&current, dir, ';') else {
+		return Ok(false);
+	};
+
+	match ty {
+		Type::String => environment.set_string("Path", &updated),
+		_ => environment.set_expand_string("Path", &updated),
+	}
+	.map_err(to_error)?;
+	broadcast_environment_change();
+	Ok(true)
+}
+
+#[cfg(target_os = "windows")]
+/// Notifies running programs, such as Explorer, that the environment changed so
+/// that newly started programs see the updated PATH.
+fn broadcast_environment_change() {
+	use windows_sys::Win32::UI::WindowsAndMessaging::{
+		SendMessageTimeoutW, HWND_BROADCAST, SMTO_ABORTIFHUNG, WM_SETTINGCHANGE,
+	};
+
+	let environment: Vec<u16> = "Environment\0".encode_utf16().collect();
+	// SAFETY: the lParam points to a NUL terminated UTF-16 string that outlives
+	// the call, and the result pointer may be null.
+	unsafe {
+		SendMessageTimeoutW(
+			HWND_BROADCAST,
+			WM_SETTINGCHANGE,
+			0,
+			environment.as_ptr() as isize,
+			SMTO_ABORTIFHUNG,
+			5000,
+			std::ptr::null_mut(),
+		);
+	}
+}
+
+#[cfg(not(target_os = "windows"))]
+/// Appends an export line for the directory to the profile of the scope,
+/// unless it already mentions it.
+fn add_profile(dir: &Path, scope: PathScope) -> Result<bool> {
+	use std::io::Write;
+
+	let profile_path = profile_path(scope)?;
+	let to_error = |e| profile_error(&profile_path, scope, e);
+
+	let dir = dir.display().to_string();
+	let profile = match std::fs::read_to_string(&profile_path) {
+		Ok(profile) => profile,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+		Err(e) => return Err(to_error(e)),
+	};
+	if profile.lines().any(|line| line.contains(&dir)) {
+		return Ok(false);
+	}
+
+	let mut file = std::fs::OpenOptions::new()
+		.create(true)
+		.append(true)
+		.open(&profile_path)
+		.map_err(to_error)?;
+	let separator = if profile.is_empty() || profile.ends_with('\n') {
+		""
+	} else {
+		"\n"
+	};
+	writeln!(file, "{separator}{}", export_line(&dir)).map_err(to_error)?;
+	Ok(true)
+}
+
+#[cfg(not(target_os = "windows"))]
+/// Removes the export line appended by [add_profile] from the profile of the
+/// scope. The machine profile is deleted once nothing else is left in it.
+fn remove_profile(dir: &Path, scope: PathScope) -> Result<bool> {
+	let profile_path = profile_path(scope)?;
+	let to_error = |e| profile_error(&profile_path, scope, e);
+
+	let profile = match std::fs::read_to_string(&profile_path) {
+		Ok(profile) => profile,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+		Err(e) => return Err(to_error(e)),
+	};
+	let export = export_line(&dir.display().to_string());
+	if !profile.lines().any(|line| line.trim() == export) {
+		return Ok(false);
+	}
+
+	let updated: String = profile
+		.lines()
+		.filter(|line| line.trim() != export)
+		.map(|line| format!("{line}\n"))
+		.collect();
+	if scope == PathScope::Machine && updated.trim().is_empty() {
+		std::fs::remove_file(&profile_path).map_err(to_error)?;
+	} else {
+		std::fs::write(&profile_path, updated).map_err(to_error)?;
+	}
+	Ok(true)
+}
+
+#[cfg(not(target_os = "windows"))]
+/// The profile the export line of the scope is written to.
+fn profile_path(scope: PathScope) -> Result<PathBuf> {
+	match scope {
+		PathScope::User => dirs::home_dir()
+			.map(|home| home.join(".profile"))
+			.ok_or_else(|| Error::UserPath("home directory not found".to_string())),
+		PathScope::Machine => Ok(PathBuf::from(MACHINE_PROFILE)),
+	}
+}
+
+#[cfg(not(target_os = "windows"))]
+/// Converts an error accessing a profile, reporting a denied write to the
+/// machine profile as [Error::PathAccessDenied].
+fn profile_error(profile_path: &Path, scope: PathScope, e: std::io::Error) -> Error {
+	let message = format!("{}: {e}", profile_path.display());
+	if scope == PathScope::Machine && e.kind() == std::io::ErrorKind::PermissionDenied {
+		Error::PathAccessDenied(scope, message)
+	} else {
+		Error::UserPath(message)
+	}
+}
+
+#[cfg(not(target_os = "windows"))]
+/// The line added to `~/.profile` to put the directory on PATH.
+fn export_line(dir: &str) -> String { format!("export PATH=\"$PATH:{dir}\"") }
+
+#[cfg(target_os = "windows")]
+/// Appends the directory to a PATH-like list, returning `None` if it's already
+/// listed. Entries are compared case-insensitively and ignoring trailing
+/// separators, and empty entries left by stray separators are dropped.
+fn append_entry(current: &str, dir: &Path, separator: char) -> Option<String> {
+	let dir = dir.display().to_string();
+	let normalize = |entry: &str| entry.trim_end_matches(['\\', '/']).to_lowercase();
+	let mut entries: Vec<&str> = current
+		.split(separator)
+		.filter(|entry| !entry.trim().is_empty())
+		.collect();
+	if entries.iter().any(|entry| normalize(entry) == normalize(&dir)) {
+		return None;
+	}
+	entries.push(&dir);
+	Some(entries.join(&separator.to_string()))
+}
+
+#[cfg(target_os = "windows")]
+/// Removes the directory from a PATH-like list, returning `None` if it isn't
+/// listed. Entries are compared like in [append_entry].
+fn remove_entry(current: &str, dir: &Path, separator: char) -> Option<String> {
+	let dir = dir.display().to_string();
+	let normalize = |entry: &str| entry.trim_end_matches(['\\', '/']).to_lowercase();
+	let entries: Vec<&str> = current
+		.split(separator)
+		.filter(|entry| !entry.trim().is_empty())
+		.collect();
+	let kept: Vec<&str> = entries
+		.iter()
+		.copied()
+		.filter(|entry| normalize(entry) != normalize(&dir))
+		.collect();
+	(kept.len() != entries.len()).then(|| kept.join(&separator.to_string()))
+}
@@ -0,0 +1,163 @@
+//! Version numbers and requirements, for the `min_version` check of aliases
+//! and the compatibility of packs.
+//!
+//! Requirements are comparisons such as `>=20` or `>=1.2, <2`, all of which
+//! must hold. A comparison only looks at as many components of the version as
+//! it names, so `<=2` allows `2.9` and `=20` any `20.x`.
+
+use std::{cmp::Ordering, process::Command};
+
+use crate::{error::Error, Result};
+
+/// The comparison operators, longest first so that `>=` isn't read as `>`.
+const OPERATORS: &[(&str, &[Ordering])] = &[
+	(">=", &[Ordering::Greater, Ordering::Equal]),
+	("<=", &[Ordering::Less, Ordering::Equal]),
+	(">", &[Ordering::Greater]),
+	("<", &[Ordering::Less]),
+	("=", &[Ordering::Equal]),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+/// A version requirement, such as `>=1.2, <2`.
+pub struct Requirement {
+	/// The orderings each version must have relative to the other, with the
+	/// components it's compared on.
+	comparisons: Vec<(&'static [Ordering], Vec<u64>)>,
+}
+
+impl Requirement {
+	/// Parses comparisons separated by commas, returning `None` if any is
+	/// malformed. A version without an operator must match exactly.
+	///
+	/// ```
+	/// use cmdlink::version::Requirement;
+	///
+	/// let requirement = Requirement::parse(">=1.2, <2").unwrap();
+	/// assert!(requirement.matches(&[1, 10, 3]));
+	/// assert!(!requirement.matches(&[2, 0]));
+	/// assert!(Requirement::parse("=20").unwrap().matches(&[20, 11, 1]));
+	/// assert!(Requirement::parse("newest").is_none());
+	/// ```
+	pub fn parse(requirement: &str) -> Option<Self> {
+		let comparisons = requirement
+			.split(',')
+			.map(|comparison| {
+				let comparison = comparison.trim();
+				let (operator, orderings) = OPERATORS
+					.iter()
+					.find(|(operator, _)| comparison.starts_with(operator))
+					.copied()
+					.unwrap_or(("", &[Ordering::Equal]));
+				let version = comparison[operator.len()..].trim().trim_start_matches('v');
+				let components = version
+					.split('.')
+					.map(|component| component.parse().ok())
+					.collect::<Option<Vec<u64>>>()?;
+				Some((orderings, components))
+			})
+			.collect::<Option<Vec<_>>>()?;
+		Some(Requirement { comparisons })
+	}
+
+	/// Whether the version satisfies every comparison, missing components
+	/// counting as `0`.
+	pub fn matches(&self, version: &[u64]) -> bool {
+		self.comparisons.iter().all(|(orderings, components)| {
+			let compared = (0..components.len()).map(|i| version.get(i).copied().unwrap_or(0));
+			orderings.contains(&compared.cmp(components.iter().copied()))
+		})
+	}
+}
+
+/// Parses a dotted version string into its numeric components, ignoring any
+/// leading `v` and pre-release or build suffix.
+pub fn parse(version: &str) -> Vec<u64> {
+	version
+		.trim_start_matches(['v', '=', '>'])
+		.split(['-', '+'])
+		.next()
+		.unwrap_or_default()
+		.split('.')
+		.map(|part| part.parse().unwrap_or(0))
+		.collect()
+}
+
+/// The version in the output of a `--version` command: the first number with
+/// dots in it, or else the first number at all.
+///
+/// ```
+/// use cmdlink::version::find;
+///
+/// assert_eq!(find("v20.11.1\n").as_deref(), Some("20.11.1"));
+/// assert_eq!(find("go version go1.22.0 linux/amd64").as_deref(), Some("1.22.0"));
+/// assert_eq!(find("OpenJDK 64-Bit Server VM (build 17.0.2+8)").as_deref(), Some("17.0.2"));
+/// assert_eq!(find("rg 14").as_deref(), Some("14"));
+/// assert_eq!(find("command not found"), None);
+/// ```
+pub fn find(output: &str) -> Option<String> {
+	let digits = |text: &str| text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len());
+	let mut numbers = Vec::new();
+	let mut rest = output;
+	while let Some(start) = rest.find(|c: char| c.is_ascii_digit()) {
+		let number = &rest[start..];
+		let mut end = digits(number);
+		// Take each dot followed by more digits.
+		while number[end..].starts_with('.') && digits(&number[end + 1..]) > 0 {
+			end += 1 + digits(&number[end + 1..]);
+		}
+		numbers.push(&number[..end]);
+		rest = &number[end..];
+	}
+	numbers
+		.iter()
+		.find(|number| number.contains('.'))
+		.or(numbers.first())
+		.map(|number| number.to_string())
+}
+
+/// Runs the command printing the version of an alias' tool, through the shell
+/// links run in, and checks it against the requirement.
+pub fn check(alias: &str, cmd: &str, requirement: &str) -> Result<()> {
+	let fail = |reason: String| Error::ToolVersionUnknown(alias.to_string(), cmd.to_string(), reason);
+	let Some(parsed) = Requirement::parse(requirement) else {
+		return Err(fail(format!("'{requirement}' is not a version requirement")));
+	};
+	#[cfg(target_os = "windows")]
+	let output = {
+		use std::os::windows::process::CommandExt;
+
+		// cmd.exe doesn't follow the quoting rules other programs' arguments
+		// are escaped with.
+		Command::new("cmd").arg("/C").raw_arg(cmd).output()
+	};
+	#[cfg(not(target_os = "windows"))]
+	let output = Command::new("sh").arg("-c").arg(cmd).output();
+	let output = output.map_err(|e| fail(e.to_string()))?;
+	let stderr = String::from_utf8_lossy(&output.stderr);
+	if !output.status.success() {
+		let code = output
+			.status
+			.code()
+			.map_or("a signal".to_string(), |code| format!("code {code}"));
+		return Err(fail(format!("it exited with {code}: {}", stderr.trim())));
+	}
+	// Some tools print their version to stderr, e.g. `java -version`.
+	let printed = format!("{}\n{stderr}", String::from_utf8_lossy(&output.stdout));
+	let Some(found) = find(&printed) else {
+		let printed = printed.trim();
+		return Err(fail(match printed.is_empty() {
+			true => "it printed no version".to_string(),
+			false => format!("it printed no version: {printed}"),
+		}));
+	};
+	debug!("Found version {} with `{}`", found, cmd);
+	if !parsed.matches(&parse(&found)) {
+		let tool = cmd.split_whitespace().next().unwrap_or(cmd);
+		return Err(Error::ToolVersion(
+			alias.to_string(),
+			format!("{tool} {found} found, alias '{alias}' requires {requirement}"),
+		));
+	}
+	Ok(())
+}
@@ -0,0 +1,73 @@
+use std::{
+	path::Path,
+	sync::mpsc::{channel, RecvTimeoutError},
+	time::Duration,
+};
+
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+
+use crate::{config::Config, paths, tr, Result};
+
+/// Watches config.toml and the `bins` directory, regenerating missing or
+/// outdated links whenever either changes. Runs until the process is stopped.
+///
+/// Events are debounced so that editors writing files in several steps only
+/// trigger a single refresh.
+pub fn watch(debounce: Duration) -> Result<()> {
+	let (tx, rx) = channel();
+	let mut watcher = notify::recommended_watcher(tx)?;
+
+	// The project directory is watched instead of config.toml itself, since
+	// many editors save by replacing the file, which would drop a file
+	// watch.
+	watcher.watch(paths::get().root(), RecursiveMode::NonRecursive)?;
+	watcher.watch(&paths::get().default_bins_dir(), RecursiveMode::NonRecursive)?;
+	info!("{}", tr!("watch.watching", path = paths::get().root().display()));
+
+	sync();
+	loop {
+		// Block until something relevant happens.
+		match rx.recv() {
+			Ok(Ok(event)) if is_relevant(&event) => {},
+			Ok(Ok(_)) => continue,
+			Ok(Err(why)) => {
+				warn!("{}", tr!("watch.error", error = why));
+				continue;
+			},
+			Err(_) => return Ok(()),
+		}
+
+		// Then wait for the burst of events to settle before syncing.
+		loop {
+			match rx.recv_timeout(debounce) {
+				Ok(_) => continue,
+				Err(RecvTimeoutError::Timeout) => break,
+				Err(RecvTimeoutError::Disconnected) => return Ok(()),
+			}
+		}
+		sync();
+	}
+}
+
+/// Reloads the config and regenerates links that are missing or outdated.
+/// Errors are logged rather than returned, so that an invalid config while
+/// it's being edited doesn't stop the watcher.
+fn sync() {
+	let result = Config::new().and_then(|mut cfg| cfg.sync_links());
+	match result {
+		Ok(0) => debug!("All command links are up to date"),
+		Ok(count) => info!("{}", tr!("refresh.regenerated", count = count)),
+		Err(why) => error!("{}", tr!("watch.failed", error = why)),
+	}
+}
+
+/// Whether or not the event concerns config.toml or a link file.
+fn is_relevant(event: &Event) -> bool {
+	if matches!(event.kind, EventKind::Access(_)) {
+		return false;
+	}
+	let bins = paths::get().default_bins_dir();
+	event.paths.iter().any(|path| {
+		path.file_name().is_some_and(|name| name == "config.toml") || path.parent() == Some(Path::new(&bins))
+	})
+}
@@ -0,0 +1,42 @@
+use std::{
+	env,
+	path::{Path, PathBuf},
+};
+
+/// Searches the directories in `PATH` for an executable with the given name,
+/// returning the first match.
+///
+/// On Windows, the extensions listed in `PATHEXT` are tried as well.
+pub fn find_executable(name: &str) -> Option<PathBuf> {
+	let path = env::var_os("PATH")?;
+	env::split_paths(&path).find_map(|dir| executable_in(&dir, name))
+}
+
+/// Like [find_executable], but skipping the given directory, e.g. the bins
+/// directory so that an alias named after its program doesn't find itself.
+pub fn find_executable_outside(name: &str, excluded: &Path) -> Option<PathBuf> {
+	let path = env::var_os("PATH")?;
+	env::split_paths(&path)
+		.filter(|dir| dir != excluded)
+		.find_map(|dir| executable_in(&dir, name))
+}
+
+/// Checks for an executable with the given name inside a single directory.
+fn executable_in(dir: &Path, name: &str) -> Option<PathBuf> {
+	#[cfg(target_os = "windows")]
+	{
+		let exts = env::var("PATHEXT").unwrap_or_else(|_| ".COM;.EXE;.BAT;.CMD".to_string());
+		std::iter::once(String::new())
+			.chain(exts.split(';').map(str::to_string))
+			.map(|ext| dir.join(format!("{name}{ext}")))
+			.find(|candidate| candidate.is_file())
+	}
+	#[cfg(not(target_os = "windows"))]
+	{
+		use std::os::unix::fs::PermissionsExt;
+
+		let candidate = dir.join(name);
+		let metadata = candidate.metadata().ok()?;
+		(metadata.is_file() && metadata.permissions().mode() & 0o111 != 0).then_some(candidate)
+	}
+}
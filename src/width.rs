@@ -0,0 +1,178 @@
+//! Display widths of text in the terminal, for laying out tables.
+//!
+//! tabled measures text one character at a time, which doesn't match how
+//! terminals draw emoji sequences: a family joined with zero width joiners
+//! takes up two columns rather than six, and a symbol followed by an emoji
+//! presentation selector, e.g. `❤️`, two rather than one. Tables with such
+//! cells are rendered through [render] so that their columns still line up.
+
+use std::{cell::RefCell, io::IsTerminal};
+
+use tabled::{
+	settings::{object::Segment, Format},
+	Table,
+};
+use unicode_width::UnicodeWidthChar;
+
+/// Joins the emoji before and after it into one.
+const ZERO_WIDTH_JOINER: char = '\u{200D}';
+
+/// Asks for the character before it to be drawn as a colored emoji.
+const EMOJI_PRESENTATION: char = '\u{FE0F}';
+
+/// The private use character the first placeholder starts with, see [render].
+const PLACEHOLDER_START: u32 = 0xE000;
+
+/// The private use character placeholders are padded with.
+const PLACEHOLDER_FILL: char = '\u{F8FF}';
+
+/// The number of terminal columns a line of text takes up.
+///
+/// ```
+/// use cmdlink::width::display_width;
+///
+/// assert_eq!(display_width("gs"), 2);
+/// assert_eq!(display_width("打招呼"), 6);
+/// assert_eq!(display_width("héllo 👋"), 8);
+/// assert_eq!(display_width("👨‍👩‍👧"), 2);
+/// assert_eq!(display_width("👍🏽"), 2);
+/// assert_eq!(display_width("❤️ ✔"), 4);
+/// ```
+pub fn display_width(line: &str) -> usize {
+	let mut width = 0;
+	// The width of the previous character, which an emoji presentation
+	// selector or skin tone modifier applies to.
+	let mut previous = 0;
+	let mut joined = false;
+	for c in line.chars() {
+		match c {
+			ZERO_WIDTH_JOINER => joined = true,
+			EMOJI_PRESENTATION => {
+				if previous == 1 {
+					width += 1;
+					previous = 2;
+				}
+			},
+			'\u{1F3FB}'..='\u{1F3FF}' if previous == 2 => {},
+			_ if joined => joined = false,
+			_ => {
+				previous = c.width().unwrap_or(0);
+				width += previous;
+			},
+		}
+	}
+	width
+}
+
+/// Renders a table with its columns lined up by [display_width].
+///
+/// Lines whose width tabled would measure differently are laid out as
+/// placeholders of their display width, made up of private use characters that
+/// tabled measures one column each, and swapped back once the table is
+/// rendered.
+pub fn render(mut table: Table) -> String {
+	let lines = RefCell::new(Vec::new());
+	table.modify(
+		Segment::all(),
+		Format::content(|cell| {
+			if cell.lines().all(|line| !needs_placeholder(line)) {
+				return cell.to_string();
+			}
+			let mut lines = lines.borrow_mut();
+			cell.lines()
+				.map(|line| match placeholder(line, lines.len()) {
+					Some(token) if needs_placeholder(line) => {
+						lines.push((token.clone(), line.to_string()));
+						token
+					},
+					_ => line.to_string(),
+				})
+				.collect::<Vec<_>>()
+				.join("\n")
+		}),
+	);
+
+	let mut rendered = table.to_string();
+	for (token, line) in lines.into_inner() {
+		rendered = rendered.replacen(&token, &line, 1);
+	}
+	rendered
+}
+
+/// The number of columns of the terminal stdout is written to, or `None` if
+/// stdout isn't a terminal. The `COLUMNS` environment variable takes precedence
+/// when set.
+pub fn terminal_width() -> Option<usize> {
+	if !std::io::stdout().is_terminal() {
+		return None;
+	}
+	std::env::var("COLUMNS")
+		.ok()
+		.and_then(|columns| columns.parse().ok())
+		.filter(|columns| *columns > 0)
+		.or_else(console_width)
+}
+
+#[cfg(target_family = "unix")]
+/// The number of columns of the terminal stdout is attached to.
+fn console_width() -> Option<usize> {
+	// SAFETY: winsize is plain data, for which all zeroes are valid, and is
+	// what TIOCGWINSZ writes to.
+	let mut size: libc::winsize = unsafe { std::mem::zeroed() };
+	let result = unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, &mut size) };
+	(result == 0 && size.ws_col > 0).then_some(usize::from(size.ws_col))
+}
+
+#[cfg(target_os = "windows")]
+/// The number of columns of the console window stdout is attached to.
+fn console_width() -> Option<usize> {
+	use windows_sys::Win32::System::Console::{
+		GetConsoleScreenBufferInfo, GetStdHandle, CONSOLE_SCREEN_BUFFER_INFO, STD_OUTPUT_HANDLE,
+	};
+
+	// SAFETY: the buffer info is plain data, for which all zeroes are valid,
+	// and the standard output handle isn't owned, so needn't be closed.
+	let mut info: CONSOLE_SCREEN_BUFFER_INFO = unsafe { std::mem::zeroed() };
+	let result = unsafe { GetConsoleScreenBufferInfo(GetStdHandle(STD_OUTPUT_HANDLE), &mut info) };
+	let columns = info.srWindow.Right - info.srWindow.Left + 1;
+	(result != 0 && columns > 0).then_some(columns as usize)
+}
+
+/// Whether tabled would measure the line differently from the terminal.
+fn needs_placeholder(line: &str) -> bool {
+	display_width(line) != line.chars().map(|c| c.width().unwrap_or(0)).sum::<usize>()
+}
+
+/// The `index`th placeholder for a line, as wide as the line is displayed, or
+/// `None` if the line takes up no columns or the private use area runs out.
+fn placeholder(line: &str, index: usize) -> Option<String> {
+	let width = display_width(line);
+	let marker =
+		char::from_u32(PLACEHOLDER_START + u32::try_from(index).ok()?).filter(|marker| *marker < PLACEHOLDER_FILL)?;
+	(width > 0).then(|| {
+		std::iter::once(marker)
+			.chain(std::iter::repeat_n(PLACEHOLDER_FILL, width - 1))
+			.collect()
+	})
+}
+
+#[cfg(test)]
+mod tests {
+	use tabled::builder::Builder;
+
+	use super::*;
+
+	#[test]
+	fn render_lines_up_wide_cells() {
+		let mut builder = Builder::default();
+		builder.push_record(["Alias", "Status", "Description"]);
+		builder.push_record(["hi", "✔", "打招呼"]);
+		builder.push_record(["fam", "👨‍👩‍👧", "family"]);
+		builder.push_record(["love", "❤️", "❤️ and 👨‍👩‍👧"]);
+		let rendered = render(builder.build());
+
+		let widths: Vec<usize> = rendered.lines().map(display_width).collect();
+		assert!(widths.iter().all(|width| *width == widths[0]), "{rendered}");
+		assert!(rendered.contains("打招呼") && rendered.contains("👨‍👩‍👧") && rendered.contains("❤️"));
+	}
+}